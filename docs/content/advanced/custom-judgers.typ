@@ -14,7 +14,7 @@ Hull includes `batch`, `stdioInteraction`, and `answerOnly`. A problem can also
 == Built-in Runtime Models
 
 - `batch` gives the contestant only stdin, stdout, and stderr. It does not predeclare writable contestant files or treat a fixed file as stdout.
-- `stdioInteraction` runs the contestant and interactor in one deterministic session. Two bounded 64 KiB pipes connect their standard streams. A connected protocol deadlock produces `time_limit_exceeded` and is recorded in the session report.
+- `stdioInteraction` runs the contestant and interactor in one deterministic session. Two bounded 64 KiB pipes connect their standard streams. A connected protocol deadlock produces `time_limit_exceeded` and is recorded in the session report. When the interactor exits, a contestant that is still running is stopped and judged by the interactor's report.
 - `answerOnly` evaluates submitted files without executing a contestant program.
 
 For both executing models, the contestant receives the test case's tick, memory, and file-size limits. The tick limit bounds executed work, while the memory limit bounds WASM linear memory and the execution stack. `fileSizeLimit` bounds each contestant-controlled regular file or pipe independently. In `batch`, it bounds stdout and stderr. In `stdioInteraction`, it bounds the contestant-to-interactor pipe and contestant stderr. The interactor is a trusted problem component and uses Hull's tool limits.
@@ -74,6 +74,12 @@ Basic skeleton:
             arguments = [ ];
             tick_limit = hull.runWasm.dynamicNumber "HULL_TICK_LIMIT";
             memory_limit = hull.runWasm.dynamicNumber "HULL_MEMORY_LIMIT";
+            stack_limit = hull.runWasm.dynamicNumber "HULL_STACK_LIMIT";
+            random_seed = hull.runWasm.dynamicNumber "HULL_RANDOM_SEED";
+            io_cost = {
+              ticks_per_call = hull.runWasm.dynamicNumber "HULL_IO_TICKS_PER_CALL";
+              ticks_per_byte = hull.runWasm.dynamicNumber "HULL_IO_TICKS_PER_BYTE";
+            };
             inherit required_accepted;
             file_system = {
               directories = [
@@ -195,6 +201,10 @@ The top-level request fields are:
   [`programs`],
   [nonempty array],
   [Programs in deterministic request and scheduling order.],
+
+  [`terminate_on_exit`],
+  [string array],
+  [Optional program names whose exit stops every other running program.],
 )
 
 Every program object has these required fields:
@@ -211,7 +221,7 @@ Every program object has these required fields:
 
   [`memory_limit`],
   [integer or `"tool"`],
-  [Linear-memory and guest-stack byte ceiling.],
+  [Linear-memory byte ceiling.],
 
   [`required_accepted`],
   [boolean],
@@ -222,6 +232,24 @@ Every program object has these required fields:
   [`initial_descriptors`], [array], [Initial fd 0, 1, 2, then fd 4 and above.],
 )
 
+The optional string map `environment` sets the variables returned by `environ_get`, for example `environment = { ONLINE_JUDGE = "1"; };`. Names must be nonempty and must not contain `=`. Programs see an empty environment by default.
+
+The optional integer `stack_limit` sets the guest execution-stack byte ceiling. It defaults to `memory_limit`. Exhausting the stack produces `runtime_error` with the message `Stack limit exceeded`.
+
+The optional boolean `coredump` asks for a Wasm core dump when the program ends in `runtime_error`. It defaults to `false`. The dump is written beside the report as `REPORT.NAME.coredump`, where `REPORT` is the report's file name and `NAME` is the program name, and it can be opened with Wasm debuggers such as `wasmgdb`. Programs that request a core dump need names that are valid file name components.
+
+The optional boolean `backtrace` asks for a symbolized guest backtrace when the program ends in `runtime_error`. It defaults to `false`. Reading DWARF debug information makes compilation slower, so request it only for the programs being judged. Built-in judgers request it for solutions.
+
+The optional array `features` lists Wasm proposals the program may use beyond Hull's strict MVP feature set. Accepted names are `bulk_memory`, `multi_value`, `mutable_global`, `saturating_float_to_int`, `sign_extension`, `simd`, and `tail_call`. It defaults to `[ ]`, and a module that uses a proposal outside the list fails to compile, which reports `internal_error`. Threads and relaxed SIMD are never accepted because their results are not deterministic. Built-in judgers pass the problem's `wasmFeatures` to solutions.
+
+`wasm_path` may also name a WASI Preview 2 component that targets the `wasi:cli/command@0.2.0` world instead of a core module with a `_start` export. Hull provides the `wasi:io`, `wasi:clocks`, `wasi:random`, `wasi:cli`, and `wasi:filesystem` interfaces of that world and serves them from the same files, pipes, clock, random stream, and tick and memory accounting as Preview1 modules. Sockets are not provided, so components that import them fail to instantiate with `internal_error`. Standard streams are fd 0, 1, and 2, and `wasi:filesystem/preopens` lists the declared directories. Initial descriptors at fd 4 and above are not reachable from a component. A `run` export that returns an error ends the program with exit code `1`.
+
+The optional integer `random_seed` selects the deterministic byte stream returned by `random_get`, which backs `std::random_device`. It defaults to `0`. Pass `HULL_RANDOM_SEED` to solutions so that randomized solutions see different but reproducible bytes on each test case.
+
+The optional object `clock` selects what the realtime and monotonic clocks return. `{ kind = "frozen"; }` is the default, and both clocks always read zero. `{ kind = "ticks"; ticks_per_second = N; }` starts both clocks at zero and advances them by one second per `N` consumed ticks, so a program that loops until a time budget runs out stops after a deterministic number of ticks. Clock waits in `poll_oneoff` do not advance the clock.
+
+The optional object `io_cost` charges ticks for the host work behind I/O, which otherwise costs only the guest instructions around each call. `{ ticks_per_call = C; ticks_per_byte = B; }` charges `C + B * N` ticks for every `fd_read`, `fd_pread`, `fd_write`, or `fd_pwrite` call that transfers `N` bytes. Failed calls cost `C`. Both fields default to `0`, so I/O is free unless requested. Charges count toward `tick_limit`, so one large write can end the program with `time_limit_exceeded`.
+
 Relative `report_path`, `host_path`, and `wasm_path` values resolve from the generated request file's directory. Use `hull.runWasm.dynamicString "ENVIRONMENT_NAME"` or `dynamicNumber` when a value must be substituted from the runner environment at execution time. Ordinary Nix strings and numbers are fixed while evaluating the derivation.
 
 === Session Files
@@ -259,6 +287,14 @@ A pipe has this shape:
 
 `capacity` is the number of bytes that can be buffered before a writer blocks. `size_limit` instead bounds cumulative successful writes across the stream. Consuming buffered bytes does not restore that allowance. Every pipe must have exactly one read endpoint and one write endpoint across the session.
 
+The optional `transcript_path` records the conversation through a pipe. Hull writes one JSON object per successful write to that host path as the session runs, in write order, and flushes it whatever the session's outcome:
+
+```json
+{"program":"solution","tick":1523,"text":"? 3 5\n"}
+```
+
+`tick` is the writer's consumed ticks when it made the write. `text` is the written bytes decoded as UTF-8, with invalid sequences replaced by U+FFFD. Writes that are not valid UTF-8 also carry `hex`, their exact bytes in lowercase hexadecimal. Set it on both pipes of an interaction to see the exact exchange that led to a wrong answer or a `Protocol deadlock`. Transcript paths follow the same overlap rules as `report_path`.
+
 Exceeding a regular file or pipe `size_limit`, or starting with an oversized referenced regular-file snapshot, produces `file_error`. Verdict precedence is `memory_limit_exceeded`, then `file_error`, then `time_limit_exceeded`, then runtime or exit semantics.
 
 === Initial Descriptors And Filesystem
@@ -271,13 +307,42 @@ A descriptor with `file = null` has null-device behavior: reads return EOF and w
 
 The fd 3 root preopen is always named `.`. `directories` declares the complete guest directory tree and must include that root. Directory `permissions` uses the Unix directory permission subset `0` for none, `1` for execute, `4` for read, or `5` for read-execute. `bindings` maps guest paths to regular files and grants `permissions` as a subset of each file's `max_permissions`. Directory permissions do not authorize undeclared files or directories.
 
-Guest paths use `/`, are relative, and must be normalized. No leading or trailing slash, empty component, `.` component, or `..` component is allowed. The root itself is exactly `.`. Every parent directory must be explicitly declared. No two directory or regular-file bindings can occupy the same path.
+Guest paths use `/`, are relative, and must be normalized. No leading or trailing slash, empty component, `.` component, or `..` component is allowed. The root itself is exactly `.`. Every parent directory must be explicitly declared. No two directory, regular-file, symlink, or scratch entries can occupy the same path.
+
+Several bindings of one file are hard links. They share an inode, and `nlink` counts them.
+
+The optional `symlinks` list in `file_system` declares symbolic links for layouts that expect them:
+
+```nix
+symlinks = [
+  {
+    path = "task/data";
+    target = "../shared/data";
+  }
+];
+```
+
+`path` follows the guest path rules. `target` must be nonempty, relative, and free of NUL bytes. It resolves from the link's parent directory. Unlike a guest path, a target can contain `..`, but it can never climb above `.`. Hull follows links in intermediate path components. It follows a final-component link when the call asks for it, as `open` and `stat` do. A path that passes through more than 40 links fails with `ELOOP`. `readlink` returns the target. Links are read-only.
+
+The optional `scratch` list in `file_system` declares private writable directories:
+
+```nix
+scratch = [
+  {
+    path = "tmp";
+    size_limit = 16 * 1024 * 1024;
+    file_limit = 64;
+  }
+];
+```
+
+A scratch directory starts empty. Inside it, the program can create, write, rename, and delete files, and create and remove subdirectories. Nothing can be declared inside it, and its parent must be a declared directory. `size_limit` bounds the total logical size of its files, and `file_limit` bounds how many files and subdirectories it holds at once. Exceeding either makes the call fail with `EDQUOT` instead of ending the program. A deleted file still counts toward `size_limit` until its last descriptor is closed. Renames cannot move entries into or out of a scratch directory. Hard links can be created only between files in the same scratch directory. Scratch contents live in memory, are private to the program, and are discarded when the session ends.
 
 === Ownership And Host-Path Invariants
 
 File names and program names must be nonempty and unique. One regular file can have several writable descriptors or path aliases inside one program. It cannot be writable by multiple programs. Every pipe has exactly one read-capable endpoint and one write-capable endpoint across all initial descriptors.
 
-Host paths must not contain `..`. Hull resolves symlinks through the longest existing ancestor before checking overlap. `report_path`, distinct `wasm_path` values, and different mapped regular files must not be equal, contain one another, or alias through a symlink. This prevents a report, module, input, or output from replacing or exposing another session artifact.
+Host paths must not contain `..`. Hull resolves symlinks through the longest existing ancestor before checking overlap. `report_path`, requested core dump paths, distinct `wasm_path` values, and different mapped regular files must not be equal, contain one another, or alias through a symlink. This prevents a report, module, input, or output from replacing or exposing another session artifact.
 
 === Deterministic Time And Deadlocks
 
@@ -285,6 +350,37 @@ WASIp1 realtime and monotonic clocks are fixed at zero. A zero-time `poll_oneoff
 
 When all runnable progress stops, Hull reports `time_limit_exceeded` with the diagnostic `Protocol deadlock`. `deadlocks` records each minimal connected component using request-order `programs` and `pipes`. A pure nonzero clock wait has an empty `pipes` list.
 
+Each deadlock also explains the wait:
+
+```json
+{
+  "programs": ["solution", "interactor"],
+  "pipes": ["to_solution", "to_interactor"],
+  "blocked": [
+    {
+      "program": "solution",
+      "hostcall": "fd_read",
+      "descriptors": [{ "fd": 0, "file": "to_solution" }],
+      "tick": 18250
+    },
+    {
+      "program": "interactor",
+      "hostcall": "poll_oneoff",
+      "descriptors": [{ "fd": 0, "file": "to_interactor" }],
+      "tick": 9411
+    }
+  ],
+  "buffered": [
+    { "pipe": "to_solution", "bytes": 0 },
+    { "pipe": "to_interactor", "bytes": 0 }
+  ]
+}
+```
+
+`blocked` follows `programs` order. `hostcall` is `fd_read`, `fd_write`, or `poll_oneoff`. `descriptors` lists the fds the call waited on, with the session file behind each one or `null`. Clock subscriptions are omitted. `tick` is the ticks the program had consumed when it blocked. `buffered` follows `pipes` order and counts the bytes written to each pipe but not yet read. A full pipe next to a blocked `fd_write` means the reader stopped reading. Two blocked `fd_read` calls on empty pipes mean both sides are waiting for the other to speak first.
+
+When a program named in `terminate_on_exit` finishes for any reason, Hull stops every program still running and reports them as `terminated` with `tick = 0`. No deadlock is recorded for them. List the interactor here so that a contestant still reading or computing after the interactor has given its verdict is not reported as `time_limit_exceeded`. A judger decides what `terminated` means for the verdict. Programs that finished earlier keep their own results.
+
 === Session Report
 
 The JSON report at `report_path` contains one result per program in request order and a `deadlocks` array:
@@ -297,15 +393,45 @@ The JSON report at `report_path` contains one result per program in request orde
       "status": "accepted",
       "tick": 123,
       "memory": 65536,
+      "stack": 4096,
       "exit_code": 0,
-      "error_message": null
+      "error_message": null,
+      "files": [
+        {
+          "file": "input",
+          "bytes_read": 4096,
+          "bytes_written": 0,
+          "reads": 2,
+          "writes": 0,
+          "size": 4096
+        }
+      ],
+      "memory_timeline": [
+        { "tick": 0, "memory": 65536 }
+      ]
     }
   ],
   "deadlocks": []
 }
 ```
 
-Runner statuses are `accepted`, `runtime_error`, `time_limit_exceeded`, `memory_limit_exceeded`, `file_error`, and `internal_error`. `required_accepted = true` makes a non-accepted program result fail the generated shell command after the report has been written.
+`memory` is the peak requested linear memory. `stack` is the peak execution-stack use in bytes, including the host frames beneath the guest. It is approximate: it is read from the lowest touched stack page, so a deepest frame that wrote only zeros can be undercounted by less than one page. Host frames that run before and after the guest can also move it slightly between identical runs, so compare it with a margin. Stack pages never use transparent huge pages, so a touch counts only its own page.
+
+`memory_timeline` lists the linear-memory growths that raised the peak, oldest first. Each entry has the requested `memory` in bytes and the `tick` count the program had reached when it requested the growth, counted up to its latest function call or return. The first entry is the module's initial memory at tick `0`, and the last entry always equals `memory`. Long timelines are thinned by dropping every second entry, which keeps the first growth and the overall shape. A peak reached by one early allocation shows few entries near tick `0`, while gradual growth shows entries spread across the run.
+
+`files` has one entry for each session file the program references through a descriptor or binding, in request order. `bytes_read` and `bytes_written` count bytes moved by the program's successful reads and writes, and `reads` and `writes` count those hostcalls. A read that reports end of file still counts. `size` is the final logical size of a regular file, or the total bytes written to a pipe by all programs. For example, `reads` close to `bytes_read` means the program reads byte by byte. A checker whose `bytes_read` is below `size` never reached the end of that file.
+
+For a `runtime_error` of a program that requested `backtrace`, the result's `backtrace` lists the guest frames innermost first. The field is absent when it would be empty. Each frame has a `function` named from the WASM name section, and `file`, `line`, and `column` from DWARF debug information when the module carries it. Calls inlined by the compiler appear as their own frames when DWARF describes them. Small functions that Wasmtime inlines without DWARF are folded into their callers. `error_message` keeps only the trap or exit description.
+
+When the program requested `coredump`, a `runtime_error` result also has `coredump_path`, the host path of the written core dump. The field is absent otherwise, including when writing the dump failed.
+
+Runner statuses are `accepted`, `runtime_error`, `time_limit_exceeded`, `memory_limit_exceeded`, `terminated`, `file_error`, and `internal_error`. `required_accepted = true` makes a non-accepted program result fail the generated shell command after the report has been written.
+
+=== Session Server
+
+`hull run-wasm --serve` executes many sessions in one process and keeps engines and compiled modules loaded between them. It reads one request object per line from stdin and writes one report per line to stdout, in request order. Relative paths resolve from the server's working directory, and reports are not written to `report_path`. A line that is not a valid request is answered with `{"error": "..."}`. Add `--socket PATH` to serve each connection to a Unix socket in the same way instead. Connections are served concurrently, at most `-j N` at a time. `N` defaults to the number of available CPUs. Further connections wait until a worker is free.
+
+When `HULL_RUN_WASM_SOCKET` names such a socket, `hull run-wasm REQUEST` runs its request on that server and then writes the report and checks `required_accepted` as usual. During runtime analysis, Hull starts a server inside its own process and sets this variable for judger runners, so `hull.runWasm.script` sessions reuse the modules Hull has already loaded.
 
 == `prepareSolution`
 
@@ -354,11 +480,15 @@ Environment variables:
 - `HULL_SOLUTION_NAME`: the solution name.
 - `HULL_INPUT_PATH`: the input file for this test case.
 - `HULL_TICK_LIMIT`: tick limit for this test case.
-- `HULL_MEMORY_LIMIT`: WASM linear-memory and execution-stack limit in bytes for this test case.
+- `HULL_MEMORY_LIMIT`: WASM linear-memory limit in bytes for this test case.
+- `HULL_STACK_LIMIT`: execution-stack limit in bytes for this test case, which is its `stackLimit` or else `HULL_MEMORY_LIMIT`.
+- `HULL_IO_TICKS_PER_CALL` and `HULL_IO_TICKS_PER_BYTE`: the test case's `ioCost`, to pass to the solution as its `io_cost`.
+- `HULL_RANDOM_SEED`: the test case's `randomSeed`, or the first 13 hex digits of the SHA-256 of its name read as an integer.
 - `HULL_FILE_SIZE_LIMIT`: byte limit for each contestant-controlled regular file or pipe.
 - `HULL_SOLUTION_SRC`: source path returned by `prepareSolution`, or the original solution source.
 - `HULL_SOLUTION_EXECUTABLE`: executable path returned by `prepareSolution` when present.
 - `HULL_OUTPUTS_DIR`: directory where the runner must place generated outputs.
+- `HULL_RUN_WASM_SOCKET`: Hull's session server socket, used by `hull run-wasm` when set. Runners built outside runtime analysis do not receive it.
 
 `HULL_REPORT_PATH` is unset in `generateOutputs` mode.
 
@@ -385,10 +515,13 @@ Report format:
   "score": 1.0,
   "message": "",
   "tick": 12345,
-  "memory": 1048576
+  "memory": 1048576,
+  "backtrace": []
 }
 ```
 
+`backtrace` is optional. Hull-owned judgers copy the contestant's session-report `backtrace` so `hull judge --json` and packaged reports can point at the crashing source line.
+
 `status` must be one of `accepted`, `wrong_answer`, `partially_correct`, `runtime_error`, `time_limit_exceeded`, `memory_limit_exceeded`, `file_error`, or `internal_error`. Preserve runtime limit statuses. Do not convert them to a generic runtime error inside a Hull-owned judger.
 
 Additional variable in `judge` mode:
@@ -31,6 +31,10 @@ hull build
 
 The default output link is `result`. Use `-p` to select a problem. Use `-t` to select a target. Use `-o` to select the output link. Use `-j` to set runtime analysis parallelism. Arguments after `--` are passed to the final `nix build`.
 
+Runtime analysis reuses reports from earlier runs. A judge report is cached under a hash of the judger runner, the prepared solution, the test input, the official outputs, and the test limits. Validator and checker reports are keyed by the program and the files they read. After editing one solution, only that solution is judged again. Hull generates official outputs on every run, so a changed main correct solution still invalidates every judge report whose outputs changed. The cache lives in `$XDG_CACHE_HOME/hull/results` (or `~/.cache/hull/results`); set `HULL_RESULT_CACHE_DIR` to move it. Reports are also keyed by the Hull executable, so upgrading Hull starts over. Internal errors are never cached. Use `--no-cache` to rerun every step. `hull build-contest` and `hull judge` accept the same flag.
+
+The cache is never pruned automatically. Every reused report counts as a use. `hull cache clean` removes every cached report, and `hull cache clean --older-than 30` only removes reports no run has used in the last 30 days. It only deletes the entry directories Hull created, so other files in the cache directory are kept.
+
 The output layout depends on the selected target. A common target usually contains:
 
 - `data/`: Contains all test case inputs and the corresponding standard answer files.
@@ -52,6 +56,46 @@ hull judge solution/std.23.cpp
 
 Use `--json` to print JSON instead of a table.
 
+Use `--test <glob>` to judge only test cases whose name matches the glob, where `*` and `?` are wildcards. Use `--subtask <index>` to judge only test cases of that subtask, counting from 0. Both flags may be repeated. When both are given, a test case must pass both filters. Test cases excluded by `--test` are neither generated nor validated. `--subtask` can only skip judging: subtask membership comes from validator traits, so Hull still generates and validates every test case that matches the name filters. A test case that `--test` skipped counts as skipped in every subtask it may belong to, unless its `traitHints` rule that subtask out. Skipped test cases count toward no score. The report lists them, and each subtask shows how many of its test cases were skipped. `hull build` accepts the same flags. With a filter it prints one report per solution and does not package a target, because a package needs every test case.
+
+```bash
+hull judge solution/wa.cpp --subtask 4
+hull judge solution/wa.cpp --test 'large-*'
+```
+
+== Choosing Limits
+
+`hull limits` helps you choose `tickLimit`. It analyzes one problem and reports, per subtask:
+
+- the peak ticks and memory of the main correct solution and of every solution predicted to pass;
+- the ratio of those peaks to the configured limits;
+- the fastest solution predicted to exceed the tick limit.
+
+```bash
+hull limits -p aPlusB
+```
+
+A solution is predicted to pass a subtask when its `subtaskPredictions` entry accepts a fully accepted result but not a `time_limit_exceeded` one. It is predicted to exceed the limit in the opposite case. During measurement, every tick limit is raised by `--tick-headroom` (4 by default), so slow solutions show how far they exceed the limit. A value marked `>=` hit even the raised limit.
+
+Hull then suggests a single tick limit that lets every intended run pass while every unintended run still exceeds it. It warns when that window is thin or empty. It also warns when an intended solution is close to the configured limit and when an unintended one is barely above it. Use `--json` to print the report as JSON.
+
+== Curating Test Cases
+
+`hull test-matrix` analyzes one problem. It shows which test cases reject which wrong solutions. A wrong solution is one that fails at least one test case. Internal errors do not count as rejections.
+
+```bash
+hull test-matrix -p aPlusB
+```
+
+The report has four parts:
+
+- A matrix of the test cases that reject something, with each wrong solution's verdict.
+- The test cases that reject no solution.
+- A minimal set of test cases that still rejects every wrong solution. The set is built greedily, and then every test case that the others make redundant is dropped. It is irredundant but not always the smallest possible.
+- The wrong solutions that only one test case rejects. Keep those test cases when you prune.
+
+Use `--json` to print the report as JSON.
+
 == Compiling a Solution
 
 `hull compile` compiles one source file to a WebAssembly executable using the selected problem's `solutionLanguages` and `solutionIncludes`.
@@ -88,7 +132,7 @@ hull run -p example -l cpp.23 solution/std.23.cpp
 
 Use `--role authoring` to run an authoring program with the authoring-side languages and includes.
 
-Arguments after the source path are passed to the program. Prefix arguments that start with `-` with a `--` separator. Use `--tick-limit`, `--memory-limit`, and `--file-size-limit` to override the tick, memory, and independent stdout/stderr byte limits. Omitted local limits use Hull's tool ceilings. Use `--show-status` to print the snake_case execution status, tick use, and peak linear memory. Standard input, output, and error remain connected to the invoking terminal or redirections.
+Arguments after the source path are passed to the program. Prefix arguments that start with `-` with a `--` separator. Use `--tick-limit`, `--memory-limit`, and `--file-size-limit` to override the tick, memory, and independent stdout/stderr byte limits. Omitted local limits use Hull's tool ceilings. Use `--stack-limit` to give the stack a separate ceiling, which otherwise equals the memory limit. Use `--show-status` to print the snake_case execution status, tick use, peak linear memory, and peak stack. Use `--env KEY=VALUE`, which may be repeated, to set the program's environment variables. The program sees no host environment variables. Use `--seed` to change the deterministic bytes returned by `random_get`, which defaults to seed `0`. Clocks read zero by default. Use `--ticks-per-second` to make them advance one second per that many consumed ticks. Standard input, output, and error remain connected to the invoking terminal or redirections.
 
 The program receives capability-scoped access to its working directory and cannot access host paths outside it. The directory defaults to where Hull was started. Use `--cwd` to select another host directory:
 
@@ -96,6 +140,27 @@ The program receives capability-scoped access to its working directory and canno
 hull run --cwd sandbox solution/std.23.cpp
 ```
 
+== Profiling a Solution
+
+`hull profile` compiles and runs one source file like `hull run`. It attributes consumed ticks to guest call stacks and writes them in the collapsed-stack format read by `flamegraph.pl` and `inferno-flamegraph`:
+
+```bash
+hull profile -o std.folded solution/std.23.cpp < input.txt
+flamegraph.pl std.folded > std.svg
+```
+
+Frames are named from the WASM name section, and DWARF debug information adds inlined source functions. A stack is sampled at the first function entry or loop header after `--sample-interval` further ticks, which defaults to 10000. Smaller intervals are more precise but slower. Ticks spent after the last sample are reported under `[unsampled]`. The command also prints the status, total tick use, and the functions with the most self ticks. Use `--top` to change how many are listed.
+
+== Inspecting a WASM File
+
+`hull inspect-wasm` loads one source WASM module or component with the same rules as judged programs and reports why it would or would not start:
+
+```bash
+hull inspect-wasm solution.wasm
+```
+
+The report lists each import and whether Hull's WASI implementation provides it, the WASM proposals the file needs beyond Hull's default feature set, each linear memory's minimum, maximum, and page size, the exports, and the size of the function bodies. Proposals that a problem can enable through `wasmFeatures` are marked as needing `--wasm-feature`, which may be repeated to inspect the file under that feature set. `Accepted: no` is followed by the error a judge run would report as `internal_error`, such as an unknown import or a missing `_start` export. Pass the problem's stack limit with `--stack-limit` to load the file exactly as a judge run would. Without it, the file is loaded with the 4 GiB stack limit that `hull run` uses by default. Use `--json` to print the report as JSON. Text WAT files are accepted too.
+
 == Stress Testing
 
 `hull stress` runs a generator repeatedly. It builds temporary test cases. It compares one or more solutions against the standard solution. It stops on the first non-accepted result.
@@ -106,4 +171,4 @@ For example, imagine you have a potentially buggy solution `wa` and a generator
 hull stress --generator rand wa -- some parameters passed --to=generator
 ```
 
-Use `-j` to control parallel cases per round. Use `-r` to set a finite number of rounds.
+Use `-j` to control parallel cases per round. Use `-r` to set a finite number of rounds. Each generated case gives solutions a `random_get` seed derived from its name. Use `--seed` to give every case the same seed instead. The printed `problem.nix` snippet includes the `randomSeed` of the failing case, so the test case reproduces the failure.
@@ -161,6 +161,8 @@ The complete testcase status vocabulary is `accepted`, `wrong_answer`, `partiall
 }
 ```
 
+`hull build` checks each prediction as soon as every testcase of its subtask has been judged. It does not wait for the whole analysis to finish. Subtask membership comes from validator traits, so a testcase that has not been validated yet holds back every subtask it could belong to. Its `traitHints` rule it out of subtasks that require a different trait value, which lets those subtasks complete earlier. After the analysis, it prints a table with each solution's raw score and statuses per predicted subtask, marking each prediction as met or violated. A violated prediction fails the build before packaging. With `--stop-on-failure`, the first violation also cancels the remaining judging.
+
 == Code Style
 
 A consistent code style is essential for collaboration and long-term maintenance. The Hull template provides configuration files for common formatting and linting tools.
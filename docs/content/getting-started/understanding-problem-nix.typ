@@ -29,7 +29,10 @@ These options define the fundamental properties of your problem.
 - `displayName`: An attribute set containing human-readable titles for the problem in different languages. The keys are language codes (for example, `en`, `zh`).
 - `tickLimit`: The default execution time limit for solutions, measured in "ticks". A common starting point is `1000 * 10000000` ticks. This roughly corresponds to 1 second of execution time in the WASM runtime.
 - `memoryLimit`: The default memory limit for solutions, measured in bytes.
+- `stackLimit`: Optional. The default stack limit for solutions, measured in bytes. Use it to restrict recursion depth independently of memory, for example `8 * 1024 * 1024` with a 512 MiB memory limit. When unset, the stack limit equals the memory limit.
 - `fileSizeLimit`: The byte ceiling applied independently to each contestant-controlled regular file or pipe. It defaults to 1 GiB. A regular file is limited by its logical length, including initial contents. A pipe is limited by cumulative successful writes. Exceeding a governed file produces `file_error`.
+- `ioCost`: Optional. Ticks that built-in judgers charge solutions for read and write calls, given as `{ ticksPerCall = C; ticksPerByte = B; }`. A call that transfers `N` bytes costs `C + B * N` ticks on top of the guest instructions around it. Both fields default to `0`, so I/O is free unless you set them. Raise them to make a solution that reads its input one byte per call slower than one that reads it in large blocks.
+- `ticksPerSecond`: Optional. When set, built-in judgers let solutions read a virtual clock that advances one second per this many consumed ticks, for example `10000000000` to match the `tickLimit` guideline above. Time-budgeted heuristics such as simulated annealing can then stop on the clock, and their results stay deterministic. When unset, every clock reading is zero.
 
 == Programs, Solutions, And Authoring
 
@@ -153,6 +156,8 @@ Each attribute in `testCases` defines a test case. The name of the attribute (fo
 - `generator`: Use this to specify the name of a generator (from the `generators` set) to create the input file.
   - `arguments`: A list of command-line arguments to pass to the generator. This allows you to create many different test cases from a single generator program.
 - `groups`: A list of strings to categorize the test case. The group `"sample"` is special. It indicates that the test case must be treated as a sample for problem statements.
+- `tickLimit`, `memoryLimit`, `stackLimit`, and `ioCost`: Override the problem's limits and I/O costs for this test case.
+- `randomSeed`: The seed of the deterministic `random_get` bytes that the built-in judgers give solutions on this test case. It defaults to a value derived from the test case name, so randomized solutions see different bytes on each test case.
 
 == Subtasks & Scoring
 
@@ -254,6 +254,8 @@ assert lib.assertMsg (
               name = problem.config.name;
               tick_limit = problem.config.tickLimit;
               memory_limit = problem.config.memoryLimit;
+              stack_limit = problem.config.stackLimit;
+              io_cost = hull.runWasm.serializeIoCost problem.config.ioCost;
               file_size_limit = problem.config.fileSizeLimit;
               full_score = problem.config.fullScore;
               judger = {
@@ -275,6 +277,9 @@ assert lib.assertMsg (
                 name = tc.name;
                 tick_limit = tc.tickLimit;
                 memory_limit = tc.memoryLimit;
+                stack_limit = tc.stackLimit;
+                io_cost = hull.runWasm.serializeIoCost tc.ioCost;
+                random_seed = tc.randomSeed;
                 groups = tc.groups;
                 trait_hints = tc.traitHints;
               }) problem.config.samples;
@@ -62,6 +62,22 @@ let
         arguments = [ ];
         tick_limit = dynamicNumber "HULL_TICK_LIMIT";
         memory_limit = dynamicNumber "HULL_MEMORY_LIMIT";
+        stack_limit = dynamicNumber "HULL_STACK_LIMIT";
+        random_seed = dynamicNumber "HULL_RANDOM_SEED";
+        io_cost = {
+          ticks_per_call = dynamicNumber "HULL_IO_TICKS_PER_CALL";
+          ticks_per_byte = dynamicNumber "HULL_IO_TICKS_PER_BYTE";
+        };
+        clock =
+          if problem.ticksPerSecond == null then
+            { kind = "frozen"; }
+          else
+            {
+              kind = "ticks";
+              ticks_per_second = problem.ticksPerSecond;
+            };
+        features = problem.wasmFeatures;
+        backtrace = true;
         inherit required_accepted;
         file_system = {
           directories = [
@@ -171,6 +187,7 @@ in
 
         tick=$(jq '.results[] | select(.program == "solution") | .tick' report.json)
         memory=$(jq '.results[] | select(.program == "solution") | .memory' report.json)
+        backtrace=$(jq -c '.results[] | select(.program == "solution") | .backtrace // []' report.json)
         final_message=$(jq -r '.results[] | select(.program == "solution") | .error_message // ""' report.json)
         final_status="$run_status"
         final_score=0.0
@@ -198,12 +215,14 @@ in
           --arg message "$final_message" \
           --argjson tick "$tick" \
           --argjson memory "$memory" \
+          --argjson backtrace "$backtrace" \
           '{
             status: $status,
             score: $score,
             message: $message,
             tick: $tick,
-            memory: $memory
+            memory: $memory,
+            backtrace: $backtrace
           }' > "$HULL_REPORT_PATH"
       '';
   };
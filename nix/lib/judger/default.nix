@@ -100,6 +100,24 @@ let
         Judger `${problem.name}` must define `prepareSolution`.
       '';
 
+  hexDigitValues = lib.listToAttrs (
+    lib.imap0 (value: digit: lib.nameValuePair digit value) (lib.stringToCharacters "0123456789abcdef")
+  );
+
+  # Matches `TestCaseSpec::random_seed`: the first 13 hex digits of the name's SHA-256.
+  testCaseRandomSeed =
+    testCase:
+    if testCase.randomSeed != null then
+      testCase.randomSeed
+    else
+      lib.foldl' (acc: digit: acc * 16 + hexDigitValues.${digit}) 0 (
+        lib.stringToCharacters (builtins.substring 0 13 (builtins.hashString "sha256" testCase.name))
+      );
+
+  # Matches `TestCaseSpec::stack_limit`: the memory limit unless a stack limit is set.
+  testCaseStackLimit =
+    testCase: if testCase.stackLimit == null then testCase.memoryLimit else testCase.stackLimit;
+
   readPreparedSolution =
     preparedDir: builtins.fromJSON (builtins.readFile "${preparedDir}/report.json");
 
@@ -129,6 +147,10 @@ let
       ${exportPathEnv "HULL_INPUT_PATH" testCase.data.input}
       ${exportEnv "HULL_TICK_LIMIT" (toString testCase.tickLimit)}
       ${exportEnv "HULL_MEMORY_LIMIT" (toString testCase.memoryLimit)}
+      ${exportEnv "HULL_STACK_LIMIT" (toString (testCaseStackLimit testCase))}
+      ${exportEnv "HULL_IO_TICKS_PER_CALL" (toString testCase.ioCost.ticksPerCall)}
+      ${exportEnv "HULL_IO_TICKS_PER_BYTE" (toString testCase.ioCost.ticksPerByte)}
+      ${exportEnv "HULL_RANDOM_SEED" (toString (testCaseRandomSeed testCase))}
       ${exportEnv "HULL_FILE_SIZE_LIMIT" (toString problem.fileSizeLimit)}
       ${exportPathEnv "HULL_SOLUTION_SRC" preparedSolution.src}
       ${exportOptionalEnv "HULL_SOLUTION_EXECUTABLE" ((preparedSolution.executable or { }).path or null)}
@@ -71,6 +71,22 @@ let
         arguments = [ ];
         tick_limit = hull.runWasm.dynamicNumber "HULL_TICK_LIMIT";
         memory_limit = hull.runWasm.dynamicNumber "HULL_MEMORY_LIMIT";
+        stack_limit = hull.runWasm.dynamicNumber "HULL_STACK_LIMIT";
+        random_seed = hull.runWasm.dynamicNumber "HULL_RANDOM_SEED";
+        io_cost = {
+          ticks_per_call = hull.runWasm.dynamicNumber "HULL_IO_TICKS_PER_CALL";
+          ticks_per_byte = hull.runWasm.dynamicNumber "HULL_IO_TICKS_PER_BYTE";
+        };
+        clock =
+          if problem.ticksPerSecond == null then
+            { kind = "frozen"; }
+          else
+            {
+              kind = "ticks";
+              ticks_per_second = problem.ticksPerSecond;
+            };
+        features = problem.wasmFeatures;
+        backtrace = true;
         required_accepted = false;
         file_system = {
           directories = [
@@ -134,6 +150,9 @@ let
         ];
       }
     ];
+    # The interactor's report is final once it exits, so a solution still running is stopped
+    # instead of running into its tick limit.
+    terminate_on_exit = [ "interactor" ];
   };
 in
 {
@@ -183,8 +202,10 @@ in
         solution_status=$(jq -r '.results[] | select(.program == "solution") | .status' session-report.json)
         tick=$(jq '.results[] | select(.program == "solution") | .tick' session-report.json)
         memory=$(jq '.results[] | select(.program == "solution") | .memory' session-report.json)
+        backtrace=$(jq -c '.results[] | select(.program == "solution") | .backtrace // []' session-report.json)
 
-        if [ "$solution_status" != "accepted" ]; then
+        # A solution stopped after the interactor exited is judged by the interactor's report.
+        if [ "$solution_status" != "accepted" ] && [ "$solution_status" != "terminated" ]; then
           final_status=$solution_status
           final_score=0.0
           final_message=$(jq -r '.results[] | select(.program == "solution") | .error_message // ""' session-report.json)
@@ -210,7 +231,8 @@ in
           --arg message "$final_message" \
           --argjson tick "$tick" \
           --argjson memory "$memory" \
-          '{ status: $status, score: $score, message: $message, tick: $tick, memory: $memory }' \
+          --argjson backtrace "$backtrace" \
+          '{ status: $status, score: $score, message: $message, tick: $tick, memory: $memory, backtrace: $backtrace }' \
           > "$HULL_REPORT_PATH"
       '';
   };
@@ -208,7 +208,14 @@
 
     memoryLimit = lib.mkOption {
       type = lib.types.ints.unsigned;
-      description = "The default byte ceiling used independently for WASM linear memory and the execution stack. Can be overridden per test case.";
+      description = "The default byte ceiling for WASM linear memory, also used for the execution stack when `stackLimit` is null. Can be overridden per test case.";
+    };
+
+    stackLimit = lib.mkOption {
+      type = lib.types.nullOr lib.types.ints.positive;
+      default = null;
+      example = 8 * 1024 * 1024;
+      description = "The default byte ceiling for the execution stack, independent of `memoryLimit`. When null, the stack ceiling equals the memory limit. Can be overridden per test case.";
     };
 
     fileSizeLimit = lib.mkOption {
@@ -217,6 +224,40 @@
       description = "The byte limit applied independently to every contestant-owned file and pipe. A file is limited by logical size, while a pipe is limited by cumulative transferred bytes. Exceeding the limit produces `file_error`.";
     };
 
+    ioCost = lib.mkOption {
+      type = hull.types.ioCost;
+      default = { };
+      example = {
+        ticksPerCall = 1000;
+        ticksPerByte = 1;
+      };
+      description = "Ticks that built-in judgers charge solutions for the host work behind read and write calls, on top of the guest instructions around them. Both costs default to zero, so I/O is free. Can be overridden per test case.";
+    };
+
+    ticksPerSecond = lib.mkOption {
+      type = lib.types.nullOr lib.types.ints.positive;
+      default = null;
+      example = 10000000000;
+      description = "When set, built-in judgers give solutions realtime and monotonic clocks that start at zero and advance one second per this many consumed ticks. When null, both clocks always read zero.";
+    };
+
+    wasmFeatures = lib.mkOption {
+      type = lib.types.listOf (
+        lib.types.enum [
+          "bulk_memory"
+          "multi_value"
+          "mutable_global"
+          "saturating_float_to_int"
+          "sign_extension"
+          "simd"
+          "tail_call"
+        ]
+      );
+      default = [ ];
+      example = [ "simd" ];
+      description = "Wasm proposals that built-in judgers accept in solutions beyond Hull's strict MVP feature set. Solutions using any other proposal fail to load.";
+    };
+
     subtasks = lib.mkOption {
       type = lib.types.listOf (hull.types.subtask config);
       default = [ { fullScore = 1.0; } ];
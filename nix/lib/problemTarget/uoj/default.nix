@@ -135,6 +135,8 @@ assert lib.assertMsg (
         name = problem.name;
         tick_limit = problem.tickLimit;
         memory_limit = problem.memoryLimit;
+        stack_limit = problem.stackLimit;
+        io_cost = hull.runWasm.serializeIoCost problem.ioCost;
         file_size_limit = problem.fileSizeLimit;
         full_score = problem.fullScore;
         checker = {
@@ -170,6 +172,9 @@ assert lib.assertMsg (
           name = tc.name;
           tick_limit = tc.tickLimit;
           memory_limit = tc.memoryLimit;
+          stack_limit = tc.stackLimit;
+          io_cost = hull.runWasm.serializeIoCost tc.ioCost;
+          random_seed = tc.randomSeed;
           groups = tc.groups;
           trait_hints = tc.traitHints;
         }) allTestCases;
@@ -104,4 +104,10 @@ in
 
   dynamicString = dynamic "string";
   dynamicNumber = dynamic "number";
+
+  # Converts an `ioCost` option value into the `io_cost` object of requests and metadata.
+  serializeIoCost = ioCost: {
+    ticks_per_call = ioCost.ticksPerCall;
+    ticks_per_byte = ioCost.ticksPerByte;
+  };
 }
@@ -147,6 +147,8 @@ let
       name = checkedProblemConfig.name;
       tick_limit = checkedProblemConfig.tickLimit;
       memory_limit = checkedProblemConfig.memoryLimit;
+      stack_limit = checkedProblemConfig.stackLimit;
+      io_cost = hull.runWasm.serializeIoCost checkedProblemConfig.ioCost;
       file_size_limit = checkedProblemConfig.fileSizeLimit;
       full_score = checkedProblemConfig.fullScore;
       checker = serializeProgram checkedProblemConfig.checker;
@@ -162,10 +164,13 @@ let
         inherit (tc) name groups;
         tick_limit = tc.tickLimit;
         memory_limit = tc.memoryLimit;
+        stack_limit = tc.stackLimit;
+        io_cost = hull.runWasm.serializeIoCost tc.ioCost;
         trait_hints = tc.traitHints;
         input_file = serializeRuntimeFile tc.inputFile;
         generator = tc.generator;
         arguments = tc.arguments;
+        random_seed = tc.randomSeed;
       }) (builtins.attrValues checkedProblemConfig.testCases);
       subtasks = map (st: {
         full_score = st.fullScore;
@@ -177,6 +182,7 @@ let
         main_correct_solution = solution.mainCorrectSolution;
         participant_visibility = solution.participantVisibility;
         src = serializeRuntimeFile solution.src;
+        subtask_predictions = map lib.toIntBase10 (builtins.attrNames solution.subtaskPredictions);
       }) selectedSolutions;
       runtime_files_anchor = toString runtimeFilesAnchor;
       checker_tests =
@@ -283,6 +289,27 @@ let
       metadata.runtime_files_anchor
     ];
 
+  # Evaluates subtask predictions for `{ solution, subtask, score, statuses }` queries, so the
+  # Hull CLI can check them while runtime analysis is still running.
+  checkSubtaskPredictions =
+    problemConfig: queries:
+    map (
+      query:
+      problemConfig.solutions.${query.solution}.subtaskPredictions.${toString query.subtask} {
+        inherit (query) score statuses;
+      }
+    ) queries;
+
+  contestProblemConfig =
+    contest: name:
+    let
+      problems = map (
+        problem: if problem ? config then problem else hull.evalProblem problem { }
+      ) contest.config.problems;
+      missing = throw "Contest `${contest.config.name}` has no problem `${name}`";
+    in
+    (lib.findFirst (problem: problem.config.name == name) missing problems).config;
+
   contestMetadata = contest: {
     name = contest.config.name;
     problems = map (
@@ -309,8 +336,10 @@ in
     adHocProblemMetadataFile
     buildContestTarget
     buildProblemTarget
+    checkSubtaskPredictions
     contestMetadata
     contestMetadataFile
+    contestProblemConfig
     problemMetadata
     problemMetadataFile
     withProblemRuntimeData
@@ -340,6 +340,21 @@ in
     };
   };
 
+  ioCost = submodule {
+    options = {
+      ticksPerCall = lib.mkOption {
+        type = ints.unsigned;
+        default = 0;
+        description = "Ticks charged for every `fd_read`, `fd_pread`, `fd_write`, or `fd_pwrite` call.";
+      };
+      ticksPerByte = lib.mkOption {
+        type = ints.unsigned;
+        default = 0;
+        description = "Ticks charged for every byte those calls transfer.";
+      };
+    };
+  };
+
   trait = submodule {
     options = {
       descriptions = lib.mkOption {
@@ -403,7 +418,27 @@ in
             type = ints.unsigned;
             default = problem.memoryLimit;
             defaultText = lib.literalExpression "problem.memoryLimit";
-            description = "WASM linear-memory and execution-stack byte ceiling for this specific test case.";
+            description = "WASM linear-memory byte ceiling for this specific test case, also its execution-stack ceiling when `stackLimit` is null.";
+          };
+          stackLimit = lib.mkOption {
+            type = nullOr ints.positive;
+            default = problem.stackLimit;
+            defaultText = lib.literalExpression "problem.stackLimit";
+            description = "Execution-stack byte ceiling for this specific test case.";
+          };
+          ioCost = lib.mkOption {
+            type = hull.types.ioCost;
+            default = problem.ioCost;
+            defaultText = lib.literalExpression "problem.ioCost";
+            description = "Ticks charged to solutions for host I/O on this specific test case.";
+          };
+          randomSeed = lib.mkOption {
+            type = nullOr ints.unsigned;
+            default = null;
+            description = ''
+              Seed of the deterministic `random_get` stream seen by solutions judged on this test case.
+              When null, the seed is derived from the test case name.
+            '';
           };
           groups = lib.mkOption {
             type = listOf nameStr;
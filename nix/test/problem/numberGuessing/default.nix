@@ -74,6 +74,12 @@
           "0" = unac;
         };
       };
+      answerThenSpin = {
+        src = ./solution/answer_then_spin.98.cpp;
+        subtaskPredictions = {
+          "0" = ac;
+        };
+      };
     };
 
   documents =
@@ -103,6 +109,7 @@
         std = "cpp";
         tooManyOp = "c";
         infinityRead = "cpp";
+        answerThenSpin = "cpp";
       };
     };
     hydroLegacy = hull.problemTarget.legacy.hydro.stdioInteraction { };
@@ -0,0 +1,26 @@
+#include <iostream>
+
+int main() {
+  int n;
+  std::cin >> n;
+  int l = 1, r = n;
+  while (l < r) {
+    int mid = (l + r) >> 1;
+    std::cout << "Q " << mid << '\n';
+    std::cout.flush();
+    char res;
+    std::cin >> res;
+    if (res == '>') {
+      r = mid - 1;
+    } else if (res == '=') {
+      l = r = mid;
+    } else {
+      l = mid + 1;
+    }
+  }
+  std::cout << "A " << l << '\n';
+  std::cout.flush();
+  // Keeps running after the interactor has its answer, which must not turn into a time limit.
+  for (volatile unsigned long long i = 0;; ++i) {
+  }
+}
@@ -16,9 +16,10 @@
 use clap::{Parser, Subcommand};
 
 use crate::cmd::{
-  build::BuildOpts, build_contest::BuildContestOpts, compile::CompileOpts,
-  integration_judge::IntegrationJudgeCommand, judge::JudgeOpts, patch::PatchOpts, run::RunOpts,
-  run_wasm::RunWasmOpts, source_config::SourceConfigOpts, stress::StressOpts,
+  build::BuildOpts, build_contest::BuildContestOpts, cache::CacheCommand, compile::CompileOpts,
+  inspect_wasm::InspectWasmOpts, integration_judge::IntegrationJudgeCommand, judge::JudgeOpts,
+  limits::LimitsOpts, patch::PatchOpts, profile::ProfileOpts, run::RunOpts, run_wasm::RunWasmOpts,
+  source_config::SourceConfigOpts, stress::StressOpts, test_matrix::TestMatrixOpts,
 };
 use crate::interactive::InteractiveMode;
 
@@ -56,24 +57,52 @@ pub enum Command {
   )]
   /// Analyzes and packages one contest.
   BuildContest(BuildContestOpts),
+  #[command(
+    about = "Manage the cache of analysis reports",
+    long_about = "Inspect and prune the persistent cache of validator, checker, and judge reports that runtime analysis reuses across runs. The cache lives in `HULL_RESULT_CACHE_DIR`, or else `$XDG_CACHE_HOME/hull/results` or `~/.cache/hull/results`."
+  )]
+  /// Manages the persistent result cache.
+  Cache {
+    /// Cache maintenance action to execute.
+    #[command(subcommand)]
+    command: CacheCommand,
+  },
   #[command(
     about = "Compile a source file to WASM",
     long_about = "Compile one source file in the selected problem context to a WebAssembly executable and write it to a local file or standard output."
   )]
   /// Compiles one source file to a WebAssembly executable.
   Compile(CompileOpts),
+  #[command(
+    about = "Inspect a WASM file",
+    long_about = "Load one source WASM module or component with the same rules as judged programs and report its imports, the proposals it needs, its linear memories, exports, code size, and whether Hull's engine would start it."
+  )]
+  /// Analyzes one WebAssembly module or component.
+  InspectWasm(InspectWasmOpts),
   #[command(
     about = "Judge one source file as an ad-hoc solution",
     long_about = "Treat the given source file as an extra solution for the selected problem, run the full problem analysis for it, and print either a human-readable or JSON judging report."
   )]
   /// Judges one source file against a problem.
   Judge(JudgeOpts),
+  #[command(
+    about = "Report tick and memory headroom and suggest a tick limit",
+    long_about = "Analyze one problem with raised tick limits and report, per subtask, the peak tick and memory usage of the main correct solution and of solutions predicted to pass, their ratio to the configured limits, and the fastest solution predicted to exceed the tick limit. Suggest a tick limit that separates intended from unintended solutions and warn when the margin is thin."
+  )]
+  /// Reports resource headroom and suggests a tick limit for one problem.
+  Limits(LimitsOpts),
   #[command(
     about = "Patch source code with a regex rewrite",
     long_about = "Parse a C or C++ source file, apply a regex replacement to the path inside each `#include \"...\"` string literal, and write the patched file to a new path."
   )]
   /// Rewrites include paths in a source file.
   Patch(PatchOpts),
+  #[command(
+    about = "Compile a source file and profile its ticks",
+    long_about = "Compile one source file in the selected problem context, run it like `hull run`, attribute consumed ticks to guest call stacks named from the WASM name section and DWARF, write the stacks in collapsed flamegraph format, and print the heaviest functions."
+  )]
+  /// Profiles tick usage of one source file.
+  Profile(ProfileOpts),
   #[command(
     about = "Compile a source file and run its WASM",
     long_about = "Compile one source file in the selected problem context to a WebAssembly executable and run it with optional tick, memory, inherited-stream file-size, and argv overrides."
@@ -104,6 +133,12 @@ pub enum Command {
   )]
   /// Searches for failing generated testcases.
   Stress(StressOpts),
+  #[command(
+    about = "Report which test cases reject which wrong solutions",
+    long_about = "Analyze one problem and report, for every solution that fails some test case, the test cases that reject it. List the test cases that reject no solution, a minimal set of test cases that still rejects every wrong solution, and the wrong solutions that only one test case rejects."
+  )]
+  /// Reports test case effectiveness against wrong solutions.
+  TestMatrix(TestMatrixOpts),
 }
 
 fn parse_interactive_mode(value: &str) -> Result<InteractiveMode, String> {
@@ -118,7 +153,8 @@ fn parse_interactive_mode(value: &str) -> Result<InteractiveMode, String> {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::cmd::{compile::CompileOpts, source_config::SourceLanguage};
+  use crate::cmd::{cache::CleanOpts, compile::CompileOpts, source_config::SourceLanguage};
+  use crate::runner::WasmFeature;
 
   #[test]
   fn compile_cli() {
@@ -214,6 +250,143 @@ mod tests {
     ));
   }
 
+  #[test]
+  fn profile_cli() {
+    let opts = Opts::try_parse_from([
+      "hull",
+      "profile",
+      "-o",
+      "std.folded",
+      "--sample-interval",
+      "1",
+      "solution.20.cpp",
+      "argument",
+    ])
+    .expect("profile command parses");
+    assert!(matches!(
+      opts.command,
+      Command::Profile(ProfileOpts {
+        source,
+        output,
+        sample_interval: 1,
+        tick_limit: None,
+        args,
+        ..
+      })
+        if source.src_path == "solution.20.cpp"
+          && output == std::path::Path::new("std.folded")
+          && args == ["argument"]
+    ));
+  }
+
+  #[test]
+  fn build_cli() {
+    let opts = Opts::try_parse_from([
+      "hull",
+      "build",
+      "--no-cache",
+      "-j",
+      "2",
+      "--test",
+      "large-*",
+      "--subtask",
+      "3",
+      "--subtask",
+      "4",
+    ])
+    .expect("build command parses");
+    assert!(matches!(
+      opts.command,
+      Command::Build(BuildOpts {
+        no_cache: true,
+        jobs: Some(2),
+        tests,
+        subtasks,
+        ..
+      }) if tests == ["large-*"] && subtasks == [3, 4]
+    ));
+    let opts = Opts::try_parse_from(["hull", "build"]).expect("build command parses");
+    assert!(matches!(
+      opts.command,
+      Command::Build(BuildOpts {
+        no_cache: false,
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn limits_cli() {
+    let opts = Opts::try_parse_from(["hull", "limits", "-p", "aPlusB", "--tick-headroom", "8"])
+      .expect("limits command parses");
+    assert!(matches!(
+      opts.command,
+      Command::Limits(LimitsOpts {
+        problem,
+        tick_headroom: 8,
+        json: false,
+        ..
+      }) if problem == "aPlusB"
+    ));
+    assert!(Opts::try_parse_from(["hull", "limits", "--tick-headroom", "0"]).is_err());
+  }
+
+  #[test]
+  fn cache_clean_cli() {
+    let opts = Opts::try_parse_from(["hull", "cache", "clean", "--older-than", "30"])
+      .expect("cache clean command parses");
+    assert!(matches!(
+      opts.command,
+      Command::Cache {
+        command: CacheCommand::Clean(CleanOpts {
+          older_than: Some(30)
+        })
+      }
+    ));
+    assert!(Opts::try_parse_from(["hull", "cache"]).is_err());
+  }
+
+  #[test]
+  fn test_matrix_cli() {
+    let opts = Opts::try_parse_from(["hull", "test-matrix", "-p", "aPlusB", "--json"])
+      .expect("test-matrix command parses");
+    assert!(matches!(
+      opts.command,
+      Command::TestMatrix(TestMatrixOpts {
+        problem,
+        json: true,
+        no_cache: false,
+        ..
+      }) if problem == "aPlusB"
+    ));
+  }
+
+  #[test]
+  fn inspect_wasm_cli() {
+    let opts = Opts::try_parse_from([
+      "hull",
+      "inspect-wasm",
+      "--wasm-feature",
+      "simd",
+      "--stack-limit",
+      "1048576",
+      "--json",
+      "solution.wasm",
+    ])
+    .expect("inspect-wasm command parses");
+    assert!(matches!(
+      opts.command,
+      Command::InspectWasm(InspectWasmOpts {
+        wasm_path,
+        wasm_features,
+        stack_limit: Some(1_048_576),
+        json: true,
+      })
+        if wasm_path == std::path::Path::new("solution.wasm")
+          && wasm_features == [WasmFeature::Simd]
+    ));
+  }
+
   #[test]
   fn source_config_cli() {
     let c_opts = Opts::try_parse_from(["hull", "source-config", "c"])
@@ -18,7 +18,8 @@ use clap::Parser;
 
 use crate::interactive;
 use crate::runtime::build::build_problem;
-use crate::runtime::types::RuntimeOptions;
+use crate::runtime::cache::ResultCache;
+use crate::runtime::types::{RuntimeOptions, TestSelection};
 
 #[derive(Parser)]
 /// Options for analyzing and packaging one problem.
@@ -43,6 +44,20 @@ pub struct BuildOpts {
   #[arg(long)]
   pub stop_on_failure: bool,
 
+  /// Rerun every validator, checker, and judge step instead of reusing cached reports.
+  #[arg(long)]
+  pub no_cache: bool,
+
+  /// Judge only testcases whose name matches this glob, where `*` and `?` are wildcards. May be
+  /// repeated.
+  #[arg(long = "test", value_name = "GLOB")]
+  pub tests: Vec<String>,
+
+  /// Judge only testcases of this subtask, counted from 0. May be repeated. Membership comes from
+  /// validator traits, so other testcases are still generated and validated.
+  #[arg(long = "subtask", value_name = "INDEX")]
+  pub subtasks: Vec<usize>,
+
   /// Extra arguments to pass through to the final `nix build` step.
   #[arg(trailing_var_arg = true)]
   pub nix_args: Vec<String>,
@@ -57,7 +72,12 @@ pub fn run(build_opts: &BuildOpts) -> Result<()> {
     &build_opts.out_link,
     RuntimeOptions::new(build_opts.jobs)
       .with_progress(progress)
-      .with_stop_on_failure(build_opts.stop_on_failure),
+      .with_stop_on_failure(build_opts.stop_on_failure)
+      .with_result_cache(ResultCache::open_default(build_opts.no_cache))
+      .with_test_selection(TestSelection::new(
+        build_opts.tests.iter().cloned(),
+        build_opts.subtasks.iter().copied(),
+      )?),
     &build_opts.nix_args,
   )
 }
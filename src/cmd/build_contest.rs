@@ -18,6 +18,7 @@ use clap::Parser;
 
 use crate::interactive;
 use crate::runtime::build::build_contest;
+use crate::runtime::cache::ResultCache;
 use crate::runtime::types::RuntimeOptions;
 
 #[derive(Parser)]
@@ -43,6 +44,10 @@ pub struct BuildContestOpts {
   #[arg(long)]
   pub stop_on_failure: bool,
 
+  /// Rerun every validator, checker, and judge step instead of reusing cached reports.
+  #[arg(long)]
+  pub no_cache: bool,
+
   /// Extra arguments to pass through to the final `nix build` step.
   #[arg(trailing_var_arg = true)]
   pub nix_args: Vec<String>,
@@ -57,7 +62,8 @@ pub fn run(build_opts: &BuildContestOpts) -> Result<()> {
     &build_opts.out_link,
     RuntimeOptions::new(build_opts.jobs)
       .with_progress(progress)
-      .with_stop_on_failure(build_opts.stop_on_failure),
+      .with_stop_on_failure(build_opts.stop_on_failure)
+      .with_result_cache(ResultCache::open_default(build_opts.no_cache)),
     &build_opts.nix_args,
   )
 }
@@ -0,0 +1,62 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+
+use crate::format::format_size;
+use crate::runtime::cache::{ResultCache, result_cache_directory};
+
+/// Result cache maintenance subcommands.
+#[derive(Subcommand)]
+pub enum CacheCommand {
+  /// Remove cached validator, checker, and judge reports.
+  Clean(CleanOpts),
+}
+
+#[derive(Parser)]
+/// Options for removing cached reports.
+pub struct CleanOpts {
+  /// Only remove reports that no run has used for this many days.
+  #[arg(long, value_name = "DAYS")]
+  pub older_than: Option<u64>,
+}
+
+/// Runs a result cache maintenance subcommand.
+pub fn run(command: &CacheCommand) -> Result<()> {
+  match command {
+    CacheCommand::Clean(opts) => clean(opts),
+  }
+}
+
+fn clean(opts: &CleanOpts) -> Result<()> {
+  let cache = ResultCache::new(result_cache_directory().context(
+    "No result cache directory is known; set HULL_RESULT_CACHE_DIR, XDG_CACHE_HOME, or HOME",
+  )?);
+  let summary = cache.clean(
+    opts
+      .older_than
+      .map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60))),
+  )?;
+  println!(
+    "Removed {} cached reports ({}) from {}",
+    summary.entries,
+    format_size(summary.bytes),
+    cache.root().display()
+  );
+  Ok(())
+}
@@ -0,0 +1,150 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+
+use crate::{
+  format::format_size,
+  runner::{self, WasmFeature, WasmInspection},
+};
+
+/// Options for inspecting one Wasm file.
+#[derive(Parser)]
+pub struct InspectWasmOpts {
+  /// Source Wasm module or component, binary or text.
+  pub wasm_path: PathBuf,
+
+  /// Wasm proposal accepted beyond Hull's strict MVP feature set, such as `simd`. May be
+  /// repeated.
+  #[arg(long = "wasm-feature", value_name = "FEATURE")]
+  pub wasm_features: Vec<WasmFeature>,
+
+  /// Stack limit in bytes the program is judged with, which is the problem's `stackLimit`, or its
+  /// `memoryLimit` when unset.
+  #[arg(long)]
+  pub stack_limit: Option<u64>,
+
+  /// Print the inspection as JSON instead of tables.
+  #[arg(long)]
+  pub json: bool,
+}
+
+/// Prints the imports, features, memories, exports, and acceptance of one Wasm file.
+pub fn run(opts: &InspectWasmOpts) -> Result<()> {
+  let inspection = runner::inspect_wasm(
+    &opts.wasm_path,
+    &opts.wasm_features,
+    opts.stack_limit.unwrap_or(runner::TOOL_MEMORY_LIMIT),
+  )?;
+  if opts.json {
+    println!("{}", serde_json::to_string(&inspection)?);
+  } else {
+    print!("{}", render(&inspection));
+  }
+  Ok(())
+}
+
+fn render(inspection: &WasmInspection) -> String {
+  let mut output = format!(
+    "Kind: {}\nCode size: {}\n",
+    inspection.kind,
+    format_size(inspection.code_size)
+  );
+  match &inspection.rejection {
+    None => output.push_str("Accepted: yes\n"),
+    Some(rejection) => output.push_str(&format!("Accepted: no\n{rejection}\n")),
+  }
+
+  let mut imports = table(vec!["Import", "Kind", "Supported"]);
+  for import in &inspection.imports {
+    let name = if import.module.is_empty() {
+      import.name.clone()
+    } else {
+      format!("{}.{}", import.module, import.name)
+    };
+    imports.add_row(vec![
+      Cell::new(name),
+      Cell::new(import.kind),
+      Cell::new(if import.supported { "yes" } else { "no" }),
+    ]);
+  }
+
+  let mut features = table(vec!["Feature", "Status"]);
+  for feature in &inspection.features {
+    features.add_row(vec![
+      Cell::new(&feature.name),
+      Cell::new(match (feature.enabled, feature.opt_in) {
+        (true, _) => "enabled",
+        (false, true) => "needs --wasm-feature",
+        (false, false) => "unsupported",
+      }),
+    ]);
+  }
+
+  let mut memories = table(vec!["Memory", "Minimum", "Maximum", "Page size", "Flags"]);
+  for (index, memory) in inspection.memories.iter().enumerate() {
+    let pages = |pages: u64| {
+      format!(
+        "{pages} ({})",
+        format_size(pages.saturating_mul(memory.page_size))
+      )
+    };
+    let flags = [
+      (memory.imported, "imported"),
+      (memory.memory64, "memory64"),
+      (memory.shared, "shared"),
+    ]
+    .into_iter()
+    .filter_map(|(set, flag)| set.then_some(flag))
+    .collect::<Vec<_>>();
+    memories.add_row(vec![
+      Cell::new(index),
+      Cell::new(pages(memory.minimum)),
+      Cell::new(memory.maximum.map_or_else(|| "none".into(), pages)),
+      Cell::new(format_size(memory.page_size)),
+      Cell::new(flags.join(", ")),
+    ]);
+  }
+
+  let mut exports = table(vec!["Export", "Kind"]);
+  for export in &inspection.exports {
+    exports.add_row(vec![Cell::new(&export.name), Cell::new(export.kind)]);
+  }
+
+  for (title, table, empty) in [
+    ("Imports", imports, inspection.imports.is_empty()),
+    ("Features", features, inspection.features.is_empty()),
+    ("Memories", memories, inspection.memories.is_empty()),
+    ("Exports", exports, inspection.exports.is_empty()),
+  ] {
+    if empty {
+      output.push_str(&format!("\n{title}: none\n"));
+    } else {
+      output.push_str(&format!("\n{title}:\n{table}\n"));
+    }
+  }
+  output
+}
+
+fn table(header: Vec<&str>) -> Table {
+  let mut table = Table::new();
+  table.load_preset(UTF8_FULL_CONDENSED);
+  table.set_header(header);
+  table
+}
@@ -13,7 +13,7 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -116,6 +116,7 @@ pub fn run(opts: &CnoiOpts) -> Result<()> {
           full_score: problem.full_score,
           scaled_score: 0.0,
           statuses: vec![JudgeStatus::InternalError],
+          skipped_test_cases: Vec::new(),
         }],
         test_case_results: BTreeMap::from([(
           problem.name.clone(),
@@ -124,10 +125,12 @@ pub fn run(opts: &CnoiOpts) -> Result<()> {
             score: 0.0,
             tick: 0,
             memory: 0,
+            backtrace: Vec::new(),
           },
         )])
         .into_iter()
         .collect(),
+        skipped_test_cases: Vec::new(),
       },
     };
 
@@ -164,6 +167,7 @@ pub fn run(opts: &CnoiOpts) -> Result<()> {
         full_score: problem.full_score,
         subtask_results: problem.subtask_results.clone(),
         test_case_results: problem.test_case_results.clone().into_iter().collect(),
+        skipped_test_cases: Vec::new(),
       };
       println!("{}\n", single_problem_report.render_human_readable());
     }
@@ -199,6 +203,8 @@ fn evaluate_problem(
     name: problem.name.clone(),
     tick_limit: problem.tick_limit,
     memory_limit: problem.memory_limit,
+    stack_limit: problem.stack_limit,
+    io_cost: problem.io_cost,
     file_size_limit: problem.file_size_limit,
     full_score: problem.full_score,
     checker: ProgramSpec {
@@ -219,6 +225,7 @@ fn evaluate_problem(
       src: local_source_path.to_string_lossy().into_owned(),
       main_correct_solution: false,
       participant_visibility: true,
+      subtask_predictions: Vec::new(),
     }],
     checker_tests: Vec::new(),
     validator_tests: Vec::new(),
@@ -243,6 +250,7 @@ fn evaluate_problem(
           statuses: Vec::new(),
           raw_score: 0.0,
           scaled_score: 0.0,
+          skipped_test_cases: BTreeSet::new(),
         })
         .collect::<Vec<_>>(),
       &BTreeMap::new(),
@@ -304,10 +312,13 @@ fn evaluate_problem(
       input_file: Some(local_input_path.to_string_lossy().into_owned()),
       tick_limit: test_case.tick_limit,
       memory_limit: test_case.memory_limit,
+      stack_limit: test_case.stack_limit,
+      io_cost: test_case.io_cost,
       groups: test_case.groups.clone(),
       trait_hints: test_case.trait_hints.clone(),
       generator: None,
       arguments: None,
+      random_seed: test_case.random_seed,
     };
     let report = run_judge(
       &runtime_problem,
@@ -354,17 +365,24 @@ fn evaluate_problem(
           input_file: Some(local_input_path.to_string_lossy().into_owned()),
           tick_limit: test_case.tick_limit,
           memory_limit: test_case.memory_limit,
+          stack_limit: test_case.stack_limit,
+          io_cost: test_case.io_cost,
           groups: test_case.groups.clone(),
           trait_hints: test_case.trait_hints.clone(),
           generator: None,
           arguments: None,
+          random_seed: None,
         }
       })
       .collect(),
     ..runtime_problem.clone()
   };
-  let subtask_reports =
-    aggregate_subtask_results(&scoring_problem, &test_case_results, &test_case_traits);
+  let subtask_reports = aggregate_subtask_results(
+    &scoring_problem,
+    &test_case_results,
+    &test_case_traits,
+    &BTreeSet::new(),
+  );
   let score = subtask_reports
     .iter()
     .map(|report| report.scaled_score)
@@ -416,6 +434,7 @@ mod tests {
           full_score: 100.0,
           scaled_score: 0.0,
           statuses: vec![JudgeStatus::FileError],
+          skipped_test_cases: Vec::new(),
         }],
         test_case_results: BTreeMap::from([(
           "sample".to_string(),
@@ -424,6 +443,7 @@ mod tests {
             score: 0.0,
             tick: 1,
             memory: 2,
+            backtrace: Vec::new(),
           },
         )]),
       }],
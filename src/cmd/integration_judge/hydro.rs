@@ -13,7 +13,7 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -22,6 +22,7 @@ use serde::Deserialize;
 use serde_json::Value;
 
 use crate::report::JudgeCliReport;
+use crate::runner::IoCost;
 use crate::runtime::analysis::aggregate_subtask_results;
 use crate::runtime::bundle_judge::{
   BundleJudgeTestCaseInput, judge_test_case_with_parts, load_official_data,
@@ -78,6 +79,9 @@ struct HydroBundleTestCase {
   official_data_path: PathBuf,
   tick_limit: u64,
   memory_limit: u64,
+  stack_limit: Option<u64>,
+  io_cost: IoCost,
+  random_seed: Option<u64>,
   groups: Vec<String>,
 }
 
@@ -132,6 +136,9 @@ pub fn run(opts: &HydroOpts) -> Result<()> {
           official_data_path: &test_case.official_data_path,
           tick_limit: test_case.tick_limit,
           memory_limit: test_case.memory_limit,
+          stack_limit: test_case.stack_limit,
+          io_cost: test_case.io_cost,
+          random_seed: test_case.random_seed,
           groups: test_case.groups.clone(),
           trait_hints: test_case.scheduled.traits.clone(),
         },
@@ -167,6 +174,9 @@ fn load_hydro_bundle_test_cases(
         official_data_path,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
+        random_seed: test_case.random_seed,
         groups: test_case.groups.clone(),
       })
     })
@@ -183,6 +193,8 @@ fn aggregate_hydro_report(
     name: problem.name.clone(),
     tick_limit: problem.tick_limit,
     memory_limit: problem.memory_limit,
+    stack_limit: problem.stack_limit,
+    io_cost: problem.io_cost,
     file_size_limit: problem.file_size_limit,
     full_score: problem.full_score,
     checker: problem.checker.clone(),
@@ -197,10 +209,13 @@ fn aggregate_hydro_report(
         input_file: None,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
         groups: test_case.groups.clone(),
         trait_hints: test_case.scheduled.traits.clone(),
         generator: None,
         arguments: None,
+        random_seed: None,
       })
       .collect(),
     subtasks: problem.subtasks.clone(),
@@ -208,8 +223,12 @@ fn aggregate_hydro_report(
     checker_tests: Vec::new(),
     validator_tests: Vec::new(),
   };
-  let subtask_reports =
-    aggregate_subtask_results(&scoring_problem, test_case_reports, runtime_traits);
+  let subtask_reports = aggregate_subtask_results(
+    &scoring_problem,
+    test_case_reports,
+    runtime_traits,
+    &BTreeSet::new(),
+  );
   let cli_report = JudgeCliReport::from_subtask_reports(
     problem.full_score,
     &problem.subtasks,
@@ -217,20 +236,20 @@ fn aggregate_hydro_report(
     test_case_reports,
   );
   let status = aggregate_top_level_status(test_case_reports);
-  let failure_details = test_case_reports
+  let failure = test_case_reports
     .values()
-    .find(|report| report.status == status && report.status != JudgeStatus::Accepted)
-    .map(|report| {
-      let mut parts = Vec::new();
-      parts.push(format!("status: {}", report.status));
-      if !report.message.is_empty() {
-        parts.push(format!("message:\n{}", report.message));
-      }
-      if !report.outputs.is_empty() {
-        parts.push(format!("outputs:\n{}", report.outputs));
-      }
-      parts.join("\n\n")
-    });
+    .find(|report| report.status == status && report.status != JudgeStatus::Accepted);
+  let failure_details = failure.map(|report| {
+    let mut parts = Vec::new();
+    parts.push(format!("status: {}", report.status));
+    if !report.message.is_empty() {
+      parts.push(format!("message:\n{}", report.message));
+    }
+    if !report.outputs.is_empty() {
+      parts.push(format!("outputs:\n{}", report.outputs));
+    }
+    parts.join("\n\n")
+  });
   let total_score = subtask_reports
     .iter()
     .map(|report| report.scaled_score)
@@ -264,6 +283,9 @@ fn aggregate_hydro_report(
     },
     tick,
     memory,
+    backtrace: failure
+      .map(|report| report.backtrace.clone())
+      .unwrap_or_default(),
     outputs: String::new(),
   }
 }
@@ -352,6 +374,7 @@ mod tests {
         message: "size limit exceeded".to_string(),
         tick: 1,
         memory: 2,
+        backtrace: Vec::new(),
         outputs: String::new(),
       },
     )
@@ -13,13 +13,14 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 
 use crate::report::JudgeCliReport;
+use crate::runner::IoCost;
 use crate::runtime::analysis::aggregate_subtask_results;
 use crate::runtime::bundle_judge::{
   BundleJudgeTestCaseInput, judge_test_case_with_parts, load_official_data,
@@ -74,6 +75,9 @@ struct LemonBundleTestCase {
   official_data_path: PathBuf,
   tick_limit: u64,
   memory_limit: u64,
+  stack_limit: Option<u64>,
+  io_cost: IoCost,
+  random_seed: Option<u64>,
   groups: Vec<String>,
 }
 
@@ -137,6 +141,9 @@ pub fn run(opts: &LemonOpts) -> Result<()> {
           official_data_path: &test_case.official_data_path,
           tick_limit: test_case.tick_limit,
           memory_limit: test_case.memory_limit,
+          stack_limit: test_case.stack_limit,
+          io_cost: test_case.io_cost,
+          random_seed: test_case.random_seed,
           groups: test_case.groups.clone(),
           trait_hints: test_case.scheduled.traits.clone(),
         },
@@ -172,6 +179,9 @@ fn load_lemon_bundle_test_cases(
         official_data_path,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
+        random_seed: test_case.random_seed,
         groups: test_case.groups.clone(),
       })
     })
@@ -188,6 +198,8 @@ fn aggregate_lemon_report(
     name: problem.name.clone(),
     tick_limit: problem.tick_limit,
     memory_limit: problem.memory_limit,
+    stack_limit: problem.stack_limit,
+    io_cost: problem.io_cost,
     file_size_limit: problem.file_size_limit,
     full_score: problem.full_score,
     checker: problem.checker.clone(),
@@ -202,10 +214,13 @@ fn aggregate_lemon_report(
         input_file: None,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
         groups: test_case.groups.clone(),
         trait_hints: test_case.scheduled.traits.clone(),
         generator: None,
         arguments: None,
+        random_seed: None,
       })
       .collect(),
     subtasks: problem.subtasks.clone(),
@@ -213,8 +228,12 @@ fn aggregate_lemon_report(
     checker_tests: Vec::new(),
     validator_tests: Vec::new(),
   };
-  let subtask_reports =
-    aggregate_subtask_results(&scoring_problem, test_case_reports, runtime_traits);
+  let subtask_reports = aggregate_subtask_results(
+    &scoring_problem,
+    test_case_reports,
+    runtime_traits,
+    &BTreeSet::new(),
+  );
   let cli_report = JudgeCliReport::from_subtask_reports(
     problem.full_score,
     &problem.subtasks,
@@ -241,16 +260,16 @@ fn aggregate_lemon_report(
     .max()
     .unwrap_or(0);
   let status = aggregate_top_level_status(test_case_reports);
-  let failure_details = test_case_reports
+  let failure = test_case_reports
     .values()
-    .find(|report| report.status == status && report.status != JudgeStatus::Accepted)
-    .map(|report| {
-      if report.message.is_empty() {
-        format!("status: {}", report.status)
-      } else {
-        format!("status: {}\n\nmessage:\n{}", report.status, report.message)
-      }
-    });
+    .find(|report| report.status == status && report.status != JudgeStatus::Accepted);
+  let failure_details = failure.map(|report| {
+    if report.message.is_empty() {
+      format!("status: {}", report.status)
+    } else {
+      format!("status: {}\n\nmessage:\n{}", report.status, report.message)
+    }
+  });
   let message = match failure_details {
     Some(details) => format!(
       "{}\n\nSelected Failure Details:\n{}",
@@ -266,6 +285,9 @@ fn aggregate_lemon_report(
     message,
     tick,
     memory,
+    backtrace: failure
+      .map(|report| report.backtrace.clone())
+      .unwrap_or_default(),
     outputs: String::new(),
   }
 }
@@ -362,6 +384,7 @@ mod tests {
       message: "size limit exceeded".to_string(),
       tick: 1,
       memory: 2,
+      backtrace: Vec::new(),
       outputs: String::new(),
     };
 
@@ -13,7 +13,7 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -23,6 +23,7 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use serde::Deserialize;
 
+use crate::runner::IoCost;
 use crate::runtime::analysis::{
   aggregate_subtask_results, run_generate_outputs, run_judge, run_prepare_solution, run_validator,
 };
@@ -107,6 +108,9 @@ struct TestCaseMaterial {
   official_data_tar_path: PathBuf,
   tick_limit: u64,
   memory_limit: u64,
+  stack_limit: Option<u64>,
+  io_cost: IoCost,
+  random_seed: Option<u64>,
   groups: Vec<String>,
   trait_hints: BTreeMap<String, bool>,
 }
@@ -268,6 +272,9 @@ fn run_impl(opts: &UojOpts) -> Result<()> {
           official_data_path: &test_case.official_data_tar_path,
           tick_limit: test_case.tick_limit,
           memory_limit: test_case.memory_limit,
+          stack_limit: test_case.stack_limit,
+          io_cost: test_case.io_cost,
+          random_seed: test_case.random_seed,
           groups: test_case.groups.clone(),
           trait_hints: test_case.trait_hints.clone(),
         },
@@ -398,6 +405,8 @@ fn write_result(
     name: problem.name.clone(),
     tick_limit: problem.tick_limit,
     memory_limit: problem.memory_limit,
+    stack_limit: problem.stack_limit,
+    io_cost: problem.io_cost,
     file_size_limit: problem.file_size_limit,
     full_score: problem.full_score,
     checker: problem.checker.clone(),
@@ -412,6 +421,8 @@ fn write_result(
         input_file: None,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
         groups: test_case.groups.clone(),
         trait_hints: runtime_traits
           .get(&test_case.name)
@@ -419,6 +430,7 @@ fn write_result(
           .unwrap_or_default(),
         generator: None,
         arguments: None,
+        random_seed: None,
       })
       .collect(),
     subtasks: problem.subtasks.clone(),
@@ -426,7 +438,12 @@ fn write_result(
     checker_tests: Vec::new(),
     validator_tests: Vec::new(),
   };
-  let subtask_reports = aggregate_subtask_results(&scoring_problem, test_case_reports, traits);
+  let subtask_reports = aggregate_subtask_results(
+    &scoring_problem,
+    test_case_reports,
+    traits,
+    &BTreeSet::new(),
+  );
 
   let mut total_score = 0.0;
   let mut max_memory = 0u64;
@@ -599,6 +616,9 @@ fn load_normal_test_cases(
         official_data_tar_path,
         tick_limit: test_case.tick_limit,
         memory_limit: test_case.memory_limit,
+        stack_limit: test_case.stack_limit,
+        io_cost: test_case.io_cost,
+        random_seed: test_case.random_seed,
         groups: test_case.groups.clone(),
         trait_hints: loaded.validation.traits,
       })
@@ -638,6 +658,9 @@ fn load_normal_test_cases(
       official_data_tar_path,
       tick_limit: problem.tick_limit,
       memory_limit: problem.memory_limit,
+      stack_limit: problem.stack_limit,
+      io_cost: problem.io_cost,
+      random_seed: None,
       groups: Vec::new(),
       trait_hints: loaded.validation.traits,
     });
@@ -733,10 +756,13 @@ fn run_custom_test_mode(ctx: CustomTestContext<'_>) -> Result<()> {
     input_file: Some(ctx.input_path.to_string_lossy().into_owned()),
     tick_limit: ctx.problem.tick_limit,
     memory_limit: ctx.problem.memory_limit,
+    stack_limit: ctx.problem.stack_limit,
+    io_cost: ctx.problem.io_cost,
     groups: Vec::new(),
     trait_hints: validation.traits,
     generator: None,
     arguments: None,
+    random_seed: None,
   };
 
   let official_outputs_dir = run_generate_outputs(
@@ -776,10 +802,13 @@ fn run_hack_mode(ctx: HackModeContext<'_>) -> Result<()> {
     input_file: Some(hack_input_path.to_string_lossy().into_owned()),
     tick_limit: ctx.problem.tick_limit,
     memory_limit: ctx.problem.memory_limit,
+    stack_limit: ctx.problem.stack_limit,
+    io_cost: ctx.problem.io_cost,
     groups: Vec::new(),
     trait_hints: BTreeMap::new(),
     generator: None,
     arguments: None,
+    random_seed: None,
   };
 
   let validation = run_validator(ctx.runtime_problem, &hack_input_path, 1)
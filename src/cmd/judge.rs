@@ -21,8 +21,9 @@ use clap::Parser;
 use crate::interactive;
 use crate::report::JudgeCliReport;
 use crate::runtime::analysis::analyze_problem;
+use crate::runtime::cache::ResultCache;
 use crate::runtime::metadata::load_ad_hoc_problem_spec;
-use crate::runtime::types::RuntimeOptions;
+use crate::runtime::types::{RuntimeOptions, TestSelection};
 use crate::runtime::workspace::RuntimeWorkspace;
 
 #[derive(Parser)]
@@ -46,6 +47,20 @@ pub struct JudgeOpts {
   /// Stop runtime analysis as soon as one failure is detected.
   #[arg(long)]
   pub stop_on_failure: bool,
+
+  /// Rerun every validator, checker, and judge step instead of reusing cached reports.
+  #[arg(long)]
+  pub no_cache: bool,
+
+  /// Judge only testcases whose name matches this glob, where `*` and `?` are wildcards. May be
+  /// repeated.
+  #[arg(long = "test", value_name = "GLOB")]
+  pub tests: Vec<String>,
+
+  /// Judge only testcases of this subtask, counted from 0. May be repeated. Membership comes from
+  /// validator traits, so other testcases are still generated and validated.
+  #[arg(long = "subtask", value_name = "INDEX")]
+  pub subtasks: Vec<usize>,
 }
 
 /// Executes the ad-hoc source judging command.
@@ -65,6 +80,11 @@ pub fn run(judge_opts: &JudgeOpts) -> Result<()> {
     RuntimeOptions::new(judge_opts.jobs)
       .with_progress(progress)
       .with_stop_on_failure(judge_opts.stop_on_failure)
+      .with_result_cache(ResultCache::open_default(judge_opts.no_cache))
+      .with_test_selection(TestSelection::new(
+        judge_opts.tests.iter().cloned(),
+        judge_opts.subtasks.iter().copied(),
+      )?)
       .with_solution_names([ad_hoc_name.clone()]),
   )?;
   let solution = runtime
@@ -0,0 +1,705 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::format::{format_size, format_tick};
+use crate::interactive;
+use crate::runtime::analysis::analyze_problem;
+use crate::runtime::cache::ResultCache;
+use crate::runtime::metadata::load_problem_spec;
+use crate::runtime::prediction::{PredictionQuery, PredictionSource};
+use crate::runtime::types::{
+  JudgeReport, JudgeStatus, ProblemSpec, RuntimeOptions, RuntimeSolutionData,
+};
+use crate::runtime::workspace::RuntimeWorkspace;
+
+// Intended runs should stay this far below the limit, and unintended runs this far above it.
+const THIN_MARGIN: f64 = 1.5;
+
+#[derive(Parser)]
+/// Options for tick and memory headroom analysis.
+pub struct LimitsOpts {
+  /// Problem name to analyze, e.g. `aPlusB`.
+  #[arg(long, short, default_value = "default")]
+  pub problem: String,
+
+  /// Number of parallel jobs to use during runtime analysis.
+  #[arg(short = 'j', long = "jobs")]
+  pub jobs: Option<usize>,
+
+  /// Multiply every testcase tick limit by this factor while measuring, so solutions predicted to
+  /// exceed the limit show by how much.
+  #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u64).range(1..))]
+  pub tick_headroom: u64,
+
+  /// Rerun every validator, checker, and judge step instead of reusing cached reports.
+  #[arg(long)]
+  pub no_cache: bool,
+
+  /// Print the report as JSON instead of a table.
+  #[arg(long)]
+  pub json: bool,
+}
+
+/// Subtasks each solution's predictions expect it to pass or to exceed the tick limit on.
+#[derive(Debug, Default)]
+struct Expectations {
+  pass: BTreeSet<(String, usize)>,
+  exceed: BTreeSet<(String, usize)>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Highest resource usage among a set of solutions on one subtask.
+struct Peak {
+  solution: String,
+  test_case: String,
+  value: u64,
+  /// Usage divided by the testcase's configured limit.
+  ratio: f64,
+  /// Whether the run was stopped at the measured limit, making `value` a lower bound.
+  at_least: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Peak tick and memory usage of a set of solutions on one subtask.
+struct Usage {
+  tick: Peak,
+  memory: Peak,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Uniform tick limits that let every intended run pass and every unintended run exceed.
+///
+/// A limit separates the solutions when `lower <= limit < upper`.
+struct TickWindow {
+  lower: Option<u64>,
+  upper: Option<u64>,
+  /// Whether `upper` is a lower bound because the run hit the measured limit.
+  upper_at_least: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Headroom of one subtask.
+struct SubtaskLimits {
+  index: usize,
+  main: Option<Usage>,
+  /// Solutions predicted to pass, which may include the main correct solution.
+  predicted_pass: Option<Usage>,
+  /// Fastest solution predicted to exceed the tick limit, by its slowest testcase.
+  fastest_predicted_exceed: Option<Peak>,
+  window: TickWindow,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Headroom of every subtask and the tick limit suggested from it.
+struct LimitsReport {
+  subtasks: Vec<SubtaskLimits>,
+  window: TickWindow,
+  suggested_tick_limit: Option<u64>,
+  warnings: Vec<String>,
+}
+
+/// Executes the tick and memory headroom analysis command.
+pub fn run(opts: &LimitsOpts) -> Result<()> {
+  let mut problem = load_problem_spec(&opts.problem)?;
+  let configured = problem.clone();
+  problem.tick_limit = problem.tick_limit.saturating_mul(opts.tick_headroom);
+  for test_case in &mut problem.test_cases {
+    test_case.tick_limit = test_case.tick_limit.saturating_mul(opts.tick_headroom);
+  }
+  let expectations = classify_predictions(&opts.problem, &problem)?;
+
+  let workspace = RuntimeWorkspace::new()?;
+  let progress = interactive::create_progress("Problem", Some(&problem.name));
+  info!(
+    "Measuring problem {} with tick limits raised {}x",
+    problem.name, opts.tick_headroom
+  );
+  let runtime = analyze_problem(
+    &problem,
+    &workspace,
+    RuntimeOptions::new(opts.jobs)
+      .with_progress(progress)
+      .with_result_cache(ResultCache::open_default(opts.no_cache)),
+  )?;
+  let report = summarize(&configured, &runtime.solutions, &expectations);
+
+  if opts.json {
+    println!("{}", serde_json::to_string(&report)?);
+  } else {
+    println!("{}", report.render_human_readable());
+    for warning in &report.warnings {
+      warn!("{warning}");
+    }
+  }
+  Ok(())
+}
+
+/// Asks Nix whether each prediction accepts a fully accepted subtask and a time-limit-exceeded
+/// one; predictions accepting exactly one of them state an expectation.
+fn classify_predictions(selector: &str, problem: &ProblemSpec) -> Result<Expectations> {
+  let predicted = problem
+    .solutions
+    .iter()
+    .flat_map(|solution| {
+      solution
+        .subtask_predictions
+        .iter()
+        .map(|&subtask| (solution.name.clone(), subtask))
+    })
+    .collect::<BTreeSet<_>>();
+  if predicted.is_empty() {
+    return Ok(Expectations::default());
+  }
+  let queries = predicted
+    .iter()
+    .flat_map(|(solution, subtask)| {
+      [
+        (1.0, JudgeStatus::Accepted),
+        (0.0, JudgeStatus::TimeLimitExceeded),
+      ]
+      .map(|(score, status)| PredictionQuery {
+        solution: solution.clone(),
+        subtask: *subtask,
+        score,
+        statuses: vec![status],
+      })
+    })
+    .collect::<Vec<_>>();
+  let verdicts = PredictionSource::Problem(selector.to_string()).nix_evaluator()(&queries)
+    .context("Failed to classify subtask predictions")?;
+  if verdicts.len() != queries.len() {
+    bail!(
+      "Subtask prediction evaluation returned {} results for {} queries",
+      verdicts.len(),
+      queries.len()
+    );
+  }
+
+  let mut expectations = Expectations::default();
+  for (key, verdicts) in predicted.into_iter().zip(verdicts.chunks(2)) {
+    match (verdicts[0], verdicts[1]) {
+      (true, false) => expectations.pass.insert(key),
+      (false, true) => expectations.exceed.insert(key),
+      _ => false,
+    };
+  }
+  Ok(expectations)
+}
+
+fn summarize(
+  problem: &ProblemSpec,
+  solutions: &BTreeMap<String, RuntimeSolutionData>,
+  expectations: &Expectations,
+) -> LimitsReport {
+  let limits = problem
+    .test_cases
+    .iter()
+    .map(|test_case| {
+      (
+        test_case.name.as_str(),
+        (test_case.tick_limit, test_case.memory_limit),
+      )
+    })
+    .collect::<BTreeMap<_, _>>();
+  let mut warnings = Vec::new();
+  let subtasks = (0..problem.subtasks.len())
+    .map(|index| {
+      let main = usage(
+        &subtask_runs(
+          solutions,
+          [problem.main_correct_solution.as_str()],
+          index,
+        ),
+        &limits,
+      );
+      let predicted_pass = usage(
+        &subtask_runs(solutions, expected(&expectations.pass, index), index),
+        &limits,
+      );
+      let fastest_predicted_exceed =
+        subtask_runs(solutions, expected(&expectations.exceed, index), index)
+          .into_iter()
+          .filter_map(|(solution, reports)| {
+            peak(solution, reports, Resource::Tick, &limits)
+          })
+          .min_by_key(|peak| peak.value);
+
+      let lower = [&main, &predicted_pass]
+        .into_iter()
+        .flatten()
+        .map(|usage| usage.tick.value)
+        .max();
+      let window = TickWindow {
+        lower,
+        upper: fastest_predicted_exceed.as_ref().map(|peak| peak.value),
+        upper_at_least: fastest_predicted_exceed
+          .as_ref()
+          .is_some_and(|peak| peak.at_least),
+      };
+
+      let subtask = format!("Subtask #{index}");
+      let mut intended = [&main, &predicted_pass]
+        .into_iter()
+        .flatten()
+        .map(|usage| &usage.tick)
+        .collect::<Vec<_>>();
+      // The main correct solution is often predicted to pass as well.
+      intended.dedup_by(|left, right| left.solution == right.solution);
+      for Peak {
+        solution, ratio, ..
+      } in intended
+      {
+        if *ratio > 1.0 {
+          warnings.push(format!(
+            "{subtask}: `{solution}` exceeds the configured tick limit ({:.0}%)",
+            ratio * 100.0
+          ));
+        } else if *ratio * THIN_MARGIN > 1.0 {
+          warnings.push(format!(
+            "{subtask}: `{solution}` uses {:.0}% of the configured tick limit",
+            ratio * 100.0
+          ));
+        }
+      }
+      if let Some(Peak {
+        solution, ratio, ..
+      }) = &fastest_predicted_exceed
+      {
+        if *ratio <= 1.0 {
+          warnings.push(format!(
+            "{subtask}: `{solution}` is predicted to exceed the tick limit but stays within it ({:.0}%)",
+            ratio * 100.0
+          ));
+        } else if *ratio < THIN_MARGIN {
+          warnings.push(format!(
+            "{subtask}: `{solution}` exceeds the configured tick limit by only {:.0}%",
+            (ratio - 1.0) * 100.0
+          ));
+        }
+      }
+      if let Some(warning) = window_warning(&subtask, &window) {
+        warnings.push(warning);
+      }
+
+      SubtaskLimits {
+        index,
+        main,
+        predicted_pass,
+        fastest_predicted_exceed,
+        window,
+      }
+    })
+    .collect::<Vec<_>>();
+
+  let tightest = subtasks
+    .iter()
+    .map(|subtask| subtask.window)
+    .filter(|window| window.upper.is_some())
+    .min_by_key(|window| window.upper)
+    .unwrap_or_default();
+  let window = TickWindow {
+    lower: subtasks
+      .iter()
+      .filter_map(|subtask| subtask.window.lower)
+      .max(),
+    ..tightest
+  };
+  if subtasks.len() > 1
+    && let Some(warning) = window_warning("Problem", &window)
+  {
+    warnings.push(warning);
+  }
+  LimitsReport {
+    subtasks,
+    window,
+    suggested_tick_limit: suggest_tick_limit(&window),
+    warnings,
+  }
+}
+
+fn expected(set: &BTreeSet<(String, usize)>, index: usize) -> impl Iterator<Item = &str> {
+  set
+    .iter()
+    .filter(move |(_, subtask)| *subtask == index)
+    .map(|(solution, _)| solution.as_str())
+}
+
+/// Returns the testcase reports of each named solution on one subtask.
+fn subtask_runs<'a>(
+  solutions: &'a BTreeMap<String, RuntimeSolutionData>,
+  names: impl IntoIterator<Item = &'a str>,
+  index: usize,
+) -> Vec<(&'a str, &'a BTreeMap<String, JudgeReport>)> {
+  names
+    .into_iter()
+    .filter_map(|name| {
+      let result = solutions.get(name)?.subtask_results.get(index)?;
+      Some((name, &result.test_cases))
+    })
+    .collect()
+}
+
+fn usage(
+  runs: &[(&str, &BTreeMap<String, JudgeReport>)],
+  limits: &BTreeMap<&str, (u64, u64)>,
+) -> Option<Usage> {
+  let highest = |resource| {
+    runs
+      .iter()
+      .filter_map(|(solution, reports)| peak(solution, reports, resource, limits))
+      .max_by(|left, right| left.ratio.total_cmp(&right.ratio))
+  };
+  Some(Usage {
+    tick: highest(Resource::Tick)?,
+    memory: highest(Resource::Memory)?,
+  })
+}
+
+#[derive(Clone, Copy)]
+enum Resource {
+  Tick,
+  Memory,
+}
+
+impl Resource {
+  fn value(self, report: &JudgeReport) -> u64 {
+    match self {
+      Self::Tick => report.tick,
+      Self::Memory => report.memory,
+    }
+  }
+
+  fn limit(self, (tick_limit, memory_limit): (u64, u64)) -> u64 {
+    match self {
+      Self::Tick => tick_limit,
+      Self::Memory => memory_limit,
+    }
+  }
+
+  fn exceeded(self) -> JudgeStatus {
+    match self {
+      Self::Tick => JudgeStatus::TimeLimitExceeded,
+      Self::Memory => JudgeStatus::MemoryLimitExceeded,
+    }
+  }
+}
+
+/// Returns the testcase where `solution` used the most of one resource relative to its limit,
+/// breaking ties by absolute value.
+fn peak(
+  solution: &str,
+  reports: &BTreeMap<String, JudgeReport>,
+  resource: Resource,
+  limits: &BTreeMap<&str, (u64, u64)>,
+) -> Option<Peak> {
+  reports
+    .iter()
+    .map(|(test_case, report)| {
+      let value = resource.value(report);
+      Peak {
+        solution: solution.to_string(),
+        test_case: test_case.clone(),
+        value,
+        ratio: limits
+          .get(test_case.as_str())
+          .map(|&limits| resource.limit(limits))
+          .filter(|&limit| limit > 0)
+          .map_or(0.0, |limit| value as f64 / limit as f64),
+        at_least: report.status == resource.exceeded(),
+      }
+    })
+    .max_by(|left, right| {
+      left
+        .ratio
+        .total_cmp(&right.ratio)
+        .then(left.value.cmp(&right.value))
+    })
+}
+
+fn window_warning(subject: &str, window: &TickWindow) -> Option<String> {
+  let (Some(lower), Some(upper)) = (window.lower, window.upper) else {
+    return None;
+  };
+  if upper <= lower {
+    Some(format!(
+      "{subject}: no tick limit separates intended runs (up to {}) from runs predicted to exceed it (from {})",
+      format_tick(lower),
+      format_tick(upper)
+    ))
+  } else if (upper as f64) < lower as f64 * THIN_MARGIN * THIN_MARGIN && !window.upper_at_least {
+    Some(format!(
+      "{subject}: thin tick limit window {}..{}, leaving less than {THIN_MARGIN}x margin on each side",
+      format_tick(lower),
+      format_tick(upper)
+    ))
+  } else {
+    None
+  }
+}
+
+/// Picks the geometric middle of the window, or twice the slowest intended run when nothing
+/// bounds it from above.
+fn suggest_tick_limit(window: &TickWindow) -> Option<u64> {
+  let lower = window.lower?;
+  match window.upper {
+    Some(upper) if upper > lower => Some(((lower as f64) * (upper as f64)).sqrt() as u64),
+    Some(_) => None,
+    None => Some(lower.saturating_mul(2)),
+  }
+}
+
+impl LimitsReport {
+  fn render_human_readable(&self) -> String {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+    table.set_header(vec![
+      "#",
+      "Main Correct",
+      "Predicted Pass",
+      "Fastest Predicted TLE",
+      "Tick Window",
+    ]);
+    for subtask in &self.subtasks {
+      table.add_row(vec![
+        Cell::new(subtask.index),
+        Cell::new(render_usage(subtask.main.as_ref())),
+        Cell::new(render_usage(subtask.predicted_pass.as_ref())),
+        Cell::new(
+          subtask
+            .fastest_predicted_exceed
+            .as_ref()
+            .map_or("-".to_string(), |peak| render_peak(peak, format_tick)),
+        ),
+        Cell::new(render_window(&subtask.window)),
+      ]);
+    }
+
+    let mut output = String::from("Subtask Headroom:\n");
+    output.push_str(&table.to_string());
+    output.push_str(&format!(
+      "\n\nTick window: {}\n",
+      render_window(&self.window)
+    ));
+    match self.suggested_tick_limit {
+      Some(limit) => output.push_str(&format!("Suggested tick limit: {}", format_tick(limit))),
+      None => output.push_str("Suggested tick limit: none"),
+    }
+    output
+  }
+}
+
+fn render_usage(usage: Option<&Usage>) -> String {
+  usage.map_or("-".to_string(), |usage| {
+    format!(
+      "{}\n{}",
+      render_peak(&usage.tick, format_tick),
+      render_peak(&usage.memory, format_size)
+    )
+  })
+}
+
+fn render_peak(peak: &Peak, format: fn(u64) -> String) -> String {
+  format!(
+    "{}{} ({:.0}%) `{}` on {}",
+    if peak.at_least { ">=" } else { "" },
+    format(peak.value),
+    peak.ratio * 100.0,
+    peak.solution,
+    peak.test_case
+  )
+}
+
+fn render_window(window: &TickWindow) -> String {
+  match (window.lower, window.upper) {
+    (None, _) => "-".to_string(),
+    (Some(lower), None) => format!(">= {}", format_tick(lower)),
+    (Some(lower), Some(upper)) => format!(
+      "{}..{}{}",
+      format_tick(lower),
+      if window.upper_at_least { ">=" } else { "" },
+      format_tick(upper)
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::runtime::types::{ScoringMethod, SubtaskRuntimeReport};
+
+  // Matches the default `--tick-headroom` applied to the 1000-tick limit of `problem`.
+  const MEASURED_TICK_LIMIT: u64 = 4000;
+
+  fn solution(ticks: &[(&str, u64)]) -> RuntimeSolutionData {
+    let test_cases = ticks
+      .iter()
+      .map(|&(name, tick)| {
+        let status = if tick >= MEASURED_TICK_LIMIT {
+          JudgeStatus::TimeLimitExceeded
+        } else {
+          JudgeStatus::Accepted
+        };
+        (
+          name.to_string(),
+          JudgeReport {
+            status,
+            score: 1.0,
+            message: String::new(),
+            tick,
+            memory: 1 << 18,
+            backtrace: Vec::new(),
+            outputs: String::new(),
+          },
+        )
+      })
+      .collect::<BTreeMap<_, _>>();
+    RuntimeSolutionData {
+      test_case_results: test_cases.clone(),
+      subtask_results: vec![SubtaskRuntimeReport {
+        test_cases,
+        statuses: Vec::new(),
+        raw_score: 1.0,
+        scaled_score: 1.0,
+        skipped_test_cases: BTreeSet::new(),
+      }],
+      score: 1.0,
+    }
+  }
+
+  fn problem() -> ProblemSpec {
+    let mut problem = ProblemSpec::fixture(ScoringMethod::Min);
+    problem.tick_limit = 1000;
+    problem.memory_limit = 1 << 20;
+    for test_case in &mut problem.test_cases {
+      test_case.tick_limit = 1000;
+      test_case.memory_limit = 1 << 20;
+    }
+    problem
+  }
+
+  #[test]
+  fn window_separates_intended_from_exceeding_runs() {
+    let solutions = BTreeMap::from([
+      ("std".to_string(), solution(&[("a", 100), ("b", 200)])),
+      ("fast".to_string(), solution(&[("a", 300), ("b", 50)])),
+      ("brute".to_string(), solution(&[("a", 90), ("b", 4000)])),
+      ("slow".to_string(), solution(&[("a", 2500), ("b", 10)])),
+    ]);
+    let expectations = Expectations {
+      pass: BTreeSet::from([("fast".to_string(), 0)]),
+      exceed: BTreeSet::from([("brute".to_string(), 0), ("slow".to_string(), 0)]),
+    };
+
+    let report = summarize(&problem(), &solutions, &expectations);
+    let subtask = &report.subtasks[0];
+    assert_eq!(subtask.main.as_ref().unwrap().tick.value, 200);
+    assert_eq!(
+      subtask.predicted_pass.as_ref().unwrap().tick.solution,
+      "fast"
+    );
+    let fastest = subtask.fastest_predicted_exceed.as_ref().unwrap();
+    assert_eq!(fastest.solution, "slow");
+    assert!(!fastest.at_least, "2500 ticks fit under the raised limit");
+    assert_eq!(
+      report.window,
+      TickWindow {
+        lower: Some(300),
+        upper: Some(2500),
+        upper_at_least: false,
+      }
+    );
+    assert_eq!(report.suggested_tick_limit, Some(866));
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+  }
+
+  #[test]
+  fn runs_stopped_at_the_raised_limit_are_lower_bounds() {
+    let solutions = BTreeMap::from([
+      ("std".to_string(), solution(&[("a", 500), ("b", 200)])),
+      (
+        "brute".to_string(),
+        solution(&[("a", 90), ("b", MEASURED_TICK_LIMIT)]),
+      ),
+    ]);
+    let expectations = Expectations {
+      pass: BTreeSet::new(),
+      exceed: BTreeSet::from([("brute".to_string(), 0)]),
+    };
+
+    let report = summarize(&problem(), &solutions, &expectations);
+    assert_eq!(
+      report.window,
+      TickWindow {
+        lower: Some(500),
+        upper: Some(MEASURED_TICK_LIMIT),
+        upper_at_least: true,
+      }
+    );
+    assert_eq!(report.suggested_tick_limit, Some(1414));
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+  }
+
+  #[test]
+  fn thin_margins_warn() {
+    let solutions = BTreeMap::from([
+      ("std".to_string(), solution(&[("a", 800), ("b", 200)])),
+      ("brute".to_string(), solution(&[("a", 90), ("b", 900)])),
+    ]);
+    let expectations = Expectations {
+      pass: BTreeSet::new(),
+      exceed: BTreeSet::from([("brute".to_string(), 0)]),
+    };
+
+    let report = summarize(&problem(), &solutions, &expectations);
+    assert_eq!(report.suggested_tick_limit, Some(848));
+    assert_eq!(
+      report.warnings,
+      [
+        "Subtask #0: `std` uses 80% of the configured tick limit",
+        "Subtask #0: `brute` is predicted to exceed the tick limit but stays within it (90%)",
+        "Subtask #0: thin tick limit window 800..900, leaving less than 1.5x margin on each side",
+      ]
+    );
+  }
+
+  #[test]
+  fn peaks_compare_usage_relative_to_each_testcase_limit() {
+    let mut problem = problem();
+    problem.test_cases[1].tick_limit = 10_000;
+    let solutions = BTreeMap::from([("std".to_string(), solution(&[("a", 800), ("b", 2000)]))]);
+    let expectations = Expectations {
+      pass: BTreeSet::new(),
+      exceed: BTreeSet::new(),
+    };
+
+    let report = summarize(&problem, &solutions, &expectations);
+    let tick = &report.subtasks[0].main.as_ref().unwrap().tick;
+    assert_eq!(tick.test_case, "a");
+    assert_eq!(tick.value, 800);
+  }
+}
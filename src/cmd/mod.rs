@@ -17,14 +17,22 @@
 pub mod build;
 /// Contest build command.
 pub mod build_contest;
+/// Result cache maintenance commands.
+pub mod cache;
 /// Source compilation commands and shared options.
 pub mod compile;
+/// Static Wasm module analysis command.
+pub mod inspect_wasm;
 /// Exported judge-system helper commands.
 pub mod integration_judge;
 /// Ad-hoc source judging command.
 pub mod judge;
+/// Tick and memory headroom analysis command.
+pub mod limits;
 /// Source include-path rewriting command.
 pub mod patch;
+/// Per-function tick profiling command.
+pub mod profile;
 /// Local source execution command.
 pub mod run;
 /// Deterministic WASIp1 session command.
@@ -33,3 +41,5 @@ pub mod run_wasm;
 pub mod source_config;
 /// Generated-testcase stress command.
 pub mod stress;
+/// Test effectiveness report command.
+pub mod test_matrix;
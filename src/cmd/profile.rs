@@ -0,0 +1,159 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::{
+  fs,
+  io::{BufWriter, Write},
+  num::NonZeroU64,
+  path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+use tracing::info;
+
+use crate::{
+  cmd::{
+    compile::{SourceCompileOpts, compile_source},
+    run::{parse_environment_variable, resolve_cwd},
+  },
+  format::{format_size, format_tick},
+  runner::{self, Clock, LocalProgramRequest, Profile, WasmFeature},
+};
+
+/// Options for profiling one source file's tick usage.
+#[derive(Parser)]
+pub struct ProfileOpts {
+  /// Source and problem options used for compilation.
+  #[command(flatten)]
+  pub source: SourceCompileOpts,
+
+  /// Override the runtime tick limit for the profiled program.
+  #[arg(long, short)]
+  pub tick_limit: Option<u64>,
+
+  /// Override the runtime memory limit in bytes.
+  #[arg(long, short)]
+  pub memory_limit: Option<u64>,
+
+  /// Override the runtime stack limit in bytes, which defaults to the memory limit.
+  #[arg(long)]
+  pub stack_limit: Option<u64>,
+
+  /// Limit bytes written to each inherited stdout and stderr stream.
+  #[arg(long)]
+  pub file_size_limit: Option<usize>,
+
+  /// Host directory exposed as the program's file system root.
+  #[arg(long)]
+  pub cwd: Option<PathBuf>,
+
+  /// Environment variable visible to the program, as `KEY=VALUE`. May be repeated.
+  #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_environment_variable)]
+  pub environment: Vec<(String, String)>,
+
+  /// Seed for the program's deterministic `random_get` stream.
+  #[arg(long, default_value_t = 0)]
+  pub seed: u64,
+
+  /// Advance the program's clocks by one second per this many consumed ticks instead of
+  /// freezing them at zero.
+  #[arg(long)]
+  pub ticks_per_second: Option<NonZeroU64>,
+
+  /// Wasm proposal accepted beyond Hull's strict MVP feature set, such as `simd`. May be
+  /// repeated.
+  #[arg(long = "wasm-feature", value_name = "FEATURE")]
+  pub wasm_features: Vec<WasmFeature>,
+
+  /// Collapsed-stack output path, readable by `flamegraph.pl` and `inferno-flamegraph`.
+  #[arg(long, short, default_value = "profile.folded")]
+  pub output: PathBuf,
+
+  /// Minimum ticks between two stack samples.
+  #[arg(long, default_value_t = 10_000)]
+  pub sample_interval: u64,
+
+  /// Number of functions listed in the self-tick summary.
+  #[arg(long, default_value_t = 20)]
+  pub top: usize,
+
+  /// Arguments to pass to the profiled program.
+  #[arg(trailing_var_arg = true)]
+  pub args: Vec<String>,
+}
+
+/// Compiles, runs, and profiles one source file in Hull's WASM runtime.
+pub fn run(opts: &ProfileOpts) -> Result<()> {
+  let wasm_path = compile_source(&opts.source)?;
+  let cwd = resolve_cwd(opts.cwd.as_deref())?;
+  let memory_limit = opts.memory_limit.unwrap_or(runner::TOOL_MEMORY_LIMIT);
+
+  info!("Profiling program");
+  let (result, profile) = runner::profile_local(
+    LocalProgramRequest {
+      wasm_path: PathBuf::from(wasm_path),
+      arguments: opts.args.clone(),
+      environment: opts.environment.iter().cloned().collect(),
+      tick_limit: opts.tick_limit.unwrap_or(runner::TOOL_TICK_LIMIT),
+      memory_limit,
+      stack_limit: opts.stack_limit.unwrap_or(memory_limit),
+      file_size_limit: opts.file_size_limit.unwrap_or(runner::TOOL_FILE_SIZE_LIMIT),
+      random_seed: opts.seed,
+      clock: opts
+        .ticks_per_second
+        .map_or(Clock::Frozen, |ticks_per_second| Clock::Ticks {
+          ticks_per_second,
+        }),
+      features: opts.wasm_features.clone(),
+      cwd: Some(cwd),
+    },
+    opts.sample_interval,
+  )?;
+
+  let mut output = BufWriter::new(
+    fs::File::create(&opts.output)
+      .with_context(|| format!("Failed to create {}", opts.output.display()))?,
+  );
+  profile.write_collapsed(&mut output)?;
+  output.flush()?;
+
+  eprintln!("Status: {}", result.status);
+  eprintln!("Tick: {}", format_tick(result.tick));
+  eprintln!("Memory: {}", format_size(result.memory));
+  eprintln!("Stack: {}", format_size(result.stack));
+  if result.error_message.is_some() {
+    eprintln!("Error message:\n{}", result.diagnostic());
+  }
+  eprintln!("{}", summary_table(&profile, opts.top));
+  info!("Collapsed stacks written to {}", opts.output.display());
+  Ok(())
+}
+
+fn summary_table(profile: &Profile, top: usize) -> Table {
+  let total = profile.total().max(1);
+  let mut table = Table::new();
+  table.load_preset(UTF8_FULL_CONDENSED);
+  table.set_header(vec!["Function", "Self Tick", "Share"]);
+  for (function, ticks) in profile.self_ticks().into_iter().take(top) {
+    table.add_row(vec![
+      Cell::new(function),
+      Cell::new(format_tick(ticks)),
+      Cell::new(format!("{:.2}%", ticks as f64 * 100.0 / total as f64)),
+    ]);
+  }
+  table
+}
@@ -13,7 +13,10 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::path::{Path, PathBuf};
+use std::{
+  num::NonZeroU64,
+  path::{Path, PathBuf},
+};
 
 use anyhow::{Context, Result};
 use clap::Parser;
@@ -21,7 +24,7 @@ use tracing::info;
 
 use crate::{
   cmd::compile::{SourceCompileOpts, compile_source},
-  runner::{self, LocalProgramRequest},
+  runner::{self, Clock, LocalProgramRequest, WasmFeature},
 };
 
 /// Options for compiling and running one source file.
@@ -39,6 +42,10 @@ pub struct RunOpts {
   #[arg(long, short)]
   pub memory_limit: Option<u64>,
 
+  /// Override the runtime stack limit in bytes, which defaults to the memory limit.
+  #[arg(long)]
+  pub stack_limit: Option<u64>,
+
   /// Limit bytes written to each inherited stdout and stderr stream.
   ///
   /// Ambient regular files exposed by `--cwd` are not subject to this limit.
@@ -53,6 +60,24 @@ pub struct RunOpts {
   #[arg(long)]
   pub cwd: Option<PathBuf>,
 
+  /// Environment variable visible to the program, as `KEY=VALUE`. May be repeated.
+  #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_environment_variable)]
+  pub environment: Vec<(String, String)>,
+
+  /// Seed for the program's deterministic `random_get` stream.
+  #[arg(long, default_value_t = 0)]
+  pub seed: u64,
+
+  /// Advance the program's clocks by one second per this many consumed ticks instead of
+  /// freezing them at zero.
+  #[arg(long)]
+  pub ticks_per_second: Option<NonZeroU64>,
+
+  /// Wasm proposal accepted beyond Hull's strict MVP feature set, such as `simd`. May be
+  /// repeated.
+  #[arg(long = "wasm-feature", value_name = "FEATURE")]
+  pub wasm_features: Vec<WasmFeature>,
+
   /// Arguments to pass to the executed program.
   #[arg(trailing_var_arg = true)]
   pub args: Vec<String>,
@@ -62,14 +87,24 @@ pub struct RunOpts {
 pub fn run(opts: &RunOpts) -> Result<()> {
   let wasm_path = compile_source(&opts.source)?;
   let cwd = resolve_cwd(opts.cwd.as_deref())?;
+  let memory_limit = opts.memory_limit.unwrap_or(runner::TOOL_MEMORY_LIMIT);
 
   info!("Running program");
   let result = runner::run_local(LocalProgramRequest {
     wasm_path: PathBuf::from(wasm_path),
     arguments: opts.args.clone(),
+    environment: opts.environment.iter().cloned().collect(),
     tick_limit: opts.tick_limit.unwrap_or(runner::TOOL_TICK_LIMIT),
-    memory_limit: opts.memory_limit.unwrap_or(runner::TOOL_MEMORY_LIMIT),
+    memory_limit,
+    stack_limit: opts.stack_limit.unwrap_or(memory_limit),
     file_size_limit: opts.file_size_limit.unwrap_or(runner::TOOL_FILE_SIZE_LIMIT),
+    random_seed: opts.seed,
+    clock: opts
+      .ticks_per_second
+      .map_or(Clock::Frozen, |ticks_per_second| Clock::Ticks {
+        ticks_per_second,
+      }),
+    features: opts.wasm_features.clone(),
     cwd: Some(cwd),
   })?;
 
@@ -82,15 +117,25 @@ pub fn run(opts: &RunOpts) -> Result<()> {
     }
     eprintln!("Tick: {}", format_tick(result.tick));
     eprintln!("Memory: {}", format_size(result.memory));
-    if let Some(error_message) = result.error_message {
-      eprintln!("Error message:\n{error_message}");
+    eprintln!("Stack: {}", format_size(result.stack));
+    if result.error_message.is_some() {
+      eprintln!("Error message:\n{}", result.diagnostic());
     }
   }
 
   Ok(())
 }
 
-fn resolve_cwd(cwd: Option<&Path>) -> Result<PathBuf> {
+/// Parses one `KEY=VALUE` command-line environment variable.
+pub fn parse_environment_variable(variable: &str) -> Result<(String, String), String> {
+  match variable.split_once('=') {
+    Some((name, value)) if !name.is_empty() => Ok((name.to_owned(), value.to_owned())),
+    _ => Err(format!("expected KEY=VALUE, got `{variable}`")),
+  }
+}
+
+/// Resolves the host directory exposed as a local program's working directory.
+pub fn resolve_cwd(cwd: Option<&Path>) -> Result<PathBuf> {
   let cwd = cwd
     .map(Path::to_path_buf)
     .map_or_else(std::env::current_dir, Ok)?;
@@ -126,4 +171,18 @@ mod tests {
     let file = tempfile::NamedTempFile::new().unwrap();
     assert!(resolve_cwd(Some(file.path())).is_err());
   }
+
+  #[test]
+  fn environment_variable_splits_at_first_equals() {
+    assert_eq!(
+      parse_environment_variable("ONLINE_JUDGE=a=b").unwrap(),
+      ("ONLINE_JUDGE".to_owned(), "a=b".to_owned())
+    );
+    assert_eq!(
+      parse_environment_variable("EMPTY=").unwrap(),
+      ("EMPTY".to_owned(), String::new())
+    );
+    assert!(parse_environment_variable("MISSING").is_err());
+    assert!(parse_environment_variable("=value").is_err());
+  }
 }
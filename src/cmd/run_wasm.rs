@@ -13,28 +13,46 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::path::PathBuf;
+use std::{io, os::unix::net::UnixListener, path::PathBuf};
 
 use anyhow::{Context, Result, bail};
 use clap::Parser;
+use tracing::info;
 
+use crate::platform::default_parallelism;
 use crate::runner::{self, RunStatus, SessionRequest};
 
 /// Options for executing one deterministic Wasm session request.
 #[derive(Parser)]
 pub struct RunWasmOpts {
   /// Strict JSON session request; relative paths resolve from this file's directory.
-  pub request_path: PathBuf,
+  #[arg(required_unless_present = "serve", conflicts_with = "serve")]
+  pub request_path: Option<PathBuf>,
+
+  /// Execute newline-delimited JSON requests from stdin and write one report line per request
+  /// to stdout, keeping engines and compiled modules loaded between requests.
+  #[arg(long)]
+  pub serve: bool,
+
+  /// Serve connections on this Unix socket instead of stdin and stdout.
+  #[arg(long, requires = "serve")]
+  pub socket: Option<PathBuf>,
+
+  /// Number of socket connections to serve at once. Defaults to the available parallelism.
+  #[arg(short = 'j', long = "jobs", requires = "socket")]
+  pub jobs: Option<usize>,
 }
 
 /// Executes the requested Wasm session and writes its metadata report.
+///
+/// When `HULL_RUN_WASM_SOCKET` names a session server, the request runs there instead.
 pub fn run(options: &RunWasmOpts) -> Result<()> {
-  let request_path = options.request_path.canonicalize().with_context(|| {
-    format!(
-      "Failed to resolve request {}",
-      options.request_path.display()
-    )
-  })?;
+  let Some(request_path) = &options.request_path else {
+    return serve(options);
+  };
+  let request_path = request_path
+    .canonicalize()
+    .with_context(|| format!("Failed to resolve request {}", request_path.display()))?;
   let request_bytes = std::fs::read(&request_path)
     .with_context(|| format!("Failed to read request {}", request_path.display()))?;
   let mut request: SessionRequest = serde_json::from_slice(&request_bytes)
@@ -47,7 +65,12 @@ pub fn run(options: &RunWasmOpts) -> Result<()> {
     .iter()
     .map(|program| (program.name.clone(), program.required_accepted))
     .collect::<Vec<_>>();
-  let report = runner::run_session(request);
+  let report = match std::env::var_os(runner::SESSION_SOCKET_ENVIRONMENT)
+    .filter(|socket| !socket.is_empty())
+  {
+    Some(socket) => runner::forward(socket.as_ref(), &request)?,
+    None => runner::run_session(request),
+  };
   runner::write_report(&report_path, &report)
     .with_context(|| format!("Failed to write session report {}", report_path.display()))?;
 
@@ -67,3 +90,20 @@ pub fn run(options: &RunWasmOpts) -> Result<()> {
 
   Ok(())
 }
+
+fn serve(options: &RunWasmOpts) -> Result<()> {
+  let base = std::env::current_dir().context("Failed to resolve the working directory")?;
+  match &options.socket {
+    Some(socket) => {
+      let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to bind session socket {}", socket.display()))?;
+      info!("Serving Wasm sessions on {}", socket.display());
+      runner::serve_socket(
+        listener,
+        base,
+        options.jobs.unwrap_or_else(default_parallelism),
+      )
+    }
+    None => runner::serve_stream(io::stdin().lock(), io::stdout().lock(), &base),
+  }
+}
@@ -26,9 +26,9 @@ use crate::{
   interactive,
   platform::default_parallelism,
   runner::{
-    DirectoryBinding, DirectoryPermissions, File, FilePermissions, FileSizeLimit, FileSystem,
-    InitialDescriptor, ProgramRequest, RunStatus, SessionRequest, TOOL_MEMORY_LIMIT,
-    TOOL_TICK_LIMIT, ToolLimit, run_session,
+    Clock, DirectoryBinding, DirectoryPermissions, File, FilePermissions, FileSizeLimit,
+    FileSystem, InitialDescriptor, IoCost, ProgramRequest, RunStatus, SessionRequest,
+    TOOL_MEMORY_LIMIT, TOOL_TICK_LIMIT, ToolLimit, run_session,
   },
   runtime::{
     analysis::analyze_problem,
@@ -78,6 +78,10 @@ pub struct StressOpts {
   #[arg(long, short)]
   pub memory_limit: Option<u64>,
 
+  /// Solution `random_get` seed for generated cases, derived from each case name by default.
+  #[arg(long)]
+  pub seed: Option<u64>,
+
   /// Extra arguments to pass to the generator after `--`.
   #[arg(allow_hyphen_values = true, last = true)]
   pub args: Vec<String>,
@@ -87,6 +91,7 @@ pub struct StressOpts {
 struct FailingTestCase {
   args: Vec<String>,
   failing_solution_name: String,
+  random_seed: u64,
   report: JudgeRunResult,
 }
 
@@ -185,6 +190,7 @@ pub fn run(opts: &StressOpts) -> Result<()> {
       generator_name: &opts.generator,
       tick_limit_override: opts.tick_limit,
       memory_limit_override: opts.memory_limit,
+      random_seed: opts.seed,
       round,
       options: RuntimeOptions::new(Some(jobs)).with_progress(progress.clone()),
     })?;
@@ -213,6 +219,7 @@ pub fn run(opts: &StressOpts) -> Result<()> {
           println!("      \"{}\"", arg.escape_default());
         }
         println!("    ];");
+        println!("    randomSeed = {};", case.random_seed);
         println!("  }};");
         println!();
         return Ok(());
@@ -267,10 +274,13 @@ fn run_stress_round(context: &StressRoundContext<'_>) -> Result<Option<FailingTe
             memory_limit: context
               .memory_limit_override
               .unwrap_or(context.problem.memory_limit),
+            stack_limit: context.problem.stack_limit,
+            io_cost: context.problem.io_cost,
             groups: Vec::new(),
             trait_hints: BTreeMap::new(),
             generator: Some(context.generator_name.to_string()),
             arguments: Some(generator_args.to_vec()),
+            random_seed: context.random_seed,
           }];
           dynamic_problem.validator_tests = Vec::new();
           dynamic_problem.checker_tests = Vec::new();
@@ -301,6 +311,7 @@ fn run_stress_round(context: &StressRoundContext<'_>) -> Result<Option<FailingTe
               return Ok(Some(FailingTestCase {
                 args: generator_args.to_vec(),
                 failing_solution_name: solution.name.clone(),
+                random_seed: dynamic_problem.test_cases[0].random_seed(),
                 report: JudgeRunResult {
                   status: solution_report.status.to_string(),
                   score: solution_report.score,
@@ -326,6 +337,7 @@ struct StressRoundContext<'a> {
   generator_name: &'a str,
   tick_limit_override: Option<u64>,
   memory_limit_override: Option<u64>,
+  random_seed: Option<u64>,
   round: u64,
   options: RuntimeOptions,
 }
@@ -360,15 +372,25 @@ fn generate_input(
       name: "generator".to_string(),
       wasm_path: std::path::PathBuf::from(generator_wasm),
       arguments: arguments.to_vec(),
+      environment: BTreeMap::new(),
       tick_limit: TOOL_TICK_LIMIT,
       memory_limit: TOOL_MEMORY_LIMIT,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".to_string(),
           permissions: DirectoryPermissions::ReadExecute,
         }],
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: vec![
         InitialDescriptor {
@@ -385,6 +407,7 @@ fn generate_input(
         },
       ],
     }],
+    terminate_on_exit: Vec::new(),
   };
   let mut session_report = run_session(request);
   if session_report.results.len() != 1 {
@@ -411,7 +434,7 @@ fn generate_input(
       "Generator failed while preparing stress input `{}` with status {:?}: {}\nStderr:\n{}",
       test_case_name,
       result.status,
-      result.error_message.as_deref().unwrap_or_default(),
+      result.diagnostic(),
       String::from_utf8_lossy(&stderr).trim()
     );
   }
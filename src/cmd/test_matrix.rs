@@ -0,0 +1,315 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use clap::Parser;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+use crate::interactive;
+use crate::runtime::analysis::analyze_problem;
+use crate::runtime::cache::ResultCache;
+use crate::runtime::metadata::load_problem_spec;
+use crate::runtime::types::{JudgeStatus, ProblemSpec, RuntimeOptions, RuntimeSolutionData};
+use crate::runtime::workspace::RuntimeWorkspace;
+
+#[derive(Parser)]
+/// Options for the test effectiveness report.
+pub struct TestMatrixOpts {
+  /// Problem name to analyze, e.g. `aPlusB`.
+  #[arg(long, short, default_value = "default")]
+  pub problem: String,
+
+  /// Number of parallel jobs to use during runtime analysis.
+  #[arg(short = 'j', long = "jobs")]
+  pub jobs: Option<usize>,
+
+  /// Rerun every validator, checker, and judge step instead of reusing cached reports.
+  #[arg(long)]
+  pub no_cache: bool,
+
+  /// Print the report as JSON instead of tables.
+  #[arg(long)]
+  pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Which testcases reject which wrong solutions, and which testcases matter.
+struct TestMatrixReport {
+  /// Solutions that fail at least one testcase, in name order.
+  wrong_solutions: Vec<String>,
+  /// Solutions that pass every judged testcase.
+  accepted_solutions: Vec<String>,
+  /// Verdicts of the wrong solutions on each testcase that rejects one of them, in testcase
+  /// order.
+  kills: Vec<TestKills>,
+  /// Testcases that reject no solution, in testcase order.
+  idle_test_cases: Vec<String>,
+  /// An irredundant set of testcases that still rejects every wrong solution, in testcase order.
+  minimal_test_cases: Vec<String>,
+  /// Wrong solutions rejected by exactly one testcase, mapped to that testcase.
+  single_kills: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Wrong solutions rejected by one testcase.
+struct TestKills {
+  test_case: String,
+  solutions: BTreeMap<String, JudgeStatus>,
+}
+
+/// Executes the test effectiveness report command.
+pub fn run(opts: &TestMatrixOpts) -> Result<()> {
+  let problem = load_problem_spec(&opts.problem)?;
+  let workspace = RuntimeWorkspace::new()?;
+  let progress = interactive::create_progress("Problem", Some(&problem.name));
+  let runtime = analyze_problem(
+    &problem,
+    &workspace,
+    RuntimeOptions::new(opts.jobs)
+      .with_progress(progress)
+      .with_result_cache(ResultCache::open_default(opts.no_cache)),
+  )?;
+  let report = TestMatrixReport::new(&problem, &runtime.solutions);
+
+  if opts.json {
+    println!("{}", serde_json::to_string(&report)?);
+  } else {
+    println!("{}", report.render_human_readable());
+  }
+  Ok(())
+}
+
+/// Returns whether a verdict rejects the solution; internal errors say nothing about it.
+fn kills(status: JudgeStatus) -> bool {
+  !matches!(status, JudgeStatus::Accepted | JudgeStatus::InternalError)
+}
+
+impl TestMatrixReport {
+  fn new(problem: &ProblemSpec, solutions: &BTreeMap<String, RuntimeSolutionData>) -> Self {
+    let (wrong, accepted): (Vec<_>, Vec<_>) = solutions.iter().partition(|(_, solution)| {
+      solution
+        .test_case_results
+        .values()
+        .any(|report| kills(report.status))
+    });
+
+    let mut kills_by_test = Vec::new();
+    let mut idle_test_cases = Vec::new();
+    for test_case in &problem.test_cases {
+      let killed = wrong
+        .iter()
+        .filter_map(|(name, solution)| {
+          let report = solution.test_case_results.get(&test_case.name)?;
+          kills(report.status).then(|| ((*name).clone(), report.status))
+        })
+        .collect::<BTreeMap<_, _>>();
+      if killed.is_empty() {
+        idle_test_cases.push(test_case.name.clone());
+      } else {
+        kills_by_test.push(TestKills {
+          test_case: test_case.name.clone(),
+          solutions: killed,
+        });
+      }
+    }
+
+    let mut killers = BTreeMap::<&str, Vec<&str>>::new();
+    for kills in &kills_by_test {
+      for solution in kills.solutions.keys() {
+        killers.entry(solution).or_default().push(&kills.test_case);
+      }
+    }
+    let single_kills = killers
+      .iter()
+      .filter(|(_, tests)| tests.len() == 1)
+      .map(|(solution, tests)| (solution.to_string(), tests[0].to_string()))
+      .collect();
+
+    Self {
+      wrong_solutions: wrong.iter().map(|(name, _)| (*name).clone()).collect(),
+      accepted_solutions: accepted.iter().map(|(name, _)| (*name).clone()).collect(),
+      minimal_test_cases: minimal_cover(&kills_by_test),
+      kills: kills_by_test,
+      idle_test_cases,
+      single_kills,
+    }
+  }
+
+  fn render_human_readable(&self) -> String {
+    let mut output = String::new();
+    if self.wrong_solutions.is_empty() {
+      output.push_str("Every solution passes every test case; no test case rejects anything.\n");
+      return output;
+    }
+
+    let mut matrix = Table::new();
+    matrix.load_preset(UTF8_FULL_CONDENSED);
+    matrix.set_header(
+      std::iter::once("Test Case")
+        .chain(self.wrong_solutions.iter().map(String::as_str))
+        .collect::<Vec<_>>(),
+    );
+    for kills in &self.kills {
+      matrix.add_row(
+        std::iter::once(Cell::new(&kills.test_case))
+          .chain(self.wrong_solutions.iter().map(|solution| {
+            kills
+              .solutions
+              .get(solution)
+              .map_or(Cell::new(""), |&status| {
+                Cell::new(abbreviation(status)).fg(Color::Red)
+              })
+          }))
+          .collect::<Vec<_>>(),
+      );
+    }
+    output.push_str("Rejecting Test Cases:\n");
+    output.push_str(&matrix.to_string());
+    output.push_str("\n\n");
+
+    output.push_str(&format!(
+      "Test cases rejecting no solution ({}): {}\n",
+      self.idle_test_cases.len(),
+      render_list(&self.idle_test_cases)
+    ));
+    output.push_str(&format!(
+      "Minimal rejecting set ({} of {} test cases): {}\n",
+      self.minimal_test_cases.len(),
+      self.kills.len() + self.idle_test_cases.len(),
+      render_list(&self.minimal_test_cases)
+    ));
+    output.push_str("Solutions rejected by a single test case:");
+    if self.single_kills.is_empty() {
+      output.push_str(" none");
+    }
+    for (solution, test_case) in &self.single_kills {
+      output.push_str(&format!("\n  {solution}: {test_case}"));
+    }
+    output
+  }
+}
+
+/// Picks testcases greedily by how many uncovered solutions they reject, then drops any that
+/// the others make redundant, so every remaining testcase is the only rejection of some solution.
+fn minimal_cover(kills: &[TestKills]) -> Vec<String> {
+  let mut uncovered = kills
+    .iter()
+    .flat_map(|kills| kills.solutions.keys())
+    .collect::<BTreeSet<_>>();
+  let mut chosen = Vec::new();
+  while !uncovered.is_empty() {
+    let Some((index, _)) = kills
+      .iter()
+      .enumerate()
+      .map(|(index, kills)| {
+        let covered = kills
+          .solutions
+          .keys()
+          .filter(|solution| uncovered.contains(solution))
+          .count();
+        (index, covered)
+      })
+      // Earlier testcases win ties, which keeps the result stable.
+      .max_by_key(|&(index, covered)| (covered, std::cmp::Reverse(index)))
+    else {
+      break;
+    };
+    for solution in kills[index].solutions.keys() {
+      uncovered.remove(solution);
+    }
+    chosen.push(index);
+  }
+
+  let mut position = chosen.len();
+  while position > 0 {
+    position -= 1;
+    let candidate = chosen[position];
+    let redundant = kills[candidate].solutions.keys().all(|solution| {
+      chosen
+        .iter()
+        .any(|&other| other != candidate && kills[other].solutions.contains_key(solution))
+    });
+    if redundant {
+      chosen.remove(position);
+    }
+  }
+  chosen.sort_unstable();
+  chosen
+    .into_iter()
+    .map(|index| kills[index].test_case.clone())
+    .collect()
+}
+
+fn abbreviation(status: JudgeStatus) -> &'static str {
+  match status {
+    JudgeStatus::Accepted => "AC",
+    JudgeStatus::WrongAnswer => "WA",
+    JudgeStatus::PartiallyCorrect => "PC",
+    JudgeStatus::RuntimeError => "RE",
+    JudgeStatus::TimeLimitExceeded => "TLE",
+    JudgeStatus::MemoryLimitExceeded => "MLE",
+    JudgeStatus::FileError => "FE",
+    JudgeStatus::InternalError => "IE",
+  }
+}
+
+fn render_list(names: &[String]) -> String {
+  if names.is_empty() {
+    "none".to_string()
+  } else {
+    names.join(", ")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn kills_of(test_case: &str, solutions: &[&str]) -> TestKills {
+    TestKills {
+      test_case: test_case.to_string(),
+      solutions: solutions
+        .iter()
+        .map(|solution| (solution.to_string(), JudgeStatus::WrongAnswer))
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn minimal_cover_drops_redundant_tests() {
+    // Greedy picks `wide` first, after which `left` and `right` make it redundant.
+    let kills = [
+      kills_of("left", &["a", "b"]),
+      kills_of("wide", &["b", "c", "d"]),
+      kills_of("right", &["c", "d", "e"]),
+      kills_of("again", &["e"]),
+    ];
+    assert_eq!(minimal_cover(&kills), ["left", "right"]);
+    assert!(minimal_cover(&[]).is_empty());
+  }
+
+  #[test]
+  fn kills_ignore_accepted_and_internal_errors() {
+    assert!(kills(JudgeStatus::TimeLimitExceeded));
+    assert!(kills(JudgeStatus::PartiallyCorrect));
+    assert!(!kills(JudgeStatus::Accepted));
+    assert!(!kills(JudgeStatus::InternalError));
+  }
+}
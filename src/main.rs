@@ -62,13 +62,18 @@ fn main() -> Result<()> {
   match &opts.command {
     cli::Command::Build(opts) => cmd::build::run(opts),
     cli::Command::BuildContest(opts) => cmd::build_contest::run(opts),
+    cli::Command::Cache { command } => cmd::cache::run(command),
     cli::Command::Compile(opts) => cmd::compile::run(opts),
+    cli::Command::InspectWasm(opts) => cmd::inspect_wasm::run(opts),
     cli::Command::IntegrationJudge { command } => cmd::integration_judge::run(command),
     cli::Command::Judge(opts) => cmd::judge::run(opts),
+    cli::Command::Limits(opts) => cmd::limits::run(opts),
     cli::Command::Patch(opts) => cmd::patch::run(opts),
+    cli::Command::Profile(opts) => cmd::profile::run(opts),
     cli::Command::Run(opts) => cmd::run::run(opts),
     cli::Command::RunWasm(opts) => cmd::run_wasm::run(opts),
     cli::Command::SourceConfig(opts) => cmd::source_config::run(opts),
     cli::Command::Stress(opts) => cmd::stress::run(opts),
+    cli::Command::TestMatrix(opts) => cmd::test_matrix::run(opts),
   }
 }
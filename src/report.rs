@@ -13,13 +13,15 @@
   not, see <https://www.gnu.org/licenses/>.
 */
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use comfy_table::presets::UTF8_FULL_CONDENSED;
 use comfy_table::{Cell, Color, Table};
 use serde::Serialize;
 
 use crate::format::{format_size, format_tick, to_title_case};
+use crate::runner::BacktraceFrame;
+use crate::runtime::prediction::PredictionOutcome;
 use crate::runtime::types::{
   JudgeReport, JudgeStatus, ProblemSpec, RuntimeSolutionData, SubtaskRuntimeReport, SubtaskSpec,
 };
@@ -36,6 +38,8 @@ pub struct JudgeCliReport {
   pub subtask_results: Vec<JudgeCliSubtaskResult>,
   /// Per-testcase summaries indexed by testcase name.
   pub test_case_results: HashMap<String, JudgeCliTestCaseResult>,
+  /// Subtask testcases left out of judging by a test selection, sorted by name.
+  pub skipped_test_cases: Vec<String>,
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -48,6 +52,8 @@ pub struct JudgeCliSubtaskResult {
   pub scaled_score: f64,
   /// Verdicts of matching testcases in configured order.
   pub statuses: Vec<JudgeStatus>,
+  /// Matching testcases left out of judging, which the score does not cover.
+  pub skipped_test_cases: Vec<String>,
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -62,6 +68,8 @@ pub struct JudgeCliTestCaseResult {
   pub tick: u64,
   /// Peak linear-memory usage in bytes.
   pub memory: u64,
+  /// Symbolized solution frames of a runtime error, innermost first.
+  pub backtrace: Vec<BacktraceFrame>,
 }
 
 impl JudgeCliReport {
@@ -78,6 +86,7 @@ impl JudgeCliReport {
           full_score: subtask.full_score,
           scaled_score: result.scaled_score,
           statuses: result.statuses.clone(),
+          skipped_test_cases: result.skipped_test_cases.iter().cloned().collect(),
         })
         .collect(),
       test_case_results: solution
@@ -91,10 +100,12 @@ impl JudgeCliReport {
               score: result.score,
               tick: result.tick,
               memory: result.memory,
+              backtrace: result.backtrace.clone(),
             },
           )
         })
         .collect(),
+      skipped_test_cases: skipped_test_cases(&solution.subtask_results),
     }
   }
 
@@ -118,6 +129,7 @@ impl JudgeCliReport {
           full_score: subtask.full_score,
           scaled_score: report.scaled_score,
           statuses: report.statuses.clone(),
+          skipped_test_cases: report.skipped_test_cases.iter().cloned().collect(),
         })
         .collect(),
       test_case_results: test_case_reports
@@ -130,10 +142,12 @@ impl JudgeCliReport {
               score: report.score,
               tick: report.tick,
               memory: report.memory,
+              backtrace: report.backtrace.clone(),
             },
           )
         })
         .collect(),
+      skipped_test_cases: skipped_test_cases(subtask_reports),
     }
   }
 
@@ -144,6 +158,12 @@ impl JudgeCliReport {
       "Overall Score: {:.3} / {:.3}\n\n",
       self.score, self.full_score
     ));
+    if !self.skipped_test_cases.is_empty() {
+      output.push_str(&format!(
+        "Skipped {} test cases outside the selection; scores cover judged test cases only.\n\n",
+        self.skipped_test_cases.len()
+      ));
+    }
 
     let mut subtask_table = Table::new();
     subtask_table.load_preset(UTF8_FULL_CONDENSED);
@@ -151,9 +171,14 @@ impl JudgeCliReport {
 
     for (index, subtask) in self.subtask_results.iter().enumerate() {
       let status = get_subtask_status(&subtask.statuses);
-      let title_case_status = status
-        .map(|status| to_title_case(&status.to_string()))
-        .unwrap_or_else(|| "N/A".to_string());
+      let title_case_status = match (status, subtask.skipped_test_cases.len()) {
+        (None, 0) => "N/A".to_string(),
+        (None, _) => "Skipped".to_string(),
+        (Some(status), 0) => to_title_case(&status.to_string()),
+        (Some(status), skipped) => {
+          format!("{} ({skipped} skipped)", to_title_case(&status.to_string()))
+        }
+      };
       subtask_table.add_row(vec![
         Cell::new(index),
         colorize_status(status, &title_case_status),
@@ -188,6 +213,55 @@ impl JudgeCliReport {
   }
 }
 
+/// Renders declared subtask predictions next to the actual subtask results.
+pub fn render_subtask_predictions(outcomes: &[PredictionOutcome]) -> String {
+  let mut table = Table::new();
+  table.load_preset(UTF8_FULL_CONDENSED);
+  table.set_header(vec![
+    "Solution",
+    "Subtask",
+    "Raw Score",
+    "Statuses",
+    "Prediction",
+  ]);
+  for outcome in outcomes {
+    let statuses = outcome
+      .statuses
+      .iter()
+      .map(|status| to_title_case(&status.to_string()))
+      .collect::<Vec<_>>();
+    let prediction = match outcome.holds {
+      Some(true) => Cell::new("Met").fg(Color::Green),
+      Some(false) => Cell::new("Violated").fg(Color::Red),
+      None => Cell::new("Unchecked"),
+    };
+    table.add_row(vec![
+      Cell::new(&outcome.solution),
+      Cell::new(outcome.subtask),
+      Cell::new(format!("{:.3}", outcome.raw_score)),
+      colorize_status(
+        get_subtask_status(&outcome.statuses),
+        &if statuses.is_empty() {
+          "N/A".to_string()
+        } else {
+          statuses.join(", ")
+        },
+      ),
+      prediction,
+    ]);
+  }
+  table.to_string()
+}
+
+fn skipped_test_cases(subtask_reports: &[SubtaskRuntimeReport]) -> Vec<String> {
+  subtask_reports
+    .iter()
+    .flat_map(|report| report.skipped_test_cases.iter().cloned())
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .collect()
+}
+
 fn get_subtask_status(statuses: &[JudgeStatus]) -> Option<JudgeStatus> {
   JudgeStatus::aggregate(statuses.iter().copied())
 }
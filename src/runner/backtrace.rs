@@ -0,0 +1,62 @@
+use wasmtime::{FrameInfo, WasmBacktrace};
+
+use super::BacktraceFrame;
+
+/// Symbolizes captured guest frames innermost first, expanding DWARF inlined calls.
+pub fn symbolize(backtrace: &WasmBacktrace) -> Vec<BacktraceFrame> {
+  backtrace.frames().iter().flat_map(frame_symbols).collect()
+}
+
+/// Symbolizes one physical frame, listing inlined callees before their containing function.
+pub fn frame_symbols(frame: &FrameInfo) -> Vec<BacktraceFrame> {
+  let symbols = frame.symbols();
+  let Some(outermost) = symbols.len().checked_sub(1) else {
+    return vec![BacktraceFrame {
+      function: function_name(frame, None),
+      file: None,
+      line: None,
+      column: None,
+    }];
+  };
+  symbols
+    .iter()
+    .enumerate()
+    .map(|(index, symbol)| BacktraceFrame {
+      function: if index == outermost {
+        function_name(frame, symbol.name())
+      } else {
+        symbol.name().map_or_else(|| "[inlined]".into(), demangle)
+      },
+      file: symbol.file().map(str::to_owned),
+      line: symbol.line(),
+      column: symbol.column(),
+    })
+    .collect()
+}
+
+// The name section is already demangled by Wasmtime; DWARF names carry linkage names.
+fn function_name(frame: &FrameInfo, symbol: Option<&str>) -> String {
+  frame
+    .func_name()
+    .map(str::to_owned)
+    .or_else(|| symbol.map(demangle))
+    .unwrap_or_else(|| format!("wasm-function[{}]", frame.func_index()))
+}
+
+fn demangle(name: &str) -> String {
+  cpp_demangle::Symbol::new(name)
+    .ok()
+    .and_then(|symbol| symbol.demangle().ok())
+    .unwrap_or_else(|| name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn demangles_linkage_names() {
+    assert_eq!(demangle("_Z5solvei"), "solve(int)");
+    assert_eq!(demangle("main"), "main");
+  }
+}
@@ -0,0 +1,410 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use wasmtime::{
+  Engine, WasmFeatures,
+  wasmparser::{
+    ComponentExternalKind, Encoding, ExternalKind, MemoryType, Parser, Payload, TypeRef, Validator,
+    WasmFeatures as Flags,
+  },
+};
+
+use super::{
+  WasmFeature,
+  module::Guest,
+  registry::{self, LoadedProgram},
+  wasm_features,
+};
+
+const PREVIEW1_MODULE: &str = "wasi_snapshot_preview1";
+const PREVIEW1_WITX: &str = include_str!("wasi/witx/wasi_snapshot_preview1.witx");
+const COMMAND_WORLD: &str = include_str!("wasi/wit/world.wit");
+const WASI_CLI_RUN: &str = "wasi:cli/run@0.2.0";
+
+/// Static description of one guest Wasm file and whether Hull would run it.
+#[derive(Clone, Debug, Serialize)]
+pub struct WasmInspection {
+  /// `module` for a core module, `component` for a component.
+  pub kind: &'static str,
+  /// Top-level imports in declaration order.
+  pub imports: Vec<WasmImport>,
+  /// Top-level exports in declaration order.
+  pub exports: Vec<WasmExport>,
+  /// Proposals beyond Hull's default feature set that validation requires.
+  pub features: Vec<UsedFeature>,
+  /// Linear memories of every core module, imported ones first within each module.
+  pub memories: Vec<WasmMemory>,
+  /// Bytes of function bodies across every core module.
+  pub code_size: u64,
+  /// Why Hull's engine would refuse to start the guest, or `None` when it would start it.
+  pub rejection: Option<String>,
+}
+
+/// One import and whether Hull provides it.
+#[derive(Clone, Debug, Serialize)]
+pub struct WasmImport {
+  /// Import module, empty for component imports.
+  pub module: String,
+  /// Imported item or interface name.
+  pub name: String,
+  /// Item kind, such as `func` or `instance`.
+  pub kind: &'static str,
+  /// Whether Hull's WASI implementation provides an item of this name.
+  pub supported: bool,
+}
+
+/// One export.
+#[derive(Clone, Debug, Serialize)]
+pub struct WasmExport {
+  /// Exported name.
+  pub name: String,
+  /// Item kind, such as `func` or `memory`.
+  pub kind: &'static str,
+}
+
+/// One Wasm proposal the guest needs.
+#[derive(Clone, Debug, Serialize)]
+pub struct UsedFeature {
+  /// Proposal name in lowercase Wasmtime spelling.
+  pub name: String,
+  /// Whether the proposal can be opted into per program.
+  pub opt_in: bool,
+  /// Whether the inspected feature set accepts the proposal.
+  pub enabled: bool,
+}
+
+/// One linear memory type.
+#[derive(Clone, Debug, Serialize)]
+pub struct WasmMemory {
+  /// Whether the memory is imported rather than defined.
+  pub imported: bool,
+  /// Initial size in pages.
+  pub minimum: u64,
+  /// Declared maximum size in pages.
+  pub maximum: Option<u64>,
+  /// Page size in bytes.
+  pub page_size: u64,
+  /// Whether the memory uses 64-bit addresses.
+  pub memory64: bool,
+  /// Whether the memory is shared between threads.
+  pub shared: bool,
+}
+
+impl WasmInspection {
+  /// Whether Hull's engine would start the guest.
+  pub fn accepted(&self) -> bool {
+    self.rejection.is_none()
+  }
+}
+
+/// Inspects source Wasm, binary or text, under the default feature set plus `opted_in`.
+///
+/// The guest is loaded with the same rules and `stack_limit` as judged programs, so `rejection`
+/// carries the error a session would report as `internal_error`. Malformed input that cannot be
+/// parsed at all is an error instead.
+pub fn inspect_wasm(
+  wasm_path: &Path,
+  opted_in: &[WasmFeature],
+  stack_limit: u64,
+) -> Result<WasmInspection> {
+  let source =
+    std::fs::read(wasm_path).with_context(|| format!("failed to read {}", wasm_path.display()))?;
+  if Engine::detect_precompiled(&source).is_some() {
+    bail!("authoritative module input must be source Wasm");
+  }
+  let binary = wat::parse_bytes(&source)
+    .with_context(|| format!("failed to parse {}", wasm_path.display()))?;
+  let features = wasm_features(opted_in);
+  let mut inspection = parse(&binary)?;
+  inspection.features = used_features(&binary, features);
+  let stack =
+    usize::try_from(stack_limit).context("stack_limit does not fit the host address width")?;
+  inspection.rejection = registry::load(stack.max(1), false, false, features, wasm_path)
+    .and_then(|loaded| check_entry(&loaded))
+    .err()
+    .map(|error| format!("{error:#}"));
+  Ok(inspection)
+}
+
+fn parse(binary: &[u8]) -> Result<WasmInspection> {
+  let mut inspection = WasmInspection {
+    kind: "module",
+    imports: Vec::new(),
+    exports: Vec::new(),
+    features: Vec::new(),
+    memories: Vec::new(),
+    code_size: 0,
+    rejection: None,
+  };
+  let mut depth = 0;
+  for payload in Parser::new(0).parse_all(binary) {
+    match payload? {
+      Payload::Version { encoding, .. } => {
+        if depth == 0 && encoding == Encoding::Component {
+          inspection.kind = "component";
+        }
+        depth += 1;
+      }
+      Payload::End(_) => depth -= 1,
+      Payload::ImportSection(reader) => {
+        for import in reader.into_imports() {
+          let import = import?;
+          if let TypeRef::Memory(memory) = import.ty {
+            inspection.memories.push(memory_type(memory, true));
+          }
+          if depth == 1 {
+            inspection.imports.push(WasmImport {
+              module: import.module.into(),
+              name: import.name.into(),
+              kind: type_kind(import.ty),
+              supported: import.module == PREVIEW1_MODULE
+                && preview1_functions().any(|function| function == import.name),
+            });
+          }
+        }
+      }
+      Payload::MemorySection(reader) => {
+        for memory in reader {
+          inspection.memories.push(memory_type(memory?, false));
+        }
+      }
+      Payload::ExportSection(reader) if depth == 1 => {
+        for export in reader {
+          let export = export?;
+          inspection.exports.push(WasmExport {
+            name: export.name.into(),
+            kind: external_kind(export.kind),
+          });
+        }
+      }
+      Payload::CodeSectionStart { size, .. } => inspection.code_size += u64::from(size),
+      Payload::ComponentImportSection(reader) if depth == 1 => {
+        for import in reader {
+          let import = import?;
+          inspection.imports.push(WasmImport {
+            module: String::new(),
+            name: import.name.name.into(),
+            kind: import.ty.kind().desc(),
+            supported: import.ty.kind() == ComponentExternalKind::Instance
+              && command_imports().any(|interface| interface == import.name.name),
+          });
+        }
+      }
+      Payload::ComponentExportSection(reader) if depth == 1 => {
+        for export in reader {
+          let export = export?;
+          inspection.exports.push(WasmExport {
+            name: export.name.name.into(),
+            kind: export.kind.desc(),
+          });
+        }
+      }
+      _ => {}
+    }
+  }
+  Ok(inspection)
+}
+
+/// Returns the proposals outside `wasm_features(&[])` without which the binary fails validation.
+///
+/// Candidates are withdrawn in halves, so a binary needing no proposal validates only twice and
+/// each needed proposal costs a logarithmic number of passes.
+fn used_features(binary: &[u8], features: WasmFeatures) -> Vec<UsedFeature> {
+  let valid = |flags| {
+    Validator::new_with_features(flags)
+      .validate_all(binary)
+      .is_ok()
+  };
+  if !valid(Flags::all()) {
+    return Vec::new();
+  }
+  let default = wasm_features(&[]);
+  let candidates = Flags::all()
+    .iter_names()
+    .filter(|(_, flag)| !default.contains(*flag))
+    .collect::<Vec<_>>();
+  let mut used = Vec::new();
+  let mut groups = vec![candidates.as_slice()];
+  while let Some(group) = groups.pop() {
+    let withdrawn = group
+      .iter()
+      .fold(Flags::empty(), |withdrawn, (_, flag)| withdrawn | *flag);
+    if valid(Flags::all() - withdrawn) {
+      continue;
+    }
+    match group {
+      [candidate] => used.push(*candidate),
+      _ => {
+        let (left, right) = group.split_at(group.len() / 2);
+        groups.extend([right, left]);
+      }
+    }
+  }
+  used
+    .into_iter()
+    .map(|(name, flag)| {
+      let name = name.to_ascii_lowercase();
+      UsedFeature {
+        opt_in: WasmFeature::from_str(&name).is_ok(),
+        enabled: features.contains(flag),
+        name,
+      }
+    })
+    .collect()
+}
+
+fn check_entry(loaded: &LoadedProgram) -> Result<()> {
+  match &loaded.guest {
+    Guest::Module(module) => {
+      loaded.linker.instantiate_pre(module)?;
+      let start = module
+        .get_export("_start")
+        .and_then(|export| export.func().cloned())
+        .context("missing exported function `_start`")?;
+      if start.params().len() > 0 || start.results().len() > 0 {
+        bail!("`_start` must take no parameters and return no results");
+      }
+    }
+    Guest::Component(component) => {
+      loaded.component_linker.instantiate_pre(component)?;
+      component
+        .get_export_index(None, WASI_CLI_RUN)
+        .with_context(|| format!("missing exported instance `{WASI_CLI_RUN}`"))?;
+    }
+  }
+  Ok(())
+}
+
+fn preview1_functions() -> impl Iterator<Item = &'static str> {
+  PREVIEW1_WITX.lines().filter_map(|line| {
+    let rest = line.trim().strip_prefix("(@interface func (export \"")?;
+    rest.split('"').next()
+  })
+}
+
+fn command_imports() -> impl Iterator<Item = &'static str> {
+  COMMAND_WORLD
+    .lines()
+    .filter_map(|line| line.trim().strip_prefix("import ")?.strip_suffix(';'))
+}
+
+fn memory_type(memory: MemoryType, imported: bool) -> WasmMemory {
+  WasmMemory {
+    imported,
+    minimum: memory.initial,
+    maximum: memory.maximum,
+    page_size: memory.page_size_log2.map_or(1 << 16, |log2| 1u64 << log2),
+    memory64: memory.memory64,
+    shared: memory.shared,
+  }
+}
+
+fn type_kind(ty: TypeRef) -> &'static str {
+  match ty {
+    TypeRef::Func(_) | TypeRef::FuncExact(_) => "func",
+    TypeRef::Table(_) => "table",
+    TypeRef::Memory(_) => "memory",
+    TypeRef::Global(_) => "global",
+    TypeRef::Tag(_) => "tag",
+  }
+}
+
+fn external_kind(kind: ExternalKind) -> &'static str {
+  match kind {
+    ExternalKind::Func | ExternalKind::FuncExact => "func",
+    ExternalKind::Table => "table",
+    ExternalKind::Memory => "memory",
+    ExternalKind::Global => "global",
+    ExternalKind::Tag => "tag",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn inspect(wat: &str, opted_in: &[WasmFeature]) -> WasmInspection {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("guest.wat");
+    std::fs::write(&path, wat).unwrap();
+    inspect_wasm(&path, opted_in, 1 << 20).unwrap()
+  }
+
+  #[test]
+  fn reports_module_shape_and_acceptance() {
+    let inspection = inspect(
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func (param i32 i32 i32 i32) (result i32)))
+        (import "env" "missing" (func))
+        (memory (export "memory") 2 16)
+        (func (export "_start")))"#,
+      &[],
+    );
+
+    assert_eq!(inspection.kind, "module");
+    let supported = inspection
+      .imports
+      .iter()
+      .map(|import| (import.name.as_str(), import.supported))
+      .collect::<Vec<_>>();
+    assert_eq!(supported, [("fd_write", true), ("missing", false)]);
+    assert_eq!(inspection.memories.len(), 1);
+    assert_eq!(
+      (
+        inspection.memories[0].minimum,
+        inspection.memories[0].maximum
+      ),
+      (2, Some(16))
+    );
+    assert_eq!(inspection.memories[0].page_size, 65536);
+    let exports = inspection
+      .exports
+      .iter()
+      .map(|export| (export.name.as_str(), export.kind))
+      .collect::<Vec<_>>();
+    assert_eq!(exports, [("memory", "memory"), ("_start", "func")]);
+    assert!(inspection.code_size > 0);
+    assert!(inspection.rejection.unwrap().contains("missing"));
+  }
+
+  #[test]
+  fn reports_features_beyond_the_default_set() {
+    let simd = r#"(module
+      (func (export "_start") v128.const i64x2 0 0 drop))"#;
+    let rejected = inspect(simd, &[]);
+    assert_eq!(rejected.features.len(), 1);
+    assert_eq!(rejected.features[0].name, "simd");
+    assert!(rejected.features[0].opt_in && !rejected.features[0].enabled);
+    assert!(!rejected.accepted());
+
+    let accepted = inspect(simd, &[WasmFeature::Simd]);
+    assert!(accepted.features[0].enabled);
+    assert!(accepted.accepted(), "{:?}", accepted.rejection);
+  }
+
+  #[test]
+  fn reports_every_feature_a_module_needs() {
+    let inspection = inspect(
+      r#"(module
+        (func $tail (result i32) return_call $tail)
+        (func (export "_start") v128.const i64x2 0 0 drop))"#,
+      &[WasmFeature::TailCall],
+    );
+    let mut names = inspection
+      .features
+      .iter()
+      .map(|feature| (feature.name.as_str(), feature.enabled))
+      .collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, [("simd", false), ("tail_call", true)]);
+  }
+
+  #[test]
+  fn reports_missing_start() {
+    let inspection = inspect("(module)", &[]);
+    assert!(inspection.features.is_empty());
+    assert!(inspection.rejection.unwrap().contains("_start"));
+  }
+}
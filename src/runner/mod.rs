@@ -6,41 +6,58 @@
   the License, or (at your option) any later version.
 */
 
+mod backtrace;
+mod inspect;
 mod module;
+mod profile;
+mod registry;
 mod request;
 mod scheduler;
+mod serve;
+mod stack;
 mod wasi;
 
 use std::{
   cell::Cell,
+  collections::BTreeMap,
   fmt,
   future::Future,
+  num::NonZeroUsize,
   path::{Path, PathBuf},
   pin::Pin,
   rc::Rc,
+  sync::{Arc, Mutex},
   task::{Context as TaskContext, Poll},
 };
 
 use anyhow::{Context, Result, anyhow};
 use futures::{FutureExt, future::LocalBoxFuture};
+pub use inspect::{UsedFeature, WasmExport, WasmImport, WasmInspection, WasmMemory, inspect_wasm};
+pub use profile::Profile;
+pub use registry::{RegistryStatistics, statistics as registry_statistics};
 pub use request::{
-  Deadlock, DirectoryBinding, DirectoryPermissions, File, FileBinding, FilePermissions,
-  FileSizeLimit, FileSystem, InitialDescriptor, ProgramRequest, ProgramResult, RunStatus,
-  SessionReport, SessionRequest, ToolLimit,
+  BacktraceFrame, BlockedCall, BlockedDescriptor, Clock, Deadlock, DirectoryBinding,
+  DirectoryPermissions, File, FileBinding, FilePermissions, FileSizeLimit, FileStatistics,
+  FileSystem, InitialDescriptor, IoCost, MemoryGrowth, PipeBuffer, ProgramRequest, ProgramResult,
+  RunStatus, ScratchDirectory, SessionReport, SessionRequest, SymlinkBinding, ToolLimit,
+  WasmFeature,
 };
+pub use serve::{SESSION_SOCKET_ENVIRONMENT, forward, serve_socket, serve_stream};
+use tracing::warn;
 use wasmtime::{
-  Config, Engine, Inlining, Linker, Module, OptLevel, ProfilingStrategy, Store, Strategy,
-  WasmFeatures,
+  Config, Engine, Inlining, Linker, OptLevel, ProfilingStrategy, Store, Strategy,
+  WasmBacktraceDetails, WasmFeatures, component,
 };
 
 /// The tick ceiling used by trusted Hull tools.
 pub const TOOL_TICK_LIMIT: u64 = 10u64.pow(18);
-/// The memory and stack ceiling used by trusted Hull tools.
+/// The memory ceiling used by trusted Hull tools, also their default stack ceiling.
 pub const TOOL_MEMORY_LIMIT: u64 = u32::MAX as u64;
 /// The file-size ceiling used by trusted Hull tools.
 pub const TOOL_FILE_SIZE_LIMIT: usize = usize::MAX;
 
 const ASYNC_HOST_STACK_RESERVE: usize = 2 * 1024 * 1024;
+const MAX_BACKTRACE_FRAMES: usize = 64;
 const SCHEDULER_TICK_INTERVAL: u64 = 10_000_000;
 
 /// Returns Hull's private Wasmtime module-cache directory when one is available.
@@ -57,8 +74,34 @@ pub fn module_cache_directory() -> Option<PathBuf> {
     .map(|home| home.join(".cache").join("hull").join("cwasm"))
 }
 
-/// Builds an engine with Hull's complete guest feature policy.
-pub fn engine_config(max_wasm_stack: usize) -> Result<Config> {
+/// Returns Hull's strict guest feature set extended with the proposals a program opted into.
+///
+/// The component model is always accepted; it only changes how a guest links, not what its core
+/// code may do.
+pub fn wasm_features(opted_in: &[WasmFeature]) -> WasmFeatures {
+  opted_in.iter().fold(
+    (WasmFeatures::MVP - WasmFeatures::GC_TYPES)
+      | WasmFeatures::CUSTOM_PAGE_SIZES
+      | WasmFeatures::COMPONENT_MODEL,
+    |features, feature| {
+      features
+        | match feature {
+          WasmFeature::BulkMemory => WasmFeatures::BULK_MEMORY,
+          WasmFeature::MultiValue => WasmFeatures::MULTI_VALUE,
+          WasmFeature::MutableGlobal => WasmFeatures::MUTABLE_GLOBAL,
+          WasmFeature::SaturatingFloatToInt => WasmFeatures::SATURATING_FLOAT_TO_INT,
+          WasmFeature::SignExtension => WasmFeatures::SIGN_EXTENSION,
+          WasmFeature::Simd => WasmFeatures::SIMD,
+          WasmFeature::TailCall => WasmFeatures::TAIL_CALL,
+        }
+    },
+  )
+}
+
+/// Builds an engine that accepts exactly `features`, usually from [`wasm_features`].
+///
+/// Traps capture no backtrace; [`enable_backtraces`] turns them on for programs that report one.
+pub fn engine_config(max_wasm_stack: usize, features: WasmFeatures) -> Result<Config> {
   if max_wasm_stack == 0 {
     return Err(anyhow!("the Wasm stack limit must be nonzero"));
   }
@@ -68,7 +111,6 @@ pub fn engine_config(max_wasm_stack: usize) -> Result<Config> {
       anyhow!("the Wasm stack limit leaves no address space for the async host stack")
     })?;
   let mut config = Config::new();
-  let features = (WasmFeatures::MVP - WasmFeatures::GC_TYPES) | WasmFeatures::CUSTOM_PAGE_SIZES;
   config
     .wasm_features(WasmFeatures::all(), false)
     .wasm_features(features, true)
@@ -78,15 +120,21 @@ pub fn engine_config(max_wasm_stack: usize) -> Result<Config> {
     .cranelift_opt_level(OptLevel::Speed)
     .compiler_inlining(Inlining::Yes)
     .wasm_backtrace_max_frames(None)
+    .wasm_backtrace_details(WasmBacktraceDetails::Disable)
     .max_wasm_stack(max_wasm_stack)
-    .async_stack_size(async_stack_size);
+    .async_stack_size(async_stack_size)
+    .with_host_stack(Arc::new(stack::MeasuredStacks));
   Ok(config)
 }
 
-fn load_module_path(engine: &Engine, source_path: &Path) -> Result<Module> {
-  let source = std::fs::read(source_path)
-    .with_context(|| format!("failed to read {}", source_path.display()))?;
-  module::load_module(engine, &source)
+/// Captures up to [`MAX_BACKTRACE_FRAMES`] frames on traps and reads DWARF to symbolize them.
+///
+/// Parsing DWARF slows every compilation, so only engines of programs that report backtraces use
+/// this.
+pub fn enable_backtraces(config: &mut Config) {
+  config
+    .wasm_backtrace_max_frames(NonZeroUsize::new(MAX_BACKTRACE_FRAMES))
+    .wasm_backtrace_details(WasmBacktraceDetails::Enable);
 }
 
 struct Execution {
@@ -95,20 +143,25 @@ struct Execution {
   state: wasi::State,
   tick: u64,
   memory: u64,
+  stack: u64,
+  coredump: Option<Vec<u8>>,
 }
 
 type ExecutionFuture = LocalBoxFuture<'static, Execution>;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Default)]
 struct PendingTelemetry {
   memory_exceeded: bool,
   memory: u64,
+  memory_timeline: Vec<MemoryGrowth>,
   local_file_error_exceeded: bool,
+  wait: Option<wasi::PendingWait>,
 }
 
 struct TrackedExecutionFuture {
   inner: ExecutionFuture,
   state: *const wasi::State,
+  completed: bool,
   telemetry: Rc<Cell<PendingTelemetry>>,
 }
 
@@ -117,20 +170,55 @@ impl Future for TrackedExecutionFuture {
 
   fn poll(mut self: Pin<&mut Self>, context: &mut TaskContext<'_>) -> Poll<Self::Output> {
     let result = Pin::new(&mut self.inner).poll(context);
-    if result.is_pending() {
-      // The store is pinned inside `inner`, so its state remains at this address until `inner`
-      // is dropped. Pending telemetry must be copied before the scheduler drops that future.
-      let state = unsafe { &*self.state };
-      self.telemetry.set(PendingTelemetry {
-        memory_exceeded: state.memory.exceeded,
-        memory: u64::try_from(state.memory.peak).unwrap_or(u64::MAX),
-        local_file_error_exceeded: state.local_file_error_exceeded(),
-      });
-    }
+    self.completed = result.is_ready();
     result
   }
 }
 
+impl Drop for TrackedExecutionFuture {
+  fn drop(&mut self) {
+    if self.completed {
+      return;
+    }
+    // The store is pinned inside `inner`, so its state remains at this address until `inner`
+    // is dropped, which happens only after this copy of the pending telemetry.
+    let state = unsafe { &*self.state };
+    self.telemetry.set(PendingTelemetry {
+      memory_exceeded: state.memory.exceeded,
+      memory: u64::try_from(state.memory.peak).unwrap_or(u64::MAX),
+      memory_timeline: state.memory.timeline.clone(),
+      local_file_error_exceeded: state.local_file_error_exceeded(),
+      wait: state.wait().cloned(),
+    });
+  }
+}
+
+/// Instantiates a loaded guest and calls its entry point.
+///
+/// Returns the run outcome and, when the guest never started, the setup diagnostic.
+async fn run_guest(
+  store: &mut Store<wasi::State>,
+  loaded: &registry::LoadedProgram,
+) -> (wasmtime::Result<()>, Option<String>) {
+  let module = match &loaded.guest {
+    module::Guest::Module(module) => module,
+    module::Guest::Component(component) => {
+      return wasi::run_component(store, &loaded.component_linker, component).await;
+    }
+  };
+  let start = match loaded.linker.instantiate_async(&mut *store, module).await {
+    Ok(instance) => instance.get_typed_func::<(), ()>(&mut *store, "_start"),
+    Err(error) => Err(error),
+  };
+  match start {
+    Ok(start) => (start.call_async(store, ()).await, None),
+    Err(error) => {
+      let message = error.to_string();
+      (Err(error), Some(message))
+    }
+  }
+}
+
 struct PreparedProgram {
   future: ExecutionFuture,
   telemetry: Rc<Cell<PendingTelemetry>>,
@@ -147,25 +235,28 @@ fn prepare_program(
     return Ok(None);
   }
   let setup = (|| {
-    let stack = usize::try_from(program.memory_limit)
-      .context("memory_limit does not fit the host address width")?;
-    let engine = Engine::new(&engine_config(stack.max(1))?)?;
-    let module = load_module_path(&engine, &program.wasm_path)?;
-    let mut linker = Linker::new(&engine);
-    wasi::add_to_linker(&mut linker)?;
-    Ok::<_, anyhow::Error>((engine, module, linker))
+    let stack = usize::try_from(program.stack_limit())
+      .context("stack_limit does not fit the host address width")?;
+    registry::load(
+      stack.max(1),
+      program.coredump,
+      program.backtrace,
+      wasm_features(&program.features),
+      &program.wasm_path,
+    )
   })();
-  let (engine, module, linker) = match setup {
+  let loaded = match setup {
     Ok(setup) => setup,
     Err(error) => {
       state.close_descriptors();
       return Err(error);
     }
   };
-  let mut store = Box::pin(Store::new(&engine, state));
+  let mut store = Box::pin(Store::new(&loaded.engine, state));
   let store_setup = (|| {
-    store.limiter(|state| &mut state.memory);
+    wasi::bind_store(&mut store);
     store.set_fuel(program.tick_limit)?;
+    wasi::install_hostcall_hook(&mut store);
     if periodic_yield {
       store.fuel_async_yield_interval(Some(SCHEDULER_TICK_INTERVAL))?;
     }
@@ -176,35 +267,29 @@ fn prepare_program(
     return Err(error);
   }
   let tick_limit = program.tick_limit;
+  let name = program.name.clone();
   let state = store.data() as *const wasi::State;
   let telemetry = Rc::new(Cell::new(PendingTelemetry::default()));
   let inner = async move {
-    let (result, setup_error) = match linker
-      .instantiate_async(store.as_mut().get_mut(), &module)
-      .await
-    {
-      Err(error) => {
-        let message = error.to_string();
-        (Err(error), Some(message))
-      }
-      Ok(instance) => match instance.get_typed_func::<(), ()>(store.as_mut().get_mut(), "_start") {
-        Err(error) => {
-          let message = error.to_string();
-          (Err(error), Some(message))
-        }
-        Ok(start) => (start.call_async(store.as_mut().get_mut(), ()).await, None),
-      },
-    };
+    let (result, setup_error) = run_guest(store.as_mut().get_mut(), &loaded).await;
     let tick = tick_limit.saturating_sub(store.get_fuel().unwrap_or(0));
     let memory = u64::try_from(store.data().memory.peak).unwrap_or(u64::MAX);
+    // Serializing reads the store's memories, so it happens before the store is released.
+    let coredump = result.as_ref().err().and_then(|error| {
+      error
+        .downcast_ref::<wasmtime::WasmCoreDump>()
+        .map(|coredump| coredump.serialize(store.as_mut().get_mut(), &name))
+    });
     store.data_mut().close_descriptors();
-    let state = (*Pin::into_inner(store)).into_data();
+    let (state, stack) = stack::measure_released(|| (*Pin::into_inner(store)).into_data());
     Execution {
       result,
       setup_error,
       state,
       tick,
       memory,
+      stack,
+      coredump,
     }
   }
   .boxed_local();
@@ -212,6 +297,7 @@ fn prepare_program(
     future: TrackedExecutionFuture {
       inner,
       state,
+      completed: false,
       telemetry: Rc::clone(&telemetry),
     }
     .boxed_local(),
@@ -260,8 +346,14 @@ fn run_validated_session(request: &SessionRequest) -> Result<SessionReport> {
       },
     )
     .collect();
-  let schedule = scheduler::round_robin(futures);
-  let deadlocks = scheduler::deadlocks(request, &schedule.waiting);
+  let controlling = request
+    .programs
+    .iter()
+    .map(|program| request.terminate_on_exit.contains(&program.name))
+    .collect::<Vec<_>>();
+  let schedule = scheduler::round_robin(futures, &controlling);
+  let mut deadlocks = scheduler::deadlocks(request, &schedule.waiting);
+  let mut waits = BTreeMap::new();
   for (index, execution) in schedule.completed.into_iter().enumerate() {
     if let Some(execution) = execution {
       let file_error = execution.state.file_error_exceeded(&files);
@@ -270,13 +362,14 @@ fn run_validated_session(request: &SessionRequest) -> Result<SessionReport> {
       if execution.setup_error.is_some() && !execution.state.memory.exceeded && !file_error {
         files.disable_file_commits(&request.programs[index]);
       }
-      setup_results[index] = Some(if execution.state.memory.exceeded || file_error {
+      let mut result = if execution.state.memory.exceeded || file_error {
         wasi::classify(
           &execution.result,
           &execution.state,
           file_error,
           execution.tick,
           execution.memory,
+          execution.stack,
           &request.programs[index].name,
         )
       } else if let Some(error) = execution.setup_error {
@@ -288,11 +381,34 @@ fn run_validated_session(request: &SessionRequest) -> Result<SessionReport> {
           false,
           execution.tick,
           execution.memory,
+          execution.stack,
           &request.programs[index].name,
         )
-      });
+      };
+      if result.status == RunStatus::RuntimeError
+        && let Some(coredump) = execution.coredump
+      {
+        let path = request.coredump_path(&request.programs[index]);
+        match std::fs::write(&path, coredump) {
+          Ok(()) => result.coredump_path = Some(path),
+          Err(error) => warn!("Failed to write core dump {}: {error}", path.display()),
+        }
+      }
+      setup_results[index] = Some(result);
+    } else if schedule.terminated.contains(&index) {
+      let pending = telemetry[index]
+        .as_ref()
+        .expect("terminated programs were scheduled with telemetry")
+        .take();
+      setup_results[index] = Some(terminated_result(&request.programs[index], pending));
     } else if setup_results[index].is_none() {
-      let pending = telemetry[index].as_ref().unwrap().get();
+      let mut pending = telemetry[index]
+        .as_ref()
+        .expect("programs without a setup result were scheduled with telemetry")
+        .take();
+      if let Some(wait) = pending.wait.take() {
+        waits.insert(request.programs[index].name.as_str(), wait);
+      }
       setup_results[index] = Some(pending_result(
         &request.programs[index],
         pending,
@@ -301,10 +417,31 @@ fn run_validated_session(request: &SessionRequest) -> Result<SessionReport> {
       ));
     }
   }
+  for deadlock in &mut deadlocks {
+    deadlock.blocked = deadlock
+      .programs
+      .iter()
+      .filter_map(|program| Some(files.blocked_call(program, waits.get(program.as_str())?)))
+      .collect();
+    deadlock.buffered = deadlock
+      .pipes
+      .iter()
+      .map(|pipe| PipeBuffer {
+        pipe: pipe.clone(),
+        bytes: files.buffered(pipe).unwrap_or(0),
+      })
+      .collect();
+  }
   let mut results = setup_results
     .into_iter()
     .map(Option::unwrap)
     .collect::<Vec<_>>();
+  for (result, program) in results.iter_mut().zip(&request.programs) {
+    result.files = files.statistics(program);
+  }
+  if let Err(error) = files.finish_transcripts() {
+    warn!("{error:#}");
+  }
   if let Err(error) = files.commit() {
     apply_commit_error(&mut results, &error);
   }
@@ -317,8 +454,13 @@ fn file_error_result(program: String, tick: u64, memory: u64) -> ProgramResult {
     status: RunStatus::FileError,
     tick,
     memory,
+    stack: 0,
     exit_code: None,
     error_message: Some("File size limit exceeded".into()),
+    backtrace: Vec::new(),
+    coredump_path: None,
+    files: Vec::new(),
+    memory_timeline: Vec::new(),
   }
 }
 
@@ -334,8 +476,13 @@ fn pending_result(
       status: RunStatus::MemoryLimitExceeded,
       tick: 0,
       memory: telemetry.memory,
+      stack: 0,
       exit_code: None,
       error_message: Some("Memory limit exceeded".into()),
+      backtrace: Vec::new(),
+      coredump_path: None,
+      files: Vec::new(),
+      memory_timeline: telemetry.memory_timeline,
     };
   }
   let file_error = request
@@ -344,19 +491,42 @@ fn pending_result(
     .enumerate()
     .any(|(index, file)| files.exceeded(index) && program.writes_file(file.name()));
   if file_error {
-    file_error_result(program.name.clone(), 0, telemetry.memory)
+    let mut result = file_error_result(program.name.clone(), 0, telemetry.memory);
+    result.memory_timeline = telemetry.memory_timeline;
+    result
   } else {
     ProgramResult {
       program: program.name.clone(),
       status: RunStatus::TimeLimitExceeded,
       tick: 0,
       memory: telemetry.memory,
+      stack: 0,
       exit_code: None,
       error_message: Some("Protocol deadlock".into()),
+      backtrace: Vec::new(),
+      coredump_path: None,
+      files: Vec::new(),
+      memory_timeline: telemetry.memory_timeline,
     }
   }
 }
 
+fn terminated_result(program: &ProgramRequest, telemetry: PendingTelemetry) -> ProgramResult {
+  ProgramResult {
+    program: program.name.clone(),
+    status: RunStatus::Terminated,
+    tick: 0,
+    memory: telemetry.memory,
+    stack: 0,
+    exit_code: None,
+    error_message: Some("Terminated after a controlling program exited".into()),
+    backtrace: Vec::new(),
+    coredump_path: None,
+    files: Vec::new(),
+    memory_timeline: telemetry.memory_timeline,
+  }
+}
+
 fn apply_commit_error(results: &mut [ProgramResult], error: &anyhow::Error) {
   for result in results
     .iter_mut()
@@ -375,73 +545,124 @@ pub struct LocalProgramRequest {
   pub wasm_path: PathBuf,
   /// Arguments after synthetic `argv[0]`.
   pub arguments: Vec<String>,
+  /// Environment variables visible through `environ_get`.
+  pub environment: BTreeMap<String, String>,
   /// Guest tick ceiling.
   pub tick_limit: u64,
-  /// Guest memory and stack ceiling.
+  /// Guest linear-memory ceiling.
   pub memory_limit: u64,
+  /// Guest execution-stack ceiling.
+  pub stack_limit: u64,
   /// Byte ceiling applied independently to inherited stdout and stderr.
   pub file_size_limit: usize,
+  /// Seed for the deterministic `random_get` stream.
+  pub random_seed: u64,
+  /// Readings returned by the realtime and monotonic clocks.
+  pub clock: Clock,
+  /// Wasm proposals accepted beyond Hull's strict MVP feature set.
+  pub features: Vec<WasmFeature>,
   /// Optional ambient working directory exposed to the guest.
   pub cwd: Option<PathBuf>,
 }
 
-/// Runs one local program with inherited streams and an explicit file-size ceiling.
+/// Runs one local program with inherited streams and an explicit file-size ceiling, reporting a
+/// guest backtrace on runtime errors.
 pub fn run_local(request: LocalProgramRequest) -> Result<ProgramResult> {
-  let stack = usize::try_from(request.memory_limit)
-    .context("memory_limit does not fit the host address width")?;
-  let engine = Engine::new(&engine_config(stack.max(1))?)?;
-  let module = load_module_path(&engine, &request.wasm_path)?;
-  let mut linker = Linker::new(&engine);
-  wasi::add_to_linker(&mut linker)?;
-  let state = wasi::State::new_local(
-    &request.arguments,
-    request.memory_limit,
-    request.file_size_limit,
-    request.cwd.as_deref(),
-  )?;
-  let mut store = Box::pin(Store::new(&engine, state));
-  store.limiter(|state| &mut state.memory);
+  run_local_with(request, None)
+}
+
+/// Runs one local program and attributes its ticks to sampled guest call stacks.
+///
+/// A stack is captured at the first function entry or loop header reached after at least
+/// `sample_interval` further ticks, and those ticks are charged to it.
+pub fn profile_local(
+  request: LocalProgramRequest,
+  sample_interval: u64,
+) -> Result<(ProgramResult, Profile)> {
+  let sampler = Arc::new(Mutex::new(profile::Sampler::new(
+    request.tick_limit,
+    sample_interval,
+  )));
+  let result = run_local_with(request, Some(Arc::clone(&sampler)))?;
+  let sampler = Arc::into_inner(sampler)
+    .ok_or_else(|| anyhow!("the profiled store outlived its execution"))?
+    .into_inner()
+    .map_err(|_| anyhow!("the profile sampler panicked"))?;
+  let profile = sampler.finish(result.tick);
+  Ok((result, profile))
+}
+
+fn run_local_with(
+  request: LocalProgramRequest,
+  sampler: Option<Arc<Mutex<profile::Sampler>>>,
+) -> Result<ProgramResult> {
+  let stack = usize::try_from(request.stack_limit)
+    .context("stack_limit does not fit the host address width")?;
+  let loaded = if sampler.is_some() {
+    // Profiling needs epoch checkpoints and uninlined frames, so it never shares compiled code.
+    let mut config = engine_config(stack.max(1), wasm_features(&request.features))?;
+    enable_backtraces(&mut config);
+    profile::configure(&mut config);
+    let engine = Engine::new(&config)?;
+    let source = std::fs::read(&request.wasm_path)
+      .with_context(|| format!("failed to read {}", request.wasm_path.display()))?;
+    let guest = module::load_guest(&engine, &source)?;
+    let mut linker = Linker::new(&engine);
+    wasi::add_to_linker(&mut linker)?;
+    let mut component_linker = component::Linker::new(&engine);
+    wasi::add_to_component_linker(&mut component_linker)?;
+    registry::LoadedProgram {
+      engine,
+      linker,
+      component_linker,
+      guest,
+    }
+  } else {
+    registry::load(
+      stack.max(1),
+      false,
+      true,
+      wasm_features(&request.features),
+      &request.wasm_path,
+    )?
+  };
+  let state = wasi::State::new_local(&request)?;
+  let mut store = Box::pin(Store::new(&loaded.engine, state));
+  wasi::bind_store(&mut store);
   store.set_fuel(request.tick_limit)?;
+  wasi::install_hostcall_hook(&mut store);
+  if let Some(sampler) = sampler {
+    profile::install(store.as_mut().get_mut(), sampler)?;
+  }
   let tick_limit = request.tick_limit;
   let state = store.data() as *const wasi::State;
   let telemetry = Rc::new(Cell::new(PendingTelemetry::default()));
   let inner = async move {
-    let (result, setup_error) = match linker
-      .instantiate_async(store.as_mut().get_mut(), &module)
-      .await
-    {
-      Err(error) => {
-        let message = error.to_string();
-        (Err(error), Some(message))
-      }
-      Ok(instance) => match instance.get_typed_func::<(), ()>(store.as_mut().get_mut(), "_start") {
-        Err(error) => {
-          let message = error.to_string();
-          (Err(error), Some(message))
-        }
-        Ok(start) => (start.call_async(store.as_mut().get_mut(), ()).await, None),
-      },
-    };
+    let (result, setup_error) = run_guest(store.as_mut().get_mut(), &loaded).await;
     let tick = tick_limit.saturating_sub(store.get_fuel().unwrap_or(0));
     let memory = u64::try_from(store.data().memory.peak).unwrap_or(u64::MAX);
+    let (state, stack) = stack::measure_released(|| (*Pin::into_inner(store)).into_data());
     Execution {
       result,
       setup_error,
-      state: (*Pin::into_inner(store)).into_data(),
+      state,
       tick,
       memory,
+      stack,
+      coredump: None,
     }
   }
   .boxed_local();
   let future = TrackedExecutionFuture {
     inner,
     state,
+    completed: false,
     telemetry: Rc::clone(&telemetry),
   }
   .boxed_local();
-  let schedule = scheduler::round_robin(vec![Some(future)]);
+  let schedule = scheduler::round_robin(vec![Some(future)], &[]);
   let Some(execution) = schedule.completed.into_iter().next().flatten() else {
-    let pending = telemetry.get();
+    let pending = telemetry.take();
     return Ok(ProgramResult {
       program: "local".into(),
       status: if pending.memory_exceeded {
@@ -453,6 +674,7 @@ pub fn run_local(request: LocalProgramRequest) -> Result<ProgramResult> {
       },
       tick: 0,
       memory: pending.memory,
+      stack: 0,
       exit_code: None,
       error_message: Some(
         if pending.memory_exceeded {
@@ -464,6 +686,10 @@ pub fn run_local(request: LocalProgramRequest) -> Result<ProgramResult> {
         }
         .into(),
       ),
+      backtrace: Vec::new(),
+      coredump_path: None,
+      files: Vec::new(),
+      memory_timeline: pending.memory_timeline,
     });
   };
   Ok(
@@ -474,6 +700,7 @@ pub fn run_local(request: LocalProgramRequest) -> Result<ProgramResult> {
         execution.state.local_file_error_exceeded(),
         execution.tick,
         execution.memory,
+        execution.stack,
         "local",
       )
     } else if let Some(error) = execution.setup_error {
@@ -485,6 +712,7 @@ pub fn run_local(request: LocalProgramRequest) -> Result<ProgramResult> {
         execution.state.local_file_error_exceeded(),
         execution.tick,
         execution.memory,
+        execution.stack,
         "local",
       )
     },
@@ -498,6 +726,7 @@ impl fmt::Display for RunStatus {
       Self::RuntimeError => "runtime_error",
       Self::TimeLimitExceeded => "time_limit_exceeded",
       Self::MemoryLimitExceeded => "memory_limit_exceeded",
+      Self::Terminated => "terminated",
       Self::FileError => "file_error",
       Self::InternalError => "internal_error",
     };
@@ -516,9 +745,10 @@ pub fn write_report(path: &Path, report: &SessionReport) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-  use std::{process::Command, sync::OnceLock};
+  use std::{num::NonZeroU64, process::Command, sync::OnceLock};
 
   use super::*;
+  use wasmtime::Module;
 
   fn file_access_module() -> PathBuf {
     static MODULE: OnceLock<Result<(tempfile::TempDir, PathBuf), String>> = OnceLock::new();
@@ -547,15 +777,25 @@ mod tests {
       name: name.into(),
       wasm_path,
       arguments: Vec::new(),
+      environment: BTreeMap::new(),
       tick_limit: 1_000_000,
       memory_limit: 1 << 20,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".into(),
           permissions: DirectoryPermissions::ReadExecute,
         }],
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: null_descriptors(),
     }
@@ -590,12 +830,12 @@ mod tests {
 
   #[test]
   fn engine_accepts_mvp() {
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
     Module::new(&engine, b"\0asm\x01\0\0\0").unwrap();
   }
 
   #[test]
-  fn deep_recursion_uses_memory_limit() {
+  fn deep_recursion_uses_stack_limit() {
     let directory = tempfile::tempdir().unwrap();
     let wasm = module(
       directory.path(),
@@ -619,36 +859,180 @@ mod tests {
           call $recurse
           drop))"#,
     );
-    let run = |memory_limit| {
+    let run = |memory_limit, stack_limit| {
       let mut recursive = program("recursive", wasm.clone());
+      recursive.backtrace = true;
       recursive.memory_limit = memory_limit;
+      recursive.stack_limit = stack_limit;
       recursive.tick_limit = 10_000_000;
       run_session(SessionRequest {
         report_path: directory.path().join("report.json"),
         files: Vec::new(),
         programs: vec![recursive],
+        terminate_on_exit: Vec::new(),
       })
       .results
       .remove(0)
     };
 
-    let limited = run(512 * 1024);
-    assert_eq!(limited.status, RunStatus::RuntimeError);
-    assert!(
-      limited
-        .error_message
-        .as_deref()
-        .is_some_and(|message| message.contains("call stack exhausted"))
+    for limited in [
+      run(512 * 1024, None),
+      run(16 * 1024 * 1024, Some(512 * 1024)),
+    ] {
+      assert_eq!(limited.status, RunStatus::RuntimeError);
+      assert_eq!(
+        limited.error_message.as_deref(),
+        Some("Stack limit exceeded")
+      );
+      assert!(!limited.backtrace.is_empty());
+    }
+    let accepted = run(512 * 1024, Some(16 * 1024 * 1024));
+    assert_eq!(accepted.status, RunStatus::Accepted);
+    assert!(accepted.stack > 512 * 1024);
+    assert!(accepted.stack < 16 * 1024 * 1024);
+  }
+
+  #[test]
+  fn runtime_error_reports_symbolized_backtrace() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "crash.wat",
+      // An indirect call keeps Wasmtime from inlining the trapping frame into its caller.
+      r#"(module
+        (type $void (func))
+        (table 1 funcref)
+        (elem (i32.const 0) $crash)
+        (func $crash unreachable)
+        (func $main (export "_start") i32.const 0 call_indirect (type $void)))"#,
+    );
+    let run = |backtrace| {
+      let mut crash = program("crash", wasm.clone());
+      crash.backtrace = backtrace;
+      run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: Vec::new(),
+        programs: vec![crash],
+        terminate_on_exit: Vec::new(),
+      })
+      .results
+      .remove(0)
+    };
+    let untraced = run(false);
+    assert_eq!(untraced.status, RunStatus::RuntimeError);
+    assert!(untraced.backtrace.is_empty());
+
+    let result = run(true);
+    assert_eq!(result.status, RunStatus::RuntimeError);
+    assert_eq!(
+      result
+        .backtrace
+        .iter()
+        .map(|frame| frame.function.as_str())
+        .collect::<Vec<_>>(),
+      ["crash", "main"]
+    );
+    let message = result.error_message.as_deref().unwrap();
+    assert!(message.contains("unreachable"), "{message}");
+    assert!(!message.contains("backtrace"), "{message}");
+  }
+
+  #[test]
+  fn runtime_error_writes_requested_coredump() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "crash.wat",
+      r#"(module (memory 1) (func (export "_start") unreachable))"#,
     );
-    assert_eq!(run(16 * 1024 * 1024).status, RunStatus::Accepted);
+    let mut crash = program("crash", wasm.clone());
+    crash.coredump = true;
+    let request = SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![crash, program("plain", wasm)],
+      terminate_on_exit: Vec::new(),
+    };
+    let expected = request.coredump_path(&request.programs[0]);
+    let report = run_session(request);
+
+    let result = &report.results[0];
+    assert_eq!(result.status, RunStatus::RuntimeError);
+    assert_eq!(result.coredump_path.as_ref(), Some(&expected));
+    assert!(std::fs::read(&expected).unwrap().starts_with(b"\0asm"));
+    let message = result.error_message.as_deref().unwrap();
+    assert!(message.contains("unreachable"), "{message}");
+    assert!(!message.contains("core dump"), "{message}");
+    assert_eq!(report.results[1].coredump_path, None);
+    assert!(!directory.path().join("report.json.plain.coredump").exists());
   }
 
   #[test]
   fn engine_rejects_simd() {
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
     assert!(Module::new(&engine, "(module (func (drop (v128.const i32x4 0 0 0 0))))").is_err());
   }
 
+  #[test]
+  fn engine_accepts_opted_in_features() {
+    let features = wasm_features(&[WasmFeature::Simd, WasmFeature::TailCall]);
+    let engine = Engine::new(&engine_config(1024, features).unwrap()).unwrap();
+    Module::new(&engine, "(module (func (drop (v128.const i32x4 0 0 0 0))))").unwrap();
+    Module::new(&engine, "(module (func $f return_call $f))").unwrap();
+    assert!(
+      Module::new(
+        &engine,
+        "(module (func (memory.fill (i32.const 0) (i32.const 0) (i32.const 0))) (memory 1))"
+      )
+      .is_err()
+    );
+  }
+
+  #[test]
+  fn profile_attributes_ticks_to_named_functions() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "hot-loop.wat",
+      r#"(module
+        (func $hot (param $n i32)
+          (loop $again
+            local.get $n
+            i32.const 1
+            i32.sub
+            local.tee $n
+            br_if $again))
+        (func $main (export "_start")
+          i32.const 10000
+          call $hot))"#,
+    );
+    let (result, profile) = profile_local(
+      LocalProgramRequest {
+        wasm_path: wasm,
+        arguments: Vec::new(),
+        environment: BTreeMap::new(),
+        tick_limit: 1_000_000,
+        memory_limit: 1 << 20,
+        stack_limit: 1 << 20,
+        random_seed: 0,
+        clock: Clock::Frozen,
+        features: Vec::new(),
+        file_size_limit: 0,
+        cwd: None,
+      },
+      100,
+    )
+    .unwrap();
+
+    assert_eq!(result.status, RunStatus::Accepted);
+    assert_eq!(profile.total(), result.tick);
+    let mut collapsed = Vec::new();
+    profile.write_collapsed(&mut collapsed).unwrap();
+    let collapsed = String::from_utf8(collapsed).unwrap();
+    assert!(collapsed.contains("main;hot "), "{collapsed}");
+    assert_eq!(profile.self_ticks()[0].0, "hot");
+  }
+
   #[test]
   fn session_accepts_ignored_stdio() {
     let directory = tempfile::tempdir().unwrap();
@@ -661,11 +1045,185 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: Vec::new(),
       programs: vec![program("empty", wasm)],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted);
     assert!(report.deadlocks.is_empty());
   }
 
+  #[test]
+  fn random_seed_selects_random_stream() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "random.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "random_get"
+          (func $random_get (param i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "_start")
+          i32.const 16 i32.const 8 call $random_get drop
+          i32.const 0 i32.const 16 i32.store
+          i32.const 4 i32.const 8 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let random_bytes = |seed: u64, name: &str| {
+      let output = directory.path().join(name);
+      let mut random = program("random", wasm.clone());
+      random.random_seed = seed;
+      connect(&mut random, 1, "output", FilePermissions::Write);
+      let report = run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: vec![File::regular(
+          "output",
+          Some(output.clone()),
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(8),
+        )],
+        programs: vec![random],
+        terminate_on_exit: Vec::new(),
+      });
+      assert_eq!(report.results[0].status, RunStatus::Accepted);
+      std::fs::read(output).unwrap()
+    };
+
+    let first = random_bytes(7, "first");
+    assert_eq!(first.len(), 8);
+    assert_eq!(random_bytes(7, "again"), first);
+    assert_ne!(random_bytes(8, "other"), first);
+  }
+
+  #[test]
+  fn environment_is_visible_to_guest() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "environ.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "environ_sizes_get"
+          (func $environ_sizes_get (param i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "environ_get"
+          (func $environ_get (param i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "_start")
+          i32.const 0 i32.const 4 call $environ_sizes_get drop
+          (if (i32.ne (i32.load (i32.const 0)) (i32.const 2)) (then unreachable))
+          i32.const 16 i32.const 64 call $environ_get drop
+          (if (i32.ne (i32.load (i32.const 16)) (i32.const 64)) (then unreachable))
+          i32.const 0 i32.const 64 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let output = directory.path().join("output");
+    let mut environ = program("environ", wasm);
+    environ.environment = BTreeMap::from([
+      ("ONLINE_JUDGE".into(), "1".into()),
+      ("A".into(), "x=y".into()),
+    ]);
+    connect(&mut environ, 1, "output", FilePermissions::Write);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![File::regular(
+        "output",
+        Some(output.clone()),
+        FilePermissions::Write,
+        FileSizeLimit::Bytes(64),
+      )],
+      programs: vec![environ],
+      terminate_on_exit: Vec::new(),
+    });
+    assert_eq!(report.results[0].status, RunStatus::Accepted);
+    assert_eq!(std::fs::read(output).unwrap(), b"A=x=y\0ONLINE_JUDGE=1\0");
+
+    let mut invalid = program("invalid", directory.path().join("environ.wat"));
+    invalid.environment = BTreeMap::from([("A=B".into(), "1".into())]);
+    let request = SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![invalid],
+      terminate_on_exit: Vec::new(),
+    };
+    let error = request.validate().unwrap_err().to_string();
+    assert!(
+      error.contains("invalid environment variable name `A=B`"),
+      "{error}"
+    );
+    let report = run_session(request);
+    assert_eq!(report.results[0].status, RunStatus::InternalError);
+    assert_eq!(
+      report.results[0].error_message.as_deref(),
+      Some(error.as_str())
+    );
+  }
+
+  #[test]
+  fn tick_clock_follows_consumed_ticks() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "clock.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "clock_time_get"
+          (func $clock_time_get (param i32 i64 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func $spin (local $i i32)
+          (loop $again
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (br_if $again (i32.lt_u (local.get $i) (i32.const 1000)))))
+        (func (export "_start")
+          call $spin
+          i32.const 1 i64.const 1 i32.const 16 call $clock_time_get drop
+          call $spin
+          i32.const 1 i64.const 1 i32.const 24 call $clock_time_get drop
+          i32.const 0 i32.const 16 i32.store
+          i32.const 4 i32.const 16 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let readings = |clock: Clock, name: &str| {
+      let output = directory.path().join(name);
+      let mut timed = program("clock", wasm.clone());
+      timed.clock = clock;
+      connect(&mut timed, 1, "output", FilePermissions::Write);
+      let report = run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: vec![File::regular(
+          "output",
+          Some(output.clone()),
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(16),
+        )],
+        programs: vec![timed],
+        terminate_on_exit: Vec::new(),
+      });
+      assert_eq!(report.results[0].status, RunStatus::Accepted);
+      let bytes = std::fs::read(output).unwrap();
+      let first = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+      let second = u64::from_le_bytes(bytes[8..].try_into().unwrap());
+      (first, second, report.results[0].tick)
+    };
+
+    assert_eq!(
+      readings(Clock::Frozen, "frozen"),
+      (0, 0, readings(Clock::Frozen, "again").2)
+    );
+    let nanosecond_ticks = Clock::Ticks {
+      ticks_per_second: NonZeroU64::new(1_000_000_000).unwrap(),
+    };
+    let (first, second, tick) = readings(nanosecond_ticks, "ticks");
+    assert!(first >= 1000, "{first}");
+    assert!(second >= first + 1000, "{first} {second}");
+    assert!(second < tick, "{second} {tick}");
+    assert_eq!(readings(nanosecond_ticks, "repeat"), (first, second, tick));
+  }
+
   #[test]
   fn session_latches_pipe_file_error() {
     let directory = tempfile::tempdir().unwrap();
@@ -704,6 +1262,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: vec![File::pipe("output", 1, FileSizeLimit::Bytes(1))],
       programs: vec![writer, reader],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::FileError);
     assert_eq!(report.results[1].status, RunStatus::Accepted);
@@ -747,6 +1306,7 @@ mod tests {
         FileSizeLimit::Bytes(1),
       )],
       programs: vec![writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::FileError);
   }
@@ -789,73 +1349,241 @@ mod tests {
         FileSizeLimit::Bytes(1),
       )],
       programs: vec![writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::FileError);
   }
 
   #[test]
-  fn initial_oversize_isolated_without_commit() {
+  fn io_cost_charges_ticks_per_call_and_byte() {
     let directory = tempfile::tempdir().unwrap();
-    let destination = directory.path().join("shared");
-    std::fs::write(&destination, b"original").unwrap();
     let wasm = module(
       directory.path(),
-      "empty.wat",
-      "(module (func (export \"_start\")))",
-    );
-    let mut affected = program("affected", wasm.clone());
-    affected.file_system.bindings.push(FileBinding {
-      path: "shared".into(),
-      file: "shared".into(),
-      permissions: FilePermissions::ReadWrite,
-    });
-    let mut descriptor_affected = program("descriptor-affected", wasm.clone());
-    connect(&mut descriptor_affected, 0, "shared", FilePermissions::Read);
-    let report = run_session(SessionRequest {
-      report_path: directory.path().join("report.json"),
-      files: vec![File::regular(
-        "shared",
-        Some(destination.clone()),
-        FilePermissions::ReadWrite,
-        FileSizeLimit::Bytes(1),
-      )],
-      programs: vec![affected, descriptor_affected, program("unrelated", wasm)],
-    });
-
-    assert!(report.results[..2].iter().all(|result| {
-      (result.status, result.tick, result.memory) == (RunStatus::FileError, 0, 0)
-    }));
-    assert_eq!(report.results[2].status, RunStatus::Accepted);
-    assert_eq!(std::fs::read(destination).unwrap(), b"original");
-  }
-
-  #[test]
-  fn file_error_belongs_to_writer() {
-    let directory = tempfile::tempdir().unwrap();
-    let writer_wasm = module(
-      directory.path(),
-      "file-writer.wat",
+      "write-twice.wat",
       r#"(module
         (import "wasi_snapshot_preview1" "fd_write"
           (func $fd_write (param i32 i32 i32 i32) (result i32)))
         (memory (export "memory") 1)
-        (data (i32.const 16) "ab")
         (func (export "_start")
-          i32.const 0 i32.const 16 i32.store
-          i32.const 4 i32.const 2 i32.store
+          i32.const 0 i32.const 64 i32.store
+          i32.const 4 i32.const 100 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop
           i32.const 1 i32.const 0 i32.const 1 i32.const 8
           call $fd_write drop))"#,
     );
-    let reader_wasm = module(
+    let run = |io_cost| {
+      let mut writer = program("writer", wasm.clone());
+      writer.io_cost = io_cost;
+      run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: Vec::new(),
+        programs: vec![writer],
+        terminate_on_exit: Vec::new(),
+      })
+      .results
+      .remove(0)
+    };
+
+    let free = run(IoCost::default());
+    let charged = run(IoCost {
+      ticks_per_call: 1_000,
+      ticks_per_byte: 10,
+    });
+    assert_eq!(free.status, RunStatus::Accepted);
+    assert_eq!(charged.status, RunStatus::Accepted);
+    assert_eq!(charged.tick - free.tick, 2 * (1_000 + 10 * 100));
+    let exhausted = run(IoCost {
+      ticks_per_call: 0,
+      ticks_per_byte: 1_000_000,
+    });
+    assert_eq!(exhausted.status, RunStatus::TimeLimitExceeded);
+    assert_eq!(exhausted.tick, 1_000_000);
+  }
+
+  #[test]
+  fn io_cost_charges_failed_and_end_of_file_reads() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
       directory.path(),
-      "file-reader.wat",
+      "read-nothing.wat",
       r#"(module
         (import "wasi_snapshot_preview1" "fd_read"
           (func $fd_read (param i32 i32 i32 i32) (result i32)))
         (memory (export "memory") 1)
         (func (export "_start")
-          i32.const 0 i32.const 16 i32.store
-          i32.const 4 i32.const 1 i32.store
+          i32.const 0 i32.const 64 i32.store
+          i32.const 4 i32.const 16 i32.store
+          i32.const 0 i32.const 0 i32.const 1 i32.const 8
+          call $fd_read drop
+          i32.const 9 i32.const 0 i32.const 1 i32.const 8
+          call $fd_read drop))"#,
+    );
+    let run = |io_cost| {
+      let mut reader = program("reader", wasm.clone());
+      reader.io_cost = io_cost;
+      run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: Vec::new(),
+        programs: vec![reader],
+        terminate_on_exit: Vec::new(),
+      })
+      .results
+      .remove(0)
+    };
+
+    let free = run(IoCost::default());
+    let charged = run(IoCost {
+      ticks_per_call: 1_000,
+      ticks_per_byte: 10,
+    });
+    assert_eq!(free.status, RunStatus::Accepted);
+    assert_eq!(charged.status, RunStatus::Accepted);
+    assert_eq!(charged.tick - free.tick, 2 * 1_000);
+  }
+
+  #[test]
+  fn session_reports_per_file_statistics() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = directory.path().join("input");
+    std::fs::write(&input, b"12345").unwrap();
+    let wasm = module(
+      directory.path(),
+      "byte-reader.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_read"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 80) "ok")
+        (func (export "_start")
+          i32.const 0 i32.const 64 i32.store
+          i32.const 4 i32.const 1 i32.store
+          (loop $bytes
+            i32.const 0 i32.const 0 i32.const 1 i32.const 8
+            call $fd_read drop
+            i32.const 8 i32.load br_if $bytes)
+          i32.const 16 i32.const 80 i32.store
+          i32.const 20 i32.const 2 i32.store
+          i32.const 1 i32.const 16 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let mut reader = program("reader", wasm);
+    connect(&mut reader, 0, "input", FilePermissions::Read);
+    connect(&mut reader, 1, "output", FilePermissions::Write);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![
+        File::regular(
+          "input",
+          Some(input),
+          FilePermissions::Read,
+          FileSizeLimit::Bytes(16),
+        ),
+        File::regular(
+          "unused",
+          None,
+          FilePermissions::Read,
+          FileSizeLimit::Bytes(16),
+        ),
+        File::regular(
+          "output",
+          None,
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(16),
+        ),
+      ],
+      programs: vec![reader],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(report.results[0].status, RunStatus::Accepted);
+    assert_eq!(
+      report.results[0].files,
+      [
+        FileStatistics {
+          file: "input".into(),
+          bytes_read: 5,
+          reads: 6,
+          size: 5,
+          ..FileStatistics::default()
+        },
+        FileStatistics {
+          file: "output".into(),
+          bytes_written: 2,
+          writes: 1,
+          size: 2,
+          ..FileStatistics::default()
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn initial_oversize_isolated_without_commit() {
+    let directory = tempfile::tempdir().unwrap();
+    let destination = directory.path().join("shared");
+    std::fs::write(&destination, b"original").unwrap();
+    let wasm = module(
+      directory.path(),
+      "empty.wat",
+      "(module (func (export \"_start\")))",
+    );
+    let mut affected = program("affected", wasm.clone());
+    affected.file_system.bindings.push(FileBinding {
+      path: "shared".into(),
+      file: "shared".into(),
+      permissions: FilePermissions::ReadWrite,
+    });
+    let mut descriptor_affected = program("descriptor-affected", wasm.clone());
+    connect(&mut descriptor_affected, 0, "shared", FilePermissions::Read);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![File::regular(
+        "shared",
+        Some(destination.clone()),
+        FilePermissions::ReadWrite,
+        FileSizeLimit::Bytes(1),
+      )],
+      programs: vec![affected, descriptor_affected, program("unrelated", wasm)],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert!(report.results[..2].iter().all(|result| {
+      (result.status, result.tick, result.memory) == (RunStatus::FileError, 0, 0)
+    }));
+    assert_eq!(report.results[2].status, RunStatus::Accepted);
+    assert_eq!(std::fs::read(destination).unwrap(), b"original");
+  }
+
+  #[test]
+  fn file_error_belongs_to_writer() {
+    let directory = tempfile::tempdir().unwrap();
+    let writer_wasm = module(
+      directory.path(),
+      "file-writer.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "ab")
+        (func (export "_start")
+          i32.const 0 i32.const 16 i32.store
+          i32.const 4 i32.const 2 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let reader_wasm = module(
+      directory.path(),
+      "file-reader.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_read"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "_start")
+          i32.const 0 i32.const 16 i32.store
+          i32.const 4 i32.const 1 i32.store
           i32.const 0 i32.const 0 i32.const 1 i32.const 8
           call $fd_read drop))"#,
     );
@@ -872,12 +1600,86 @@ mod tests {
         FileSizeLimit::Bytes(1),
       )],
       programs: vec![writer, reader],
+      terminate_on_exit: Vec::new(),
     });
 
     assert_eq!(report.results[0].status, RunStatus::FileError);
     assert_eq!(report.results[1].status, RunStatus::Accepted);
   }
 
+  /// Grows memory by one page after every hundred loop iterations, without any hostcall.
+  const GROWTH_LOOP: &str = r#"(module
+    (memory (export "memory") 1)
+    (func $grow (param $pages i32)
+      local.get $pages memory.grow drop)
+    (func (export "_start") (local $index i32)
+      loop $spin
+        local.get $index i32.const 1 i32.add local.tee $index
+        i32.const 100 i32.rem_u i32.eqz
+        if i32.const 1 call $grow end
+        local.get $index i32.const 300 i32.lt_u br_if $spin
+      end))"#;
+
+  #[test]
+  fn session_reports_memory_growth_timeline() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(directory.path(), "growth.wat", GROWTH_LOOP);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![program("growth", wasm)],
+      terminate_on_exit: Vec::new(),
+    });
+
+    let result = &report.results[0];
+    assert_eq!(result.status, RunStatus::Accepted, "{report:?}");
+    let timeline = &result.memory_timeline;
+    assert_eq!(
+      timeline
+        .iter()
+        .map(|growth| growth.memory)
+        .collect::<Vec<_>>(),
+      [1 << 16, 2 << 16, 3 << 16, 4 << 16]
+    );
+    assert_eq!(timeline[0].tick, 0);
+    assert!(timeline[1].tick > 100);
+    assert!(
+      timeline.windows(2).all(|pair| pair[0].tick < pair[1].tick),
+      "{timeline:?}"
+    );
+    assert!(timeline[3].tick < result.tick);
+    assert_eq!(timeline[3].memory, result.memory);
+  }
+
+  #[test]
+  fn local_run_times_memory_growth_without_hostcalls() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(directory.path(), "growth.wat", GROWTH_LOOP);
+    let result = run_local(LocalProgramRequest {
+      wasm_path: wasm,
+      arguments: Vec::new(),
+      environment: BTreeMap::new(),
+      tick_limit: 1_000_000,
+      memory_limit: 1 << 20,
+      stack_limit: 1 << 20,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      features: Vec::new(),
+      file_size_limit: 0,
+      cwd: None,
+    })
+    .unwrap();
+
+    assert_eq!(result.status, RunStatus::Accepted);
+    let ticks = result
+      .memory_timeline
+      .iter()
+      .map(|growth| growth.tick)
+      .collect::<Vec<_>>();
+    assert_eq!(ticks.len(), 4);
+    assert!(ticks.windows(2).all(|pair| pair[0] < pair[1]), "{ticks:?}");
+  }
+
   #[test]
   fn pending_memory_precedes_writer_file_error() {
     let directory = tempfile::tempdir().unwrap();
@@ -938,6 +1740,7 @@ mod tests {
           File::pipe("right", 1, FileSizeLimit::Bytes(1)),
         ],
         programs: vec![writer, peer],
+        terminate_on_exit: Vec::new(),
       })
     };
 
@@ -971,8 +1774,13 @@ mod tests {
         status,
         tick: 7,
         memory: 11,
+        stack: 13,
         exit_code: Some(0),
         error_message: None,
+        backtrace: Vec::new(),
+        coredump_path: None,
+        files: Vec::new(),
+        memory_timeline: Vec::new(),
       })
       .collect::<Vec<_>>();
 
@@ -1033,6 +1841,7 @@ mod tests {
         FileSizeLimit::Bytes(2),
       )],
       programs: vec![writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted);
     assert_eq!(std::fs::read(output_path).unwrap(), b"B");
@@ -1063,6 +1872,7 @@ mod tests {
         FileSizeLimit::Bytes(1024),
       )],
       programs: vec![guest],
+      terminate_on_exit: Vec::new(),
     });
     (
       report.results.into_iter().next().unwrap(),
@@ -1160,6 +1970,7 @@ mod tests {
         FileSizeLimit::Bytes(1024),
       )],
       programs: vec![guest],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
   }
@@ -1194,6 +2005,7 @@ mod tests {
         FileSizeLimit::Bytes(1024),
       )],
       programs: vec![waiting],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(
       report.results[0].status,
@@ -1204,13 +2016,14 @@ mod tests {
       report.results[0].error_message.as_deref(),
       Some("Protocol deadlock")
     );
-    assert_eq!(
-      report.deadlocks,
-      vec![Deadlock {
-        programs: vec!["poll-nonzero".into()],
-        pipes: Vec::new(),
-      }]
-    );
+    assert_eq!(report.deadlocks.len(), 1);
+    let deadlock = &report.deadlocks[0];
+    assert_eq!(deadlock.programs, ["poll-nonzero"]);
+    assert!(deadlock.pipes.is_empty() && deadlock.buffered.is_empty());
+    assert_eq!(deadlock.blocked.len(), 1);
+    assert_eq!(deadlock.blocked[0].hostcall, "poll_oneoff");
+    assert!(deadlock.blocked[0].descriptors.is_empty());
+    assert!(deadlock.blocked[0].tick > 0);
   }
 
   #[test]
@@ -1253,6 +2066,7 @@ mod tests {
         FileSizeLimit::Bytes(200_000),
       )],
       programs: vec![writer],
+      terminate_on_exit: Vec::new(),
     });
 
     assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
@@ -1313,6 +2127,7 @@ mod tests {
         FileSizeLimit::Bytes(200_000),
       )],
       programs: vec![reader],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
   }
@@ -1348,6 +2163,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: Vec::new(),
       programs: vec![guest],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
   }
@@ -1389,6 +2205,7 @@ mod tests {
         ),
       ],
       programs: vec![load_failure, missing_start],
+      terminate_on_exit: Vec::new(),
     });
     assert!(
       report
@@ -1423,6 +2240,7 @@ mod tests {
         FileSizeLimit::Bytes(1024),
       )],
       programs: vec![guest],
+      terminate_on_exit: Vec::new(),
     });
 
     assert_eq!(report.results[0].status, RunStatus::MemoryLimitExceeded);
@@ -1437,10 +2255,490 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: Vec::new(),
       programs: vec![program("missing-start", wasm)],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::InternalError);
   }
 
+  /// Component that writes `hello` to stdout through `wasi:cli/stdout` and returns the write
+  /// status, or an error when `fail` is set.
+  fn hello_component(fail: bool) -> String {
+    r#"(component $command
+      (import "wasi:io/error@0.2.0" (instance $error_instance
+        (export "error" (type (sub resource)))))
+      (alias export $error_instance "error" (type $error))
+      (import "wasi:io/streams@0.2.0" (instance $streams
+        (alias outer $command $error (type $outer_error))
+        (export "error" (type $stream_error_resource (eq $outer_error)))
+        (export "output-stream" (type $output_stream (sub resource)))
+        (type $failure (variant
+          (case "last-operation-failed" (own $stream_error_resource))
+          (case "closed")))
+        (export "stream-error" (type $stream_error (eq $failure)))
+        (export "[method]output-stream.blocking-write-and-flush"
+          (func (param "self" (borrow $output_stream)) (param "contents" (list u8))
+            (result (result (error $stream_error)))))))
+      (alias export $streams "output-stream" (type $output_stream))
+      (import "wasi:cli/stdout@0.2.0" (instance $stdout
+        (alias outer $command $output_stream (type $outer_stream))
+        (export "output-stream" (type $stream (eq $outer_stream)))
+        (export "get-stdout" (func (result (own $stream))))))
+      (core module $Memory (memory (export "memory") 1))
+      (core instance $memory (instantiate $Memory))
+      (core func $get_stdout (canon lower (func $stdout "get-stdout")))
+      (core func $write
+        (canon lower (func $streams "[method]output-stream.blocking-write-and-flush")
+          (memory $memory "memory")))
+      (core module $Main
+        (import "host" "get-stdout" (func $get_stdout (result i32)))
+        (import "host" "write" (func $write (param i32 i32 i32 i32)))
+        (import "host" "memory" (memory 1))
+        (data (i32.const 16) "hello")
+        (func (export "run") (result i32)
+          call $get_stdout
+          i32.const 16 i32.const 5 i32.const 32
+          call $write
+          i32.const 32 i32.load8_u
+          i32.const FAIL i32.or))
+      (core instance $main (instantiate $Main
+        (with "host" (instance
+          (export "get-stdout" (func $get_stdout))
+          (export "write" (func $write))
+          (export "memory" (memory $memory "memory"))))))
+      (func $run (result (result)) (canon lift (core func $main "run")))
+      (instance $run_instance (export "run" (func $run)))
+      (export "wasi:cli/run@0.2.0" (instance $run_instance)))"#
+      .replace("FAIL", if fail { "1" } else { "0" })
+  }
+
+  #[test]
+  fn session_runs_command_components() {
+    let directory = tempfile::tempdir().unwrap();
+    for (fail, status) in [
+      (false, RunStatus::Accepted),
+      (true, RunStatus::RuntimeError),
+    ] {
+      let output_path = directory.path().join(format!("output-{fail}"));
+      let wasm = module(
+        directory.path(),
+        &format!("hello-{fail}.wat"),
+        &hello_component(fail),
+      );
+      let mut command = program("command", wasm);
+      connect(&mut command, 1, "output", FilePermissions::Write);
+      let report = run_session(SessionRequest {
+        report_path: directory.path().join("report.json"),
+        files: vec![File::regular(
+          "output",
+          Some(output_path.clone()),
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(1024),
+        )],
+        programs: vec![command],
+        terminate_on_exit: Vec::new(),
+      });
+
+      assert_eq!(report.results[0].status, status, "{report:?}");
+      assert_eq!(report.results[0].exit_code, Some(i32::from(fail)));
+      assert!(report.results[0].tick > 0);
+      assert_eq!(std::fs::read(output_path).unwrap(), b"hello");
+    }
+  }
+
+  /// `wasi:io` streams and `wasi:cli/stdout` imports shared by the test components below, with a
+  /// bump allocator for lowered lists and lowered `blocking-read`, `blocking-write-and-flush`, and
+  /// `get-stdout`.
+  const COMPONENT_STREAMS: &str = r#"
+      (import "wasi:io/error@0.2.0" (instance $error_instance
+        (export "error" (type (sub resource)))))
+      (alias export $error_instance "error" (type $error))
+      (import "wasi:io/streams@0.2.0" (instance $streams
+        (alias outer $command $error (type $outer_error))
+        (export "error" (type $stream_error_resource (eq $outer_error)))
+        (export "input-stream" (type $input_stream (sub resource)))
+        (export "output-stream" (type $output_stream (sub resource)))
+        (type $failure (variant
+          (case "last-operation-failed" (own $stream_error_resource))
+          (case "closed")))
+        (export "stream-error" (type $stream_error (eq $failure)))
+        (export "[method]input-stream.blocking-read"
+          (func (param "self" (borrow $input_stream)) (param "len" u64)
+            (result (result (list u8) (error $stream_error)))))
+        (export "[method]output-stream.blocking-write-and-flush"
+          (func (param "self" (borrow $output_stream)) (param "contents" (list u8))
+            (result (result (error $stream_error)))))))
+      (alias export $streams "input-stream" (type $input_stream))
+      (alias export $streams "output-stream" (type $output_stream))
+      (import "wasi:cli/stdout@0.2.0" (instance $stdout
+        (alias outer $command $output_stream (type $outer_stream))
+        (export "output-stream" (type $stream (eq $outer_stream)))
+        (export "get-stdout" (func (result (own $stream))))))
+      (core module $Memory
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "realloc") (param i32 i32 i32 i32) (result i32) (local $pointer i32)
+          global.get $next local.get 2 i32.add i32.const 1 i32.sub
+          i32.const 0 local.get 2 i32.sub i32.and
+          local.tee $pointer local.get 3 i32.add global.set $next
+          local.get $pointer))
+      (core instance $memory (instantiate $Memory))
+      (core func $get_stdout (canon lower (func $stdout "get-stdout")))
+      (core func $read
+        (canon lower (func $streams "[method]input-stream.blocking-read")
+          (memory $memory "memory") (realloc (func $memory "realloc"))))
+      (core func $write
+        (canon lower (func $streams "[method]output-stream.blocking-write-and-flush")
+          (memory $memory "memory")))"#;
+
+  /// Lifts the core instance `$main` as the `wasi:cli/run` export.
+  const COMPONENT_RUN: &str = r#"
+      (func $run (result (result)) (canon lift (core func $main "run")))
+      (instance $run_instance (export "run" (func $run)))
+      (export "wasi:cli/run@0.2.0" (instance $run_instance))"#;
+
+  /// Component that copies one `blocking-read` of up to 64 stdin bytes to stdout.
+  fn cat_component() -> String {
+    format!(
+      r#"(component $command
+      {COMPONENT_STREAMS}
+      (import "wasi:cli/stdin@0.2.0" (instance $stdin
+        (alias outer $command $input_stream (type $outer_stream))
+        (export "input-stream" (type $stream (eq $outer_stream)))
+        (export "get-stdin" (func (result (own $stream))))))
+      (core func $get_stdin (canon lower (func $stdin "get-stdin")))
+      (core module $Main
+        (import "host" "get-stdin" (func $get_stdin (result i32)))
+        (import "host" "get-stdout" (func $get_stdout (result i32)))
+        (import "host" "read" (func $read (param i32 i64 i32)))
+        (import "host" "write" (func $write (param i32 i32 i32 i32)))
+        (import "host" "memory" (memory 1))
+        (func (export "run") (result i32)
+          call $get_stdin i64.const 64 i32.const 16 call $read
+          (if (i32.load8_u (i32.const 16)) (then unreachable))
+          call $get_stdout
+          (i32.load (i32.const 20)) (i32.load (i32.const 24)) i32.const 32
+          call $write
+          i32.const 32 i32.load8_u))
+      (core instance $main (instantiate $Main
+        (with "host" (instance
+          (export "get-stdin" (func $get_stdin))
+          (export "get-stdout" (func $get_stdout))
+          (export "read" (func $read))
+          (export "write" (func $write))
+          (export "memory" (memory $memory "memory"))))))
+      {COMPONENT_RUN})"#
+    )
+  }
+
+  /// Component that opens `input.txt` beside its first preopen and copies up to 64 bytes of it to
+  /// stdout.
+  fn read_file_component() -> String {
+    format!(
+      r#"(component $command
+      {COMPONENT_STREAMS}
+      (import "wasi:filesystem/types@0.2.0" (instance $types
+        (export "descriptor" (type $descriptor (sub resource)))
+        (type $path_flags_type (flags "symlink-follow"))
+        (export "path-flags" (type $path_flags (eq $path_flags_type)))
+        (type $open_flags_type (flags "create" "directory" "exclusive" "truncate"))
+        (export "open-flags" (type $open_flags (eq $open_flags_type)))
+        (type $descriptor_flags_type (flags "read" "write" "file-integrity-sync"
+          "data-integrity-sync" "requested-write-sync" "mutate-directory"))
+        (export "descriptor-flags" (type $descriptor_flags (eq $descriptor_flags_type)))
+        (type $error_code_type (enum "access" "would-block" "already" "bad-descriptor" "busy"
+          "deadlock" "quota" "exist" "file-too-large" "illegal-byte-sequence" "in-progress"
+          "interrupted" "invalid" "io" "is-directory" "loop" "too-many-links" "message-size"
+          "name-too-long" "no-device" "no-entry" "no-lock" "insufficient-memory"
+          "insufficient-space" "not-directory" "not-empty" "not-recoverable" "unsupported"
+          "no-tty" "no-such-device" "overflow" "not-permitted" "pipe" "read-only"
+          "invalid-seek" "text-file-busy" "cross-device"))
+        (export "error-code" (type $error_code (eq $error_code_type)))
+        (export "[method]descriptor.open-at"
+          (func (param "self" (borrow $descriptor)) (param "path-flags" $path_flags)
+            (param "path" string) (param "open-flags" $open_flags)
+            (param "flags" $descriptor_flags)
+            (result (result (own $descriptor) (error $error_code)))))
+        (export "[method]descriptor.read"
+          (func (param "self" (borrow $descriptor)) (param "length" u64) (param "offset" u64)
+            (result (result (tuple (list u8) bool) (error $error_code)))))))
+      (alias export $types "descriptor" (type $descriptor))
+      (import "wasi:filesystem/preopens@0.2.0" (instance $preopens
+        (alias outer $command $descriptor (type $outer_descriptor))
+        (export "descriptor" (type $preopen (eq $outer_descriptor)))
+        (export "get-directories" (func (result (list (tuple (own $preopen) string)))))))
+      (core func $get_directories
+        (canon lower (func $preopens "get-directories")
+          (memory $memory "memory") (realloc (func $memory "realloc"))))
+      (core func $open_at
+        (canon lower (func $types "[method]descriptor.open-at") (memory $memory "memory")))
+      (core func $read_at
+        (canon lower (func $types "[method]descriptor.read")
+          (memory $memory "memory") (realloc (func $memory "realloc"))))
+      (core module $Main
+        (import "host" "get-directories" (func $get_directories (param i32)))
+        (import "host" "open-at" (func $open_at (param i32 i32 i32 i32 i32 i32 i32)))
+        (import "host" "read-at" (func $read_at (param i32 i64 i64 i32)))
+        (import "host" "get-stdout" (func $get_stdout (result i32)))
+        (import "host" "write" (func $write (param i32 i32 i32 i32)))
+        (import "host" "memory" (memory 1))
+        (data (i32.const 64) "input.txt")
+        (func (export "run") (result i32)
+          i32.const 48 call $get_directories
+          (i32.load (i32.load (i32.const 48)))
+          i32.const 0 i32.const 64 i32.const 9 i32.const 0 i32.const 1 i32.const 16
+          call $open_at
+          (if (i32.load8_u (i32.const 16)) (then unreachable))
+          (i32.load (i32.const 20)) i64.const 64 i64.const 0 i32.const 16
+          call $read_at
+          (if (i32.load8_u (i32.const 16)) (then unreachable))
+          call $get_stdout
+          (i32.load (i32.const 20)) (i32.load (i32.const 24)) i32.const 32
+          call $write
+          i32.const 32 i32.load8_u))
+      (core instance $main (instantiate $Main
+        (with "host" (instance
+          (export "get-directories" (func $get_directories))
+          (export "open-at" (func $open_at))
+          (export "read-at" (func $read_at))
+          (export "get-stdout" (func $get_stdout))
+          (export "write" (func $write))
+          (export "memory" (memory $memory "memory"))))))
+      {COMPONENT_RUN})"#
+    )
+  }
+
+  #[test]
+  fn session_component_copies_stdin_to_stdout() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = directory.path().join("input");
+    let output = directory.path().join("output");
+    std::fs::write(&input, b"ping").unwrap();
+    let mut cat = program("cat", module(directory.path(), "cat.wat", &cat_component()));
+    connect(&mut cat, 0, "input", FilePermissions::Read);
+    connect(&mut cat, 1, "output", FilePermissions::Write);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![
+        File::regular(
+          "input",
+          Some(input),
+          FilePermissions::Read,
+          FileSizeLimit::Bytes(16),
+        ),
+        File::regular(
+          "output",
+          Some(output.clone()),
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(16),
+        ),
+      ],
+      programs: vec![cat],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
+    assert_eq!(std::fs::read(output).unwrap(), b"ping");
+  }
+
+  #[test]
+  fn session_component_reads_bound_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = directory.path().join("input");
+    let output = directory.path().join("output");
+    std::fs::write(&input, b"from file").unwrap();
+    let wasm = module(directory.path(), "read-file.wat", &read_file_component());
+    let mut reader = program("reader", wasm);
+    reader.file_system.bindings.push(FileBinding {
+      path: "input.txt".into(),
+      file: "input".into(),
+      permissions: FilePermissions::Read,
+    });
+    connect(&mut reader, 1, "output", FilePermissions::Write);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![
+        File::regular(
+          "input",
+          Some(input),
+          FilePermissions::Read,
+          FileSizeLimit::Bytes(16),
+        ),
+        File::regular(
+          "output",
+          Some(output.clone()),
+          FilePermissions::Write,
+          FileSizeLimit::Bytes(16),
+        ),
+      ],
+      programs: vec![reader],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
+    assert_eq!(std::fs::read(output).unwrap(), b"from file");
+  }
+
+  #[test]
+  fn session_component_memory_growth_is_mle() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(
+      directory.path(),
+      "grow.wat",
+      &format!(
+        r#"(component $command
+        (core module $Main
+          (memory (export "memory") 1)
+          (func (export "run") (result i32)
+            (if (i32.lt_s (memory.grow (i32.const 16)) (i32.const 0)) (then unreachable))
+            i32.const 0))
+        (core instance $main (instantiate $Main))
+        {COMPONENT_RUN})"#
+      ),
+    );
+    let mut grower = program("grower", wasm);
+    grower.memory_limit = 4 * 64 * 1024;
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![grower],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(
+      report.results[0].status,
+      RunStatus::MemoryLimitExceeded,
+      "{report:?}"
+    );
+  }
+
+  #[test]
+  fn session_component_pipe_cycle_is_deadlock() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = module(directory.path(), "cat.wat", &cat_component());
+    let mut first = program("first", wasm.clone());
+    connect(&mut first, 0, "left", FilePermissions::Read);
+    connect(&mut first, 1, "right", FilePermissions::Write);
+    let mut second = program("second", wasm);
+    connect(&mut second, 0, "right", FilePermissions::Read);
+    connect(&mut second, 1, "left", FilePermissions::Write);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![
+        File::pipe("left", 1, FileSizeLimit::Bytes(1)),
+        File::pipe("right", 1, FileSizeLimit::Bytes(1)),
+      ],
+      programs: vec![first, second],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(
+      report
+        .results
+        .iter()
+        .map(|result| result.status)
+        .collect::<Vec<_>>(),
+      [RunStatus::TimeLimitExceeded, RunStatus::TimeLimitExceeded],
+      "{report:?}"
+    );
+    assert_eq!(report.deadlocks.len(), 1, "{report:?}");
+    let deadlock = &report.deadlocks[0];
+    assert_eq!(deadlock.programs, ["first", "second"]);
+    assert_eq!(deadlock.pipes, ["left", "right"]);
+    assert_eq!(
+      deadlock
+        .blocked
+        .iter()
+        .map(|call| (call.program.as_str(), call.descriptors[0].file.as_deref()))
+        .collect::<Vec<_>>(),
+      [("first", Some("left")), ("second", Some("right"))]
+    );
+  }
+
+  #[test]
+  fn session_component_check_write_reports_free_pipe_space() {
+    let directory = tempfile::tempdir().unwrap();
+    let writer_wasm = module(
+      directory.path(),
+      "check-write.wat",
+      r#"(component $command
+        (import "wasi:io/error@0.2.0" (instance $error_instance
+          (export "error" (type (sub resource)))))
+        (alias export $error_instance "error" (type $error))
+        (import "wasi:io/streams@0.2.0" (instance $streams
+          (alias outer $command $error (type $outer_error))
+          (export "error" (type $stream_error_resource (eq $outer_error)))
+          (export "output-stream" (type $output_stream (sub resource)))
+          (type $failure (variant
+            (case "last-operation-failed" (own $stream_error_resource))
+            (case "closed")))
+          (export "stream-error" (type $stream_error (eq $failure)))
+          (export "[method]output-stream.check-write"
+            (func (param "self" (borrow $output_stream))
+              (result (result u64 (error $stream_error)))))))
+        (alias export $streams "output-stream" (type $output_stream))
+        (import "wasi:cli/stdout@0.2.0" (instance $stdout
+          (alias outer $command $output_stream (type $outer_stream))
+          (export "output-stream" (type $stream (eq $outer_stream)))
+          (export "get-stdout" (func (result (own $stream))))))
+        (core module $Memory (memory (export "memory") 1))
+        (core instance $memory (instantiate $Memory))
+        (core func $get_stdout (canon lower (func $stdout "get-stdout")))
+        (core func $check_write
+          (canon lower (func $streams "[method]output-stream.check-write")
+            (memory $memory "memory")))
+        (core module $Main
+          (import "host" "get-stdout" (func $get_stdout (result i32)))
+          (import "host" "check-write" (func $check_write (param i32 i32)))
+          (import "host" "memory" (memory 1))
+          (func (export "run") (result i32)
+            call $get_stdout i32.const 16 call $check_write
+            (if (i32.load8_u (i32.const 16)) (then unreachable))
+            (if (i64.ne (i64.load (i32.const 24)) (i64.const 8)) (then unreachable))
+            i32.const 0))
+        (core instance $main (instantiate $Main
+          (with "host" (instance
+            (export "get-stdout" (func $get_stdout))
+            (export "check-write" (func $check_write))
+            (export "memory" (memory $memory "memory"))))))
+        (func $run (result (result)) (canon lift (core func $main "run")))
+        (instance $run_instance (export "run" (func $run)))
+        (export "wasi:cli/run@0.2.0" (instance $run_instance)))"#,
+    );
+    let reader_wasm = module(
+      directory.path(),
+      "eof-reader.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_read"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "_start")
+          i32.const 0 i32.const 16 i32.store
+          i32.const 4 i32.const 1 i32.store
+          i32.const 0 i32.const 0 i32.const 1 i32.const 8
+          call $fd_read drop))"#,
+    );
+    let mut writer = program("writer", writer_wasm);
+    connect(&mut writer, 1, "pipe", FilePermissions::Write);
+    let mut reader = program("reader", reader_wasm);
+    connect(&mut reader, 0, "pipe", FilePermissions::Read);
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![File::pipe("pipe", 8, FileSizeLimit::Bytes(64))],
+      programs: vec![writer, reader],
+      terminate_on_exit: Vec::new(),
+    });
+
+    assert_eq!(
+      report
+        .results
+        .iter()
+        .map(|result| result.status)
+        .collect::<Vec<_>>(),
+      [RunStatus::Accepted, RunStatus::Accepted],
+      "{report:?}"
+    );
+  }
+
   #[test]
   fn session_setup_failure_closes_pipe_writer() {
     let directory = tempfile::tempdir().unwrap();
@@ -1472,6 +2770,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: vec![File::pipe("pipe", 1, FileSizeLimit::Bytes(1))],
       programs: vec![reader, writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted);
     assert_eq!(report.results[1].status, RunStatus::InternalError);
@@ -1502,6 +2801,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: vec![File::pipe("pipe", 1, FileSizeLimit::Bytes(1))],
       programs: vec![reader, writer],
+      terminate_on_exit: Vec::new(),
     });
 
     assert_eq!(report.results[0].status, RunStatus::Accepted);
@@ -1509,6 +2809,87 @@ mod tests {
     assert!(report.deadlocks.is_empty());
   }
 
+  #[test]
+  fn pipe_transcript_records_writes_with_program_and_tick() {
+    let directory = tempfile::tempdir().unwrap();
+    let writer_wasm = module(
+      directory.path(),
+      "transcript-writer.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 64) "ping\ff")
+        (func (export "_start") (local $index i32)
+          i32.const 0 i32.const 64 i32.store
+          i32.const 4 i32.const 4 i32.store
+          i32.const 1 i32.const 0 i32.const 1 i32.const 8
+          call $fd_write drop
+          (loop $spin
+            local.get $index i32.const 1 i32.add local.tee $index
+            i32.const 100 i32.lt_u br_if $spin)
+          i32.const 16 i32.const 68 i32.store
+          i32.const 20 i32.const 1 i32.store
+          i32.const 1 i32.const 16 i32.const 1 i32.const 8
+          call $fd_write drop))"#,
+    );
+    let reader_wasm = module(
+      directory.path(),
+      "transcript-reader.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "fd_read"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "_start")
+          i32.const 0 i32.const 64 i32.store
+          i32.const 4 i32.const 16 i32.store
+          (loop $drain
+            i32.const 0 i32.const 0 i32.const 1 i32.const 8
+            call $fd_read drop
+            i32.const 8 i32.load br_if $drain)))"#,
+    );
+    let mut writer = program("writer", writer_wasm);
+    connect(&mut writer, 1, "pipe", FilePermissions::Write);
+    let mut reader = program("reader", reader_wasm);
+    connect(&mut reader, 0, "pipe", FilePermissions::Read);
+    let transcript = directory.path().join("pipe.jsonl");
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![File::Pipe {
+        name: "pipe".into(),
+        capacity: 64,
+        size_limit: FileSizeLimit::Bytes(64),
+        transcript_path: Some(transcript.clone()),
+      }],
+      programs: vec![writer, reader],
+      terminate_on_exit: Vec::new(),
+    });
+    assert!(
+      report
+        .results
+        .iter()
+        .all(|result| result.status == RunStatus::Accepted),
+      "{report:?}"
+    );
+
+    let writes = std::fs::read_to_string(&transcript)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(writes.len(), 2);
+    assert_eq!(writes[0]["program"], "writer");
+    assert_eq!(writes[0]["text"], "ping");
+    assert!(writes[0].get("hex").is_none());
+    assert_eq!(writes[1]["text"], "\u{fffd}");
+    assert_eq!(writes[1]["hex"], "ff");
+    let ticks = writes
+      .iter()
+      .map(|write| write["tick"].as_u64().unwrap())
+      .collect::<Vec<_>>();
+    assert!(0 < ticks[0] && ticks[0] + 100 < ticks[1], "{ticks:?}");
+  }
+
   #[test]
   fn session_rejects_undeclared_file_creation() {
     let directory = tempfile::tempdir().unwrap();
@@ -1535,10 +2916,211 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: Vec::new(),
       programs: vec![program("writer", wasm)],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(report.results[0].status, RunStatus::Accepted);
   }
 
+  #[test]
+  fn scratch_directory_enforces_quota() {
+    let directory = tempfile::tempdir().unwrap();
+    // Each step traps unless the call returns the expected errno.
+    let wasm = module(
+      directory.path(),
+      "scratch.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "path_open"
+          (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_rename"
+          (func $path_rename (param i32 i32 i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_create_directory"
+          (func $path_create_directory (param i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_unlink_file"
+          (func $path_unlink_file (param i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 64) "tmp/a")
+        (data (i32.const 72) "tmp/b")
+        (data (i32.const 80) "tmp/c")
+        (data (i32.const 88) "tmp/d")
+        (data (i32.const 96) "x")
+        (data (i32.const 104) "hello")
+        (func $expect (param i32 i32)
+          (if (i32.ne (local.get 0) (local.get 1)) (then unreachable)))
+        (func $create (param i32) (result i32)
+          i32.const 3 i32.const 0 local.get 0 i32.const 5 i32.const 1
+          i64.const 66 i64.const 0 i32.const 0 i32.const 32
+          call $path_open)
+        (func $write (result i32)
+          i32.const 0 i32.const 104 i32.store
+          i32.const 4 i32.const 5 i32.store
+          i32.const 32 i32.load i32.const 0 i32.const 1 i32.const 8
+          call $fd_write)
+        (func (export "_start")
+          (call $expect (call $create (i32.const 64)) (i32.const 0))
+          (call $expect (call $write) (i32.const 0))
+          (call $expect
+            (call $path_rename (i32.const 3) (i32.const 64) (i32.const 5)
+              (i32.const 3) (i32.const 72) (i32.const 5))
+            (i32.const 0))
+          (call $expect (call $create (i32.const 80)) (i32.const 0))
+          (call $expect
+            (call $path_create_directory (i32.const 3) (i32.const 88) (i32.const 5))
+            (i32.const 19))
+          (call $expect (call $write) (i32.const 19))
+          (call $expect
+            (call $path_unlink_file (i32.const 3) (i32.const 72) (i32.const 5))
+            (i32.const 0))
+          (call $expect
+            (call $path_create_directory (i32.const 3) (i32.const 88) (i32.const 5))
+            (i32.const 0))
+          (call $expect
+            (call $path_open (i32.const 3) (i32.const 0) (i32.const 96) (i32.const 1)
+              (i32.const 1) (i64.const 66) (i64.const 0) (i32.const 0) (i32.const 32))
+            (i32.const 69))))"#,
+    );
+    let mut scratch = program("scratch", wasm);
+    scratch.file_system.scratch.push(ScratchDirectory {
+      path: "tmp".into(),
+      size_limit: FileSizeLimit::Bytes(8),
+      file_limit: 2,
+    });
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![scratch],
+      terminate_on_exit: Vec::new(),
+    });
+    assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
+    assert!(!directory.path().join("tmp").exists());
+  }
+
+  #[test]
+  fn symlinks_resolve_relative_targets_and_aliases_share_inodes() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(directory.path().join("data"), b"data").unwrap();
+    // Each step traps unless the call returns the expected errno or value.
+    let wasm = module(
+      directory.path(),
+      "symlinks.wat",
+      r#"(module
+        (import "wasi_snapshot_preview1" "path_readlink"
+          (func $path_readlink (param i32 i32 i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_open"
+          (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_read"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_filestat_get"
+          (func $path_filestat_get (param i32 i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 64) "task/data")
+        (data (i32.const 80) "loop")
+        (data (i32.const 96) "alias")
+        (func $expect (param i32 i32)
+          (if (i32.ne (local.get 0) (local.get 1)) (then unreachable)))
+        (func $open (param i32 i32 i32) (result i32)
+          i32.const 3 local.get 0 local.get 1 local.get 2 i32.const 0
+          i64.const 2 i64.const 0 i32.const 0 i32.const 32
+          call $path_open)
+        (func $stat (param i32 i32 i32) (result i32)
+          i32.const 3 local.get 0 local.get 1 local.get 2 i32.const 256
+          call $path_filestat_get)
+        (func (export "_start")
+          (call $expect
+            (call $path_readlink (i32.const 3) (i32.const 64) (i32.const 9)
+              (i32.const 200) (i32.const 64) (i32.const 40))
+            (i32.const 0))
+          (call $expect (i32.load (i32.const 40)) (i32.const 14))
+          (call $expect (i32.load8_u (i32.const 213)) (i32.const 97))
+          (call $expect (call $open (i32.const 1) (i32.const 64) (i32.const 9)) (i32.const 0))
+          (i32.store (i32.const 0) (i32.const 300))
+          (i32.store (i32.const 4) (i32.const 4))
+          (call $expect
+            (call $fd_read (i32.load (i32.const 32)) (i32.const 0) (i32.const 1) (i32.const 40))
+            (i32.const 0))
+          (call $expect (i32.load (i32.const 300)) (i32.const 0x61746164))
+          (call $expect (call $open (i32.const 0) (i32.const 64) (i32.const 9)) (i32.const 32))
+          (call $expect (call $open (i32.const 1) (i32.const 80) (i32.const 4)) (i32.const 32))
+          (call $expect (call $stat (i32.const 0) (i32.const 64) (i32.const 9)) (i32.const 0))
+          (call $expect (i32.load8_u (i32.const 272)) (i32.const 7))
+          (call $expect (call $stat (i32.const 1) (i32.const 64) (i32.const 9)) (i32.const 0))
+          (call $expect (i32.load8_u (i32.const 272)) (i32.const 4))
+          (call $expect (i32.wrap_i64 (i64.load (i32.const 280))) (i32.const 2))
+          (i64.store (i32.const 400) (i64.load (i32.const 264)))
+          (call $expect (call $stat (i32.const 0) (i32.const 96) (i32.const 5)) (i32.const 0))
+          (call $expect
+            (i64.eq (i64.load (i32.const 264)) (i64.load (i32.const 400)))
+            (i32.const 1))))"#,
+    );
+    let mut linked = program("linked", wasm);
+    for path in ["task", "shared"] {
+      linked.file_system.directories.push(DirectoryBinding {
+        path: path.into(),
+        permissions: DirectoryPermissions::ReadExecute,
+      });
+    }
+    for path in ["shared/data", "alias"] {
+      linked.file_system.bindings.push(FileBinding {
+        path: path.into(),
+        file: "data".into(),
+        permissions: FilePermissions::Read,
+      });
+    }
+    for (path, target) in [("task/data", "../shared/data"), ("loop", "loop")] {
+      linked.file_system.symlinks.push(SymlinkBinding {
+        path: path.into(),
+        target: target.into(),
+      });
+    }
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: vec![File::regular(
+        "data",
+        Some(directory.path().join("data")),
+        FilePermissions::Read,
+        FileSizeLimit::Bytes(4),
+      )],
+      programs: vec![linked],
+      terminate_on_exit: Vec::new(),
+    });
+    assert_eq!(report.results[0].status, RunStatus::Accepted, "{report:?}");
+  }
+
+  #[test]
+  fn controlling_program_exit_terminates_running_programs() {
+    let directory = tempfile::tempdir().unwrap();
+    let spinning = module(
+      directory.path(),
+      "spin.wat",
+      r#"(module (func (export "_start") (loop br 0)))"#,
+    );
+    let exiting = module(
+      directory.path(),
+      "exit.wat",
+      r#"(module (func (export "_start")))"#,
+    );
+    // The solution yields to the interactor once per scheduler interval and would otherwise spin
+    // until its tick limit.
+    let mut solution = program("solution", spinning);
+    solution.tick_limit = 1_000_000_000;
+    let report = run_session(SessionRequest {
+      report_path: directory.path().join("report.json"),
+      files: Vec::new(),
+      programs: vec![solution, program("interactor", exiting)],
+      terminate_on_exit: vec!["interactor".into()],
+    });
+    assert_eq!(
+      report
+        .results
+        .iter()
+        .map(|result| result.status)
+        .collect::<Vec<_>>(),
+      [RunStatus::Terminated, RunStatus::Accepted]
+    );
+    assert!(report.deadlocks.is_empty());
+  }
+
   #[test]
   fn session_transfers_bounded_pipe() {
     let directory = tempfile::tempdir().unwrap();
@@ -1577,6 +3159,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: vec![File::pipe("pipe", 1, FileSizeLimit::Bytes(1))],
       programs: vec![reader, writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(
       report
@@ -1634,6 +3217,7 @@ mod tests {
       report_path: directory.path().join("report.json"),
       files: vec![File::pipe("pipe", 1, FileSizeLimit::Bytes(1))],
       programs: vec![reader, writer],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(
       report
@@ -1675,6 +3259,7 @@ mod tests {
         File::pipe("right", 1, FileSizeLimit::Bytes(1)),
       ],
       programs: vec![first, second],
+      terminate_on_exit: Vec::new(),
     });
     assert_eq!(
       report
@@ -1690,6 +3275,36 @@ mod tests {
       vec![Deadlock {
         programs: vec!["first".into(), "second".into()],
         pipes: vec!["left".into(), "right".into()],
+        blocked: vec![
+          BlockedCall {
+            program: "first".into(),
+            hostcall: "fd_read".into(),
+            descriptors: vec![BlockedDescriptor {
+              fd: 0,
+              file: Some("left".into()),
+            }],
+            tick: 12,
+          },
+          BlockedCall {
+            program: "second".into(),
+            hostcall: "fd_read".into(),
+            descriptors: vec![BlockedDescriptor {
+              fd: 0,
+              file: Some("right".into()),
+            }],
+            tick: 12,
+          },
+        ],
+        buffered: vec![
+          PipeBuffer {
+            pipe: "left".into(),
+            bytes: 0,
+          },
+          PipeBuffer {
+            pipe: "right".into(),
+            bytes: 0,
+          },
+        ],
       }]
     );
   }
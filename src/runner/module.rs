@@ -8,48 +8,101 @@ use std::{
 
 use anyhow::{Result, anyhow, bail};
 use sha2::{Digest, Sha256};
-use wasmtime::{Engine, Module};
+use wasmtime::{Engine, Module, component::Component};
+
+/// Compiled guest program.
+#[derive(Clone)]
+pub enum Guest {
+  /// Core module started through its `_start` export.
+  Module(Module),
+  /// Component started through its `wasi:cli/run` export.
+  Component(Component),
+}
+
+impl Guest {
+  fn serialize(&self) -> wasmtime::Result<Vec<u8>> {
+    match self {
+      Self::Module(module) => module.serialize(),
+      Self::Component(component) => component.serialize(),
+    }
+  }
+}
 
 /// Loads authoritative source Wasm, transparently reusing Hull's private native cache.
-pub fn load_module(engine: &Engine, source: &[u8]) -> Result<Module> {
+pub fn load_guest(engine: &Engine, source: &[u8]) -> Result<Guest> {
   if Engine::detect_precompiled(source).is_some() {
     bail!("authoritative module input must be source Wasm");
   }
   let Some(cache) = super::module_cache_directory() else {
-    return compile_module(engine, source);
+    return compile_guest(engine, source);
   };
-  load_module_in_cache(engine, source, &cache)
+  load_guest_in_cache(engine, source, &cache)
+}
+
+/// Reports whether source Wasm, binary or text, encodes a component rather than a core module.
+fn is_component(source: &[u8]) -> bool {
+  if let Some(header) = source.strip_prefix(b"\0asm") {
+    // Both encodings share the magic; the layer field after the version tells them apart.
+    return header.get(2..4) == Some(&[1, 0]);
+  }
+  let mut text = source;
+  loop {
+    text = text.trim_ascii_start();
+    match text.strip_prefix(b";;") {
+      Some(comment) => {
+        let end = comment
+          .iter()
+          .position(|byte| *byte == b'\n')
+          .unwrap_or(comment.len());
+        text = &comment[end..];
+      }
+      None => break,
+    }
+  }
+  text
+    .strip_prefix(b"(")
+    .is_some_and(|form| form.trim_ascii_start().starts_with(b"component"))
 }
 
-fn compile_module(engine: &Engine, source: &[u8]) -> Result<Module> {
-  Module::new(engine, source)
-    .map_err(|error| anyhow!("failed to compile authoritative Wasm source: {error}"))
+fn compile_guest(engine: &Engine, source: &[u8]) -> Result<Guest> {
+  if is_component(source) {
+    Component::new(engine, source).map(Guest::Component)
+  } else {
+    Module::new(engine, source).map(Guest::Module)
+  }
+  .map_err(|error| anyhow!("failed to compile authoritative Wasm source: {error:#}"))
 }
 
-fn load_module_in_cache(engine: &Engine, source: &[u8], cache: &Path) -> Result<Module> {
+fn load_guest_in_cache(engine: &Engine, source: &[u8], cache: &Path) -> Result<Guest> {
   let path = cache.join(format!("{}.cwasm", cache_key(engine, source)));
   if let Ok(bytes) = fs::read(&path) {
-    // SAFETY: This private cache contains only the direct output of `Module::serialize` below.
-    // Reading into owned memory also prevents later file replacement from changing live code.
-    if let Ok(module) = unsafe { Module::deserialize(engine, &bytes) } {
-      return Ok(module);
+    // SAFETY: This private cache contains only the direct output of `Guest::serialize` below,
+    // keyed by the source that also decides the guest kind. Reading into owned memory also
+    // prevents later file replacement from changing live code.
+    let cached = if is_component(source) {
+      unsafe { Component::deserialize(engine, &bytes) }.map(Guest::Component)
+    } else {
+      unsafe { Module::deserialize(engine, &bytes) }.map(Guest::Module)
+    };
+    if let Ok(guest) = cached {
+      return Ok(guest);
     }
   }
 
-  let module = compile_module(engine, source)?;
-  let Ok(serialized) = module.serialize() else {
-    return Ok(module);
+  let guest = compile_guest(engine, source)?;
+  let Ok(serialized) = guest.serialize() else {
+    return Ok(guest);
   };
   if fs::create_dir_all(cache).is_err() {
-    return Ok(module);
+    return Ok(guest);
   }
   let Ok(mut temporary) = tempfile::NamedTempFile::new_in(cache) else {
-    return Ok(module);
+    return Ok(guest);
   };
   if std::io::Write::write_all(&mut temporary, &serialized).is_ok() {
     let _ = temporary.persist(path);
   }
-  Ok(module)
+  Ok(guest)
 }
 
 fn cache_key(engine: &Engine, source: &[u8]) -> String {
@@ -72,36 +125,36 @@ fn cache_key(engine: &Engine, source: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::runner::engine_config;
+  use crate::runner::{engine_config, wasm_features};
 
   const EMPTY_WASM: &[u8] = b"\0asm\x01\0\0\0";
 
   #[test]
   fn source_loads() {
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
-    load_module(&engine, EMPTY_WASM).unwrap();
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
+    load_guest(&engine, EMPTY_WASM).unwrap();
   }
 
   #[test]
   fn native_artifact_is_rejected() {
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
     let native = engine.precompile_module(EMPTY_WASM).unwrap();
-    assert!(load_module(&engine, &native).is_err());
+    assert!(load_guest(&engine, &native).is_err());
   }
 
   #[test]
   fn cache_never_hides_invalid_source_without_cache() {
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
-    assert!(load_module(&engine, b"invalid").is_err());
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
+    assert!(load_guest(&engine, b"invalid").is_err());
   }
 
   #[test]
   fn cached_module_is_shared_across_stack_limits() {
     let cache = tempfile::tempdir().unwrap();
-    let small = Engine::new(&engine_config(64 * 1024).unwrap()).unwrap();
-    let large = Engine::new(&engine_config(16 * 1024 * 1024).unwrap()).unwrap();
+    let small = Engine::new(&engine_config(64 * 1024, wasm_features(&[])).unwrap()).unwrap();
+    let large = Engine::new(&engine_config(16 * 1024 * 1024, wasm_features(&[])).unwrap()).unwrap();
 
-    load_module_in_cache(&small, EMPTY_WASM, cache.path()).unwrap();
+    load_guest_in_cache(&small, EMPTY_WASM, cache.path()).unwrap();
     let path = cache
       .path()
       .join(format!("{}.cwasm", cache_key(&small, EMPTY_WASM)));
@@ -113,14 +166,29 @@ mod tests {
   #[test]
   fn invalid_cache_falls_back_to_source() {
     let cache = tempfile::tempdir().unwrap();
-    let engine = Engine::new(&engine_config(1024).unwrap()).unwrap();
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
     let path = cache
       .path()
       .join(format!("{}.cwasm", cache_key(&engine, EMPTY_WASM)));
     std::fs::write(&path, b"invalid cache entry").unwrap();
 
-    load_module_in_cache(&engine, EMPTY_WASM, cache.path()).unwrap();
+    load_guest_in_cache(&engine, EMPTY_WASM, cache.path()).unwrap();
     let repaired = std::fs::read(path).unwrap();
     unsafe { Module::deserialize(&engine, repaired) }.unwrap();
   }
+
+  #[test]
+  fn components_are_told_apart_from_modules() {
+    assert!(!is_component(EMPTY_WASM));
+    assert!(!is_component(b"(module)"));
+    assert!(is_component(b"\0asm\x0d\0\x01\0"));
+    assert!(is_component(b";; command\n( component )"));
+
+    let engine = Engine::new(&engine_config(1024, wasm_features(&[])).unwrap()).unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    for _ in 0..2 {
+      let guest = load_guest_in_cache(&engine, b"(component)", cache.path()).unwrap();
+      assert!(matches!(guest, Guest::Component(_)));
+    }
+  }
 }
@@ -0,0 +1,169 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  io::{self, Write},
+  num::NonZeroUsize,
+  sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use wasmtime::{
+  AsContext, Config, FrameInfo, Inlining, Store, StoreContextMut, UpdateDeadline, WasmBacktrace,
+};
+
+const MAX_PROFILE_FRAMES: usize = 4096;
+const UNSAMPLED_FRAME: &str = "[unsampled]";
+
+/// Ticks attributed to distinct guest call stacks, ordered from the outermost frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+  stacks: BTreeMap<Vec<String>, u64>,
+}
+
+impl Profile {
+  /// Returns the total number of attributed ticks.
+  pub fn total(&self) -> u64 {
+    self.stacks.values().sum()
+  }
+
+  /// Returns self ticks per function, heaviest first.
+  pub fn self_ticks(&self) -> Vec<(String, u64)> {
+    let mut functions = BTreeMap::<&str, u64>::new();
+    for (stack, ticks) in &self.stacks {
+      if let Some(function) = stack.last() {
+        *functions.entry(function).or_default() += ticks;
+      }
+    }
+    let mut functions = functions
+      .into_iter()
+      .map(|(function, ticks)| (function.to_owned(), ticks))
+      .collect::<Vec<_>>();
+    functions.sort_by(|left, right| right.1.cmp(&left.1).then_with(|| left.0.cmp(&right.0)));
+    functions
+  }
+
+  /// Writes the profile in the collapsed-stack format read by flamegraph tools.
+  pub fn write_collapsed(&self, writer: &mut impl Write) -> io::Result<()> {
+    for (stack, ticks) in &self.stacks {
+      writeln!(writer, "{} {ticks}", stack.join(";"))?;
+    }
+    Ok(())
+  }
+
+  fn add(&mut self, stack: Vec<String>, ticks: u64) {
+    if ticks > 0 {
+      *self.stacks.entry(stack).or_default() += ticks;
+    }
+  }
+}
+
+/// Fuel-driven stack sampler shared with a store's epoch callback.
+pub struct Sampler {
+  tick_limit: u64,
+  interval: u64,
+  attributed: u64,
+  names: HashMap<(u32, Option<usize>), Vec<String>>,
+  profile: Profile,
+}
+
+impl Sampler {
+  pub fn new(tick_limit: u64, interval: u64) -> Self {
+    Self {
+      tick_limit,
+      interval: interval.max(1),
+      attributed: 0,
+      names: HashMap::new(),
+      profile: Profile::default(),
+    }
+  }
+
+  fn sample<T>(&mut self, context: StoreContextMut<'_, T>) {
+    let tick = self
+      .tick_limit
+      .saturating_sub(context.get_fuel().unwrap_or(0));
+    let ticks = tick.saturating_sub(self.attributed);
+    if ticks < self.interval {
+      return;
+    }
+    let backtrace = WasmBacktrace::force_capture(context.as_context());
+    let mut stack = Vec::new();
+    for frame in backtrace.frames().iter().rev() {
+      let key = (frame.func_index(), frame.module_offset());
+      stack.extend_from_slice(self.names.entry(key).or_insert_with(|| frame_names(frame)));
+    }
+    self.profile.add(stack, ticks);
+    self.attributed = tick;
+  }
+
+  /// Attributes ticks consumed after the last checkpoint and returns the profile.
+  pub fn finish(mut self, tick: u64) -> Profile {
+    let ticks = tick.saturating_sub(self.attributed);
+    self.profile.add(vec![UNSAMPLED_FRAME.into()], ticks);
+    self.profile
+  }
+}
+
+/// Enables the checkpoints and deep stacks used by the sampler.
+///
+/// Cross-function inlining is disabled because inlined Wasm callees leave no frame to sample.
+/// Fuel is charged per Wasm operator, so ticks are unaffected.
+pub fn configure(config: &mut Config) {
+  config
+    .compiler_inlining(Inlining::No)
+    .epoch_interruption(true)
+    .wasm_backtrace_max_frames(NonZeroUsize::new(MAX_PROFILE_FRAMES));
+}
+
+/// Samples at every epoch checkpoint without ever advancing the engine epoch.
+pub fn install<T: 'static>(store: &mut Store<T>, sampler: Arc<Mutex<Sampler>>) -> Result<()> {
+  // Compiled code only publishes fuel at calls and when a fuel interval runs out. The fuel check
+  // at a checkpoint precedes its epoch check, so yielding at the sample interval keeps the
+  // sampled tick exact.
+  store.fuel_async_yield_interval(Some(sampler.lock().unwrap().interval))?;
+  store.set_epoch_deadline(0);
+  store.epoch_deadline_callback(move |context| {
+    sampler.lock().unwrap().sample(context);
+    Ok(UpdateDeadline::Continue(0))
+  });
+  Ok(())
+}
+
+fn frame_names(frame: &FrameInfo) -> Vec<String> {
+  super::backtrace::frame_symbols(frame)
+    .into_iter()
+    .rev()
+    .map(|symbol| symbol.function.replace(';', ":"))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn collapsed_output_and_self_ticks() {
+    let mut profile = Profile::default();
+    profile.add(vec!["main".into(), "solve".into()], 7);
+    profile.add(vec!["main".into()], 2);
+    profile.add(vec!["main".into(), "read".into(), "solve".into()], 3);
+    profile.add(vec!["main".into(), "idle".into()], 0);
+
+    let mut output = Vec::new();
+    profile.write_collapsed(&mut output).unwrap();
+    assert_eq!(
+      String::from_utf8(output).unwrap(),
+      "main 2\nmain;read;solve 3\nmain;solve 7\n"
+    );
+    assert_eq!(profile.total(), 12);
+    assert_eq!(
+      profile.self_ticks(),
+      [("solve".to_owned(), 10), ("main".to_owned(), 2)]
+    );
+  }
+
+  #[test]
+  fn finish_keeps_total_tick() {
+    let profile = Sampler::new(100, 10).finish(42);
+    assert_eq!(profile.total(), 42);
+    assert_eq!(profile.self_ticks(), [(UNSAMPLED_FRAME.to_owned(), 42)]);
+  }
+}
@@ -0,0 +1,254 @@
+use std::{
+  collections::HashMap,
+  hash::Hash,
+  path::Path,
+  sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use wasmtime::{Engine, Linker, WasmFeatures, component};
+
+use super::{
+  enable_backtraces, engine_config,
+  module::{self, Guest},
+  wasi,
+};
+
+/// Counters describing the setup work done and avoided by the process-wide registry.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RegistryStatistics {
+  /// Engines built, one per distinct stack ceiling, core dump setting, and feature set.
+  pub engines: u64,
+  /// Modules compiled or read from Hull's native cache.
+  pub module_loads: u64,
+  /// Module requests answered by an already loaded module.
+  pub module_reuses: u64,
+}
+
+/// Engine, WASI linkers, and guest shared by programs with equal engine settings and source.
+pub struct LoadedProgram {
+  /// Engine configured for the program's stack ceiling, core dump setting, and features.
+  pub engine: Engine,
+  /// Preview1 linker bound to `engine`.
+  pub linker: Linker<wasi::State>,
+  /// `wasi:cli/command` linker bound to `engine`.
+  pub component_linker: component::Linker<wasi::State>,
+  /// Module or component compiled by `engine`.
+  pub guest: Guest,
+}
+
+/// Engine with both of its linkers.
+type EngineEntry = (Engine, Linker<wasi::State>, component::Linker<wasi::State>);
+
+/// Stack ceiling, whether traps capture core dumps, whether they capture backtraces, and accepted
+/// Wasm features.
+type EngineKey = (usize, bool, bool, WasmFeatures);
+
+/// Engines kept alive at once; a problem rarely needs more than a few stack and feature settings.
+const MAX_ENGINES: usize = 16;
+
+/// Compiled guests kept alive at once, enough for every program of a large contest.
+const MAX_MODULES: usize = 256;
+
+/// Map that drops its least recently used entry once it grows past a fixed capacity.
+struct LruMap<K, V> {
+  entries: HashMap<K, (V, u64)>,
+  capacity: usize,
+  clock: u64,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> LruMap<K, V> {
+  fn new(capacity: usize) -> Self {
+    Self {
+      entries: HashMap::new(),
+      capacity,
+      clock: 0,
+    }
+  }
+
+  fn get(&mut self, key: &K) -> Option<V> {
+    self.clock += 1;
+    let (value, last_used) = self.entries.get_mut(key)?;
+    *last_used = self.clock;
+    Some(value.clone())
+  }
+
+  /// Returns the value already stored under `key`, or stores and returns `value`.
+  fn get_or_insert(&mut self, key: K, value: V) -> V {
+    if let Some(existing) = self.get(&key) {
+      return existing;
+    }
+    if self.entries.len() >= self.capacity
+      && let Some(oldest) = self
+        .entries
+        .iter()
+        .min_by_key(|(_, (_, last_used))| *last_used)
+        .map(|(key, _)| key.clone())
+    {
+      self.entries.remove(&oldest);
+    }
+    self.entries.insert(key, (value.clone(), self.clock));
+    value
+  }
+
+  fn contains_key(&self, key: &K) -> bool {
+    self.entries.contains_key(key)
+  }
+}
+
+struct Registry {
+  engines: LruMap<EngineKey, EngineEntry>,
+  modules: LruMap<(EngineKey, [u8; 32]), Guest>,
+  statistics: RegistryStatistics,
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(|| {
+  Mutex::new(Registry {
+    engines: LruMap::new(MAX_ENGINES),
+    modules: LruMap::new(MAX_MODULES),
+    statistics: RegistryStatistics::default(),
+  })
+});
+
+/// Locks the registry, ignoring poisoning: every update leaves the maps consistent, so a panic
+/// elsewhere while holding the lock cannot corrupt them.
+fn registry() -> MutexGuard<'static, Registry> {
+  REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the shared engine, linker, and module for engine settings and a source Wasm path.
+///
+/// Compilation runs outside the registry lock, so concurrent first loads of one source may both
+/// compile it. The first module inserted is kept and returned to both.
+///
+/// The registry keeps at most [`MAX_ENGINES`] engines and [`MAX_MODULES`] guests, dropping the
+/// least recently used ones; programs still running keep their own references.
+pub fn load(
+  max_wasm_stack: usize,
+  coredump: bool,
+  backtrace: bool,
+  features: WasmFeatures,
+  wasm_path: &Path,
+) -> Result<LoadedProgram> {
+  let source =
+    std::fs::read(wasm_path).with_context(|| format!("failed to read {}", wasm_path.display()))?;
+  let engine_key = (max_wasm_stack, coredump, backtrace, features);
+  let key = (engine_key, Sha256::digest(&source).into());
+  let (engine, linker, component_linker) = engine(engine_key)?;
+  {
+    let mut registry = registry();
+    if let Some(guest) = registry.modules.get(&key) {
+      registry.statistics.module_reuses += 1;
+      return Ok(LoadedProgram {
+        engine,
+        linker,
+        component_linker,
+        guest,
+      });
+    }
+  }
+  let guest = module::load_guest(&engine, &source)?;
+  let mut registry = registry();
+  registry.statistics.module_loads += 1;
+  let guest = registry.modules.get_or_insert(key, guest);
+  Ok(LoadedProgram {
+    engine,
+    linker,
+    component_linker,
+    guest,
+  })
+}
+
+/// Returns a snapshot of the registry counters for this process.
+pub fn statistics() -> RegistryStatistics {
+  registry().statistics
+}
+
+fn engine(key: EngineKey) -> Result<EngineEntry> {
+  if let Some(entry) = registry().engines.get(&key) {
+    return Ok(entry);
+  }
+  let (max_wasm_stack, coredump, backtrace, features) = key;
+  let mut config = engine_config(max_wasm_stack, features)?;
+  config.coredump_on_trap(coredump);
+  if backtrace {
+    enable_backtraces(&mut config);
+  }
+  let engine = Engine::new(&config)?;
+  let mut linker = Linker::new(&engine);
+  wasi::add_to_linker(&mut linker)?;
+  let mut component_linker = component::Linker::new(&engine);
+  wasi::add_to_component_linker(&mut component_linker)?;
+  let mut registry = registry();
+  if !registry.engines.contains_key(&key) {
+    registry.statistics.engines += 1;
+  }
+  Ok(
+    registry
+      .engines
+      .get_or_insert(key, (engine, linker, component_linker)),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::runner::{WasmFeature, wasm_features};
+  use wasmtime::Module;
+
+  fn module(loaded: &LoadedProgram) -> &Module {
+    match &loaded.guest {
+      Guest::Module(module) => module,
+      Guest::Component(_) => panic!("expected a core module"),
+    }
+  }
+
+  #[test]
+  fn programs_share_engines_by_settings_and_modules_by_source() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = directory.path().join("first.wat");
+    let copy = directory.path().join("copy.wat");
+    let other = directory.path().join("other.wat");
+    std::fs::write(&first, "(module)").unwrap();
+    std::fs::write(&copy, "(module)").unwrap();
+    std::fs::write(&other, "(module (func))").unwrap();
+    let stack = 3 * 1024 * 1024 + 17;
+    let strict = wasm_features(&[]);
+
+    let loaded = load(stack, false, false, strict, &first).unwrap();
+    let reused = load(stack, false, false, strict, &copy).unwrap();
+    assert!(Engine::same(&loaded.engine, &reused.engine));
+    assert!(Module::same(module(&loaded), module(&reused)));
+    let different = load(stack, false, false, strict, &other).unwrap();
+    assert!(!Module::same(module(&loaded), module(&different)));
+    let deeper = load(stack + 1, false, false, strict, &first).unwrap();
+    assert!(!Engine::same(&loaded.engine, &deeper.engine));
+    let dumping = load(stack, true, false, strict, &first).unwrap();
+    assert!(!Engine::same(&loaded.engine, &dumping.engine));
+    let tracing = load(stack, false, true, strict, &first).unwrap();
+    assert!(!Engine::same(&loaded.engine, &tracing.engine));
+    let simd = load(
+      stack,
+      false,
+      false,
+      wasm_features(&[WasmFeature::Simd]),
+      &first,
+    )
+    .unwrap();
+    assert!(!Engine::same(&loaded.engine, &simd.engine));
+  }
+
+  #[test]
+  fn lru_map_evicts_least_recently_used_entry() {
+    let mut map = LruMap::new(2);
+    assert_eq!(map.get_or_insert("a", 1), 1);
+    assert_eq!(map.get_or_insert("b", 2), 2);
+    assert_eq!(map.get(&"a"), Some(1));
+    assert_eq!(map.get_or_insert("c", 3), 3);
+    assert!(map.contains_key(&"a"));
+    assert!(!map.contains_key(&"b"));
+    assert_eq!(map.get_or_insert("a", 4), 1);
+    assert_eq!(map.entries.len(), 2);
+  }
+}
@@ -1,17 +1,19 @@
 use std::{
-  collections::{HashMap, HashSet},
+  collections::{BTreeMap, HashMap, HashSet},
   fmt,
+  num::NonZeroU64,
   path::{Path, PathBuf},
+  str::FromStr,
 };
 
 use anyhow::{Result, anyhow, bail};
 use serde::{
   Deserialize, Deserializer, Serialize, Serializer,
-  de::{self, Visitor},
+  de::{self, IntoDeserializer, Visitor},
 };
 
 /// A strict deterministic execution request.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct SessionRequest {
   /// Destination for the metadata report.
@@ -20,6 +22,9 @@ pub struct SessionRequest {
   pub files: Vec<File>,
   /// Programs in deterministic request order.
   pub programs: Vec<ProgramRequest>,
+  /// Programs whose exit stops every program still running.
+  #[serde(default)]
+  pub terminate_on_exit: Vec<String>,
 }
 
 /// Permissions granted to a file or stream descriptor.
@@ -147,7 +152,7 @@ impl<'de> Deserialize<'de> for DirectoryPermissions {
 }
 
 /// A named session file.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
 pub enum File {
   /// A seekable regular file with bounded capabilities and logical size.
@@ -169,6 +174,9 @@ pub enum File {
     capacity: u64,
     /// Cumulative stream-length ceiling.
     size_limit: FileSizeLimit,
+    /// Optional host destination for a JSON Lines log of every write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transcript_path: Option<PathBuf>,
   },
 }
 
@@ -194,6 +202,7 @@ impl File {
       name: name.into(),
       capacity,
       size_limit,
+      transcript_path: None,
     }
   }
 
@@ -205,8 +214,37 @@ impl File {
   }
 }
 
+/// A Wasm proposal a program may opt into beyond Hull's strict MVP feature set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasmFeature {
+  /// Bulk memory and table instructions such as `memory.copy` and `memory.fill`.
+  BulkMemory,
+  /// Functions and blocks with several results.
+  MultiValue,
+  /// Importing and exporting mutable globals.
+  MutableGlobal,
+  /// Non-trapping float-to-int conversions.
+  SaturatingFloatToInt,
+  /// Sign-extension operators.
+  SignExtension,
+  /// Fixed-width 128-bit SIMD.
+  Simd,
+  /// Tail calls.
+  TailCall,
+}
+
+impl FromStr for WasmFeature {
+  type Err = String;
+
+  fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+    Self::deserialize(value.into_deserializer())
+      .map_err(|error: de::value::Error| error.to_string())
+  }
+}
+
 /// A JSON byte limit or Hull's trusted-tool ceiling.
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum FileSizeLimit {
   /// An explicit byte count.
@@ -216,7 +254,7 @@ pub enum FileSizeLimit {
 }
 
 /// Marker accepted only from the JSON string `tool`.
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum ToolLimit {
   /// Selects the trusted-tool file-size ceiling.
   #[serde(rename = "tool")]
@@ -235,7 +273,7 @@ impl FileSizeLimit {
 }
 
 /// One descriptor installed before a program starts.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct InitialDescriptor {
   /// Referenced file, or `None` for `/dev/null`.
@@ -245,7 +283,7 @@ pub struct InitialDescriptor {
 }
 
 /// One program in a session.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProgramRequest {
   /// Unique program name.
@@ -254,21 +292,128 @@ pub struct ProgramRequest {
   pub wasm_path: PathBuf,
   /// Arguments after synthetic `argv[0]`.
   pub arguments: Vec<String>,
+  /// Environment variables visible through `environ_get`.
+  #[serde(default)]
+  pub environment: BTreeMap<String, String>,
   /// Guest tick ceiling.
   #[serde(deserialize_with = "deserialize_tick_limit")]
   pub tick_limit: u64,
-  /// Linear-memory and execution-stack byte ceiling.
+  /// Linear-memory byte ceiling.
   #[serde(deserialize_with = "deserialize_memory_limit")]
   pub memory_limit: u64,
+  /// Execution-stack byte ceiling, `memory_limit` when absent.
+  #[serde(default)]
+  pub stack_limit: Option<u64>,
+  /// Seed for the deterministic `random_get` stream.
+  #[serde(default)]
+  pub random_seed: u64,
+  /// Readings returned by the realtime and monotonic clocks.
+  #[serde(default)]
+  pub clock: Clock,
+  /// Ticks charged for host I/O work on top of the guest instructions around it.
+  #[serde(default)]
+  pub io_cost: IoCost,
   /// Whether a non-accepted result fails the command.
   pub required_accepted: bool,
+  /// Whether a runtime error writes a Wasm core dump next to the report.
+  #[serde(default)]
+  pub coredump: bool,
+  /// Whether a runtime error reports a symbolized guest backtrace.
+  #[serde(default)]
+  pub backtrace: bool,
+  /// Wasm proposals accepted beyond Hull's strict MVP feature set.
+  #[serde(default)]
+  pub features: Vec<WasmFeature>,
   /// Complete immutable guest filesystem view.
   pub file_system: FileSystem,
   /// Descriptors installed at fd 0, 1, 2, then fd 4 and above.
   pub initial_descriptors: Vec<InitialDescriptor>,
 }
 
+/// Time source behind `clock_time_get`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Clock {
+  /// Both clocks always read zero.
+  #[default]
+  Frozen,
+  /// Both clocks start at zero and advance deterministically with consumed ticks.
+  Ticks {
+    /// Consumed ticks per virtual second.
+    ticks_per_second: NonZeroU64,
+  },
+}
+
+/// Tick cost model for read and write hostcalls.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IoCost {
+  /// Ticks charged for every `fd_read`, `fd_pread`, `fd_write`, or `fd_pwrite` call.
+  pub ticks_per_call: u64,
+  /// Ticks charged for every byte those calls transfer.
+  pub ticks_per_byte: u64,
+}
+
+impl IoCost {
+  /// Returns the ticks charged for one call that transferred `bytes` bytes.
+  pub fn ticks(self, bytes: u64) -> u64 {
+    self
+      .ticks_per_call
+      .saturating_add(self.ticks_per_byte.saturating_mul(bytes))
+  }
+
+  /// Returns whether every I/O call is free.
+  pub fn is_free(self) -> bool {
+    self == Self::default()
+  }
+}
+
+impl Clock {
+  /// Returns the clock reading in nanoseconds after `tick` consumed ticks.
+  pub fn nanoseconds(self, tick: u64) -> u64 {
+    match self {
+      Self::Frozen => 0,
+      Self::Ticks { ticks_per_second } => {
+        let nanoseconds =
+          u128::from(tick) * NANOSECONDS_PER_SECOND / u128::from(ticks_per_second.get());
+        u64::try_from(nanoseconds).unwrap_or(u64::MAX)
+      }
+    }
+  }
+
+  /// Returns the smallest nonzero step between two readings in nanoseconds.
+  pub fn resolution(self) -> u64 {
+    match self {
+      Self::Frozen => 1,
+      Self::Ticks { ticks_per_second } => {
+        u64::try_from(NANOSECONDS_PER_SECOND.div_ceil(u128::from(ticks_per_second.get())))
+          .unwrap_or(u64::MAX)
+      }
+    }
+  }
+}
+
+const NANOSECONDS_PER_SECOND: u128 = 1_000_000_000;
+
 impl ProgramRequest {
+  /// Returns the effective execution-stack byte ceiling.
+  pub fn stack_limit(&self) -> u64 {
+    self.stack_limit.unwrap_or(self.memory_limit)
+  }
+
+  /// Returns whether this program has any descriptor or binding for a file.
+  pub fn references_file(&self, file: &str) -> bool {
+    self
+      .initial_descriptors
+      .iter()
+      .any(|descriptor| descriptor.file.as_deref() == Some(file))
+      || self
+        .file_system
+        .bindings
+        .iter()
+        .any(|binding| binding.file == file)
+  }
+
   /// Returns whether this program has a write-capable descriptor or binding for a file.
   pub fn writes_file(&self, file: &str) -> bool {
     self.initial_descriptors.iter().any(|descriptor| {
@@ -315,18 +460,46 @@ where
   })
 }
 
-/// A declared immutable guest entry tree.
-#[derive(Clone, Debug, Deserialize)]
+/// A declared immutable guest entry tree with optional private scratch directories.
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct FileSystem {
   /// Declared directories.
   pub directories: Vec<DirectoryBinding>,
-  /// Declared regular files.
+  /// Declared regular files. Bindings of one file are hard links to it.
   pub bindings: Vec<FileBinding>,
+  /// Declared symbolic links.
+  #[serde(default)]
+  pub symlinks: Vec<SymlinkBinding>,
+  /// Writable directories whose contents the program creates and the session discards.
+  #[serde(default)]
+  pub scratch: Vec<ScratchDirectory>,
+}
+
+/// A guest symbolic link.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymlinkBinding {
+  /// Normalized guest path.
+  pub path: String,
+  /// Relative target resolved from the link's parent directory, possibly through `..`.
+  pub target: String,
+}
+
+/// A guest directory in which the program may create, rename, and delete entries.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScratchDirectory {
+  /// Normalized guest path.
+  pub path: String,
+  /// Ceiling on the total logical size of the files inside.
+  pub size_limit: FileSizeLimit,
+  /// Ceiling on the number of files and subdirectories inside.
+  pub file_limit: u64,
 }
 
 /// A guest directory binding.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct DirectoryBinding {
   /// Normalized guest path.
@@ -336,7 +509,7 @@ pub struct DirectoryBinding {
 }
 
 /// A guest regular file binding.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct FileBinding {
   /// Normalized guest path.
@@ -359,6 +532,8 @@ pub enum RunStatus {
   TimeLimitExceeded,
   /// Denied linear-memory growth.
   MemoryLimitExceeded,
+  /// Stopped because a program listed in `terminate_on_exit` exited first.
+  Terminated,
   /// File or stream length exceeded its declared ceiling.
   FileError,
   /// Invalid module or host setup failure.
@@ -366,7 +541,10 @@ pub enum RunStatus {
 }
 
 /// Metadata for one program execution.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+///
+/// Equality ignores `stack`, whose approximate peak depends on host frames that vary between
+/// otherwise identical runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProgramResult {
   /// Program name.
@@ -377,12 +555,87 @@ pub struct ProgramResult {
   pub tick: u64,
   /// Peak requested linear memory.
   pub memory: u64,
+  /// Peak execution-stack bytes, including host frames below the guest.
+  ///
+  /// Approximate: the deepest frame is found from the lowest touched stack page, and zeros it
+  /// wrote are indistinguishable from untouched bytes, so this may undercount by less than a page.
+  /// Host frames that run before and after the guest can move it between identical runs.
+  #[serde(default)]
+  pub stack: u64,
   /// Normal return or `proc_exit` code.
   pub exit_code: Option<i32>,
   /// Small diagnostic text.
   pub error_message: Option<String>,
+  /// Symbolized guest frames of a trap, innermost first.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub backtrace: Vec<BacktraceFrame>,
+  /// Wasm core dump written for a runtime error of a program that requested one.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub coredump_path: Option<PathBuf>,
+  /// I/O through each session file the program references, in request order.
+  #[serde(default)]
+  pub files: Vec<FileStatistics>,
+  /// Linear-memory growths that raised the peak, oldest first and thinned to a bounded length.
+  #[serde(default)]
+  pub memory_timeline: Vec<MemoryGrowth>,
+}
+
+/// One linear-memory growth that raised a program's peak.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MemoryGrowth {
+  /// Ticks the program had consumed when it requested the growth.
+  pub tick: u64,
+  /// Requested linear-memory size in bytes.
+  pub memory: u64,
+}
+
+/// One program's I/O through one session file.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FileStatistics {
+  /// Request file name.
+  pub file: String,
+  /// Bytes delivered by successful reads.
+  pub bytes_read: u64,
+  /// Bytes accepted by successful writes.
+  pub bytes_written: u64,
+  /// Successful read hostcalls, including those that reported end of file.
+  pub reads: u64,
+  /// Successful write hostcalls.
+  pub writes: u64,
+  /// Final logical size of a regular file, or the cumulative bytes written to a pipe.
+  pub size: u64,
 }
 
+impl PartialEq for ProgramResult {
+  fn eq(&self, other: &Self) -> bool {
+    let Self {
+      program,
+      status,
+      tick,
+      memory,
+      stack: _,
+      exit_code,
+      error_message,
+      backtrace,
+      coredump_path,
+      files,
+      memory_timeline,
+    } = self;
+    *program == other.program
+      && *status == other.status
+      && *tick == other.tick
+      && *memory == other.memory
+      && *exit_code == other.exit_code
+      && *error_message == other.error_message
+      && *backtrace == other.backtrace
+      && *coredump_path == other.coredump_path
+      && *files == other.files
+      && *memory_timeline == other.memory_timeline
+  }
+}
+
+impl Eq for ProgramResult {}
+
 impl ProgramResult {
   /// Constructs a setup-failure result.
   pub fn internal_error(program: String, message: String) -> Self {
@@ -391,24 +644,105 @@ impl ProgramResult {
       status: RunStatus::InternalError,
       tick: 0,
       memory: 0,
+      stack: 0,
       exit_code: None,
       error_message: Some(message),
+      backtrace: Vec::new(),
+      coredump_path: None,
+      files: Vec::new(),
+      memory_timeline: Vec::new(),
+    }
+  }
+
+  /// Returns the diagnostic text followed by the numbered guest backtrace, if any.
+  pub fn diagnostic(&self) -> String {
+    let mut diagnostic = self.error_message.clone().unwrap_or_default();
+    if !self.backtrace.is_empty() {
+      diagnostic.push_str("\nBacktrace:");
+      for (index, frame) in self.backtrace.iter().enumerate() {
+        diagnostic.push_str(&format!("\n{index:>4}: {frame}"));
+      }
+    }
+    diagnostic
+  }
+}
+
+/// One symbolized guest stack frame.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BacktraceFrame {
+  /// Demangled function name, or `wasm-function[index]` without symbols.
+  pub function: String,
+  /// DWARF source file.
+  pub file: Option<String>,
+  /// DWARF source line.
+  pub line: Option<u32>,
+  /// DWARF source column.
+  pub column: Option<u32>,
+}
+
+impl fmt::Display for BacktraceFrame {
+  fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (&self.file, self.line) {
+      (Some(file), Some(line)) => write!(formatter, "{file}:{line} {}", self.function),
+      (Some(file), None) => write!(formatter, "{file} {}", self.function),
+      (None, _) => formatter.write_str(&self.function),
     }
   }
 }
 
 /// A detected minimal protocol deadlock component.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Deadlock {
   /// Programs in request order.
   pub programs: Vec<String>,
   /// Pipes in request order.
   pub pipes: Vec<String>,
+  /// Hostcall each program was blocked in, in `programs` order.
+  #[serde(default)]
+  pub blocked: Vec<BlockedCall>,
+  /// Unread bytes left in each pipe, in `pipes` order.
+  #[serde(default)]
+  pub buffered: Vec<PipeBuffer>,
+}
+
+/// The hostcall a deadlocked program was blocked in.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockedCall {
+  /// Program name.
+  pub program: String,
+  /// `fd_read`, `fd_write`, or `poll_oneoff`.
+  pub hostcall: String,
+  /// Descriptors the call waited on; clock subscriptions are omitted.
+  pub descriptors: Vec<BlockedDescriptor>,
+  /// Ticks the program had consumed when it blocked.
+  pub tick: u64,
+}
+
+/// One descriptor a blocked hostcall waited on.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockedDescriptor {
+  /// Guest descriptor number.
+  pub fd: u32,
+  /// Session file behind the descriptor, if any.
+  pub file: Option<String>,
+}
+
+/// Unread bytes left in a deadlocked pipe.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipeBuffer {
+  /// Pipe name.
+  pub pipe: String,
+  /// Bytes written but not yet read.
+  pub bytes: u64,
 }
 
 /// Complete small session metadata report.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct SessionReport {
   /// One result per program in request order.
@@ -434,12 +768,20 @@ impl SessionReport {
 impl SessionRequest {
   /// Resolves all relative host paths against the request file's parent.
   pub fn resolve_paths(&mut self, request_path: &Path) {
-    let parent = request_path.parent().unwrap_or_else(|| Path::new("."));
+    self.resolve_paths_from(request_path.parent().unwrap_or_else(|| Path::new(".")));
+  }
+
+  /// Resolves all relative host paths against a base directory.
+  pub fn resolve_paths_from(&mut self, parent: &Path) {
     resolve_path(&mut self.report_path, parent);
     for file in &mut self.files {
       if let File::Regular {
         host_path: Some(path),
         ..
+      }
+      | File::Pipe {
+        transcript_path: Some(path),
+        ..
       } = file
       {
         resolve_path(path, parent);
@@ -450,6 +792,13 @@ impl SessionRequest {
     }
   }
 
+  /// Returns where a runtime error of `program` writes its core dump, beside the report.
+  pub fn coredump_path(&self, program: &ProgramRequest) -> PathBuf {
+    let mut file_name = self.report_path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!(".{}.coredump", program.name));
+    self.report_path.with_file_name(file_name)
+  }
+
   /// Validates names, references, capabilities, paths, ownership, and endpoints.
   pub fn validate(&self) -> Result<()> {
     if self.programs.is_empty() {
@@ -460,6 +809,15 @@ impl SessionRequest {
       self.programs.iter().map(|program| program.name.as_str()),
       "program",
     )?;
+    unique(
+      self.terminate_on_exit.iter().map(String::as_str),
+      "terminate_on_exit program",
+    )?;
+    for name in &self.terminate_on_exit {
+      if !self.programs.iter().any(|program| program.name == *name) {
+        bail!("terminate_on_exit names unknown program `{name}`");
+      }
+    }
     validate_host_paths(self)?;
     let files = self
       .files
@@ -496,6 +854,9 @@ impl SessionRequest {
     for program in &self.programs {
       usize::try_from(program.memory_limit)
         .map_err(|_| anyhow!("memory limit does not fit the host address width"))?;
+      usize::try_from(program.stack_limit())
+        .map_err(|_| anyhow!("stack limit does not fit the host address width"))?;
+      validate_environment(&program.environment)?;
       validate_initial_descriptors(program, &files)?;
       validate_file_system(&program.file_system, &files)?;
     }
@@ -505,12 +866,54 @@ impl SessionRequest {
   }
 }
 
+/// Rejects environment entries that cannot be encoded as `NAME=VALUE` C strings.
+pub fn validate_environment(environment: &BTreeMap<String, String>) -> Result<()> {
+  for (name, value) in environment {
+    if name.is_empty() || name.contains(['=', '\0']) {
+      bail!(
+        "invalid environment variable name `{}`",
+        name.escape_debug()
+      );
+    }
+    if value.contains('\0') {
+      bail!("environment variable `{name}` contains NUL");
+    }
+  }
+  Ok(())
+}
+
 fn validate_host_paths(request: &SessionRequest) -> Result<()> {
-  let report = normalize_host_path(&request.report_path)?;
+  let mut outputs = vec![normalize_host_path(&request.report_path)?];
+  for program in request.programs.iter().filter(|program| program.coredump) {
+    if program.name.contains(['/', '\0']) {
+      bail!(
+        "program `{}` requests a core dump but its name is not a file name component",
+        program.name
+      );
+    }
+    outputs.push(normalize_host_path(&request.coredump_path(program))?);
+  }
+  for file in &request.files {
+    let File::Pipe {
+      transcript_path: Some(path),
+      ..
+    } = file
+    else {
+      continue;
+    };
+    let path = normalize_host_path(path)?;
+    if outputs.iter().any(|output| paths_overlap(&path, output)) {
+      bail!(
+        "transcript host path `{}` conflicts with another session path",
+        path.display()
+      );
+    }
+    outputs.push(path);
+  }
   let mut wasm_paths = Vec::<PathBuf>::new();
   for program in &request.programs {
     let path = normalize_host_path(&program.wasm_path)?;
-    if paths_overlap(&path, &report)
+    if outputs.iter().any(|output| paths_overlap(&path, output))
       || wasm_paths
         .iter()
         .any(|other| path != *other && paths_overlap(&path, other))
@@ -537,7 +940,7 @@ fn validate_host_paths(request: &SessionRequest) -> Result<()> {
       continue;
     };
     let path = normalize_host_path(path)?;
-    if paths_overlap(&path, &report)
+    if outputs.iter().any(|output| paths_overlap(&path, output))
       || wasm_paths.iter().any(|other| paths_overlap(&path, other))
       || mapped
         .iter()
@@ -676,12 +1079,39 @@ fn validate_file_system(file_system: &FileSystem, files: &HashMap<&str, &File>)
       None => bail!("unknown file `{}`", binding.file),
     }
   }
+  for symlink in &file_system.symlinks {
+    normalized(&symlink.path)?;
+    if !paths.insert(symlink.path.as_str()) {
+      bail!("duplicate file system path `{}`", symlink.path);
+    }
+    if symlink.target.is_empty() || symlink.target.starts_with('/') || symlink.target.contains('\0')
+    {
+      bail!(
+        "symlink `{}` must have a nonempty relative target",
+        symlink.path
+      );
+    }
+  }
+  for scratch in &file_system.scratch {
+    normalized(&scratch.path)?;
+    if !paths.insert(scratch.path.as_str()) {
+      bail!("duplicate file system path `{}`", scratch.path);
+    }
+    scratch.size_limit.resolve()?;
+  }
+  // Scratch directories are not in `directories`, so nothing may be declared inside one.
   for directory in &file_system.directories {
     validate_declared_parent(&directory.path, &directories)?;
   }
   for binding in &file_system.bindings {
     validate_declared_parent(&binding.path, &directories)?;
   }
+  for symlink in &file_system.symlinks {
+    validate_declared_parent(&symlink.path, &directories)?;
+  }
+  for scratch in &file_system.scratch {
+    validate_declared_parent(&scratch.path, &directories)?;
+  }
   Ok(())
 }
 
@@ -788,15 +1218,25 @@ mod tests {
       name: name.into(),
       wasm_path: format!("{name}.wasm").into(),
       arguments: Vec::new(),
+      environment: BTreeMap::new(),
       tick_limit: 1,
       memory_limit: 1,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".into(),
           permissions: DirectoryPermissions::ReadExecute,
         }],
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: null_descriptors(),
     }
@@ -807,6 +1247,7 @@ mod tests {
       report_path: "report.json".into(),
       files: Vec::new(),
       programs: vec![program("main")],
+      terminate_on_exit: Vec::new(),
     }
   }
 
@@ -855,6 +1296,23 @@ mod tests {
     }
   }
 
+  #[test]
+  fn environment_names_must_be_encodable() {
+    for (name, value, message) in [
+      ("", "1", "invalid environment variable name ``"),
+      ("A=B", "1", "invalid environment variable name `A=B`"),
+      ("A\0B", "1", "invalid environment variable name `A\\0B`"),
+      ("A", "x\0y", "environment variable `A` contains NUL"),
+    ] {
+      let mut request = valid_request();
+      request.programs[0].environment = BTreeMap::from([(name.into(), value.into())]);
+      validation_error(&request, message);
+    }
+    let mut request = valid_request();
+    request.programs[0].environment = BTreeMap::from([("A".into(), "x=y".into())]);
+    request.validate().unwrap();
+  }
+
   #[test]
   fn strict_schema_deserializes() {
     let json = request(
@@ -867,6 +1325,30 @@ mod tests {
       .unwrap();
   }
 
+  #[test]
+  fn clock_serde_and_readings() {
+    assert_eq!(
+      serde_json::from_str::<Clock>(r#"{"kind":"frozen"}"#).unwrap(),
+      Clock::Frozen
+    );
+    let clock =
+      serde_json::from_str::<Clock>(r#"{"kind":"ticks","ticks_per_second":3000000000}"#).unwrap();
+    assert_eq!(clock.nanoseconds(3), 1);
+    assert_eq!(clock.nanoseconds(3_000_000_000), 1_000_000_000);
+    assert_eq!(clock.resolution(), 1);
+    let coarse = serde_json::from_str::<Clock>(r#"{"kind":"ticks","ticks_per_second":3}"#).unwrap();
+    assert_eq!(coarse.nanoseconds(u64::MAX), u64::MAX);
+    assert_eq!(coarse.resolution(), 333_333_334);
+    assert_eq!(Clock::Frozen.nanoseconds(u64::MAX), 0);
+    for json in [
+      r#"{"kind":"ticks","ticks_per_second":0}"#,
+      r#"{"kind":"ticks"}"#,
+      r#"{"kind":"wall"}"#,
+    ] {
+      assert!(serde_json::from_str::<Clock>(json).is_err(), "{json}");
+    }
+  }
+
   #[test]
   fn descriptors_require_three_entries_and_known_capabilities() {
     let mut request = valid_request();
@@ -981,6 +1463,79 @@ mod tests {
     validation_error(&request, "pipe cannot be a guest-path binding");
   }
 
+  #[test]
+  fn scratch_directories_take_unique_paths_without_declared_children() {
+    let mut request = valid_request();
+    request.files.push(File::regular(
+      "data",
+      None,
+      FilePermissions::Read,
+      FileSizeLimit::Bytes(1),
+    ));
+    let file_system = &mut request.programs[0].file_system;
+    file_system.scratch.push(ScratchDirectory {
+      path: "tmp".into(),
+      size_limit: FileSizeLimit::Bytes(1),
+      file_limit: 1,
+    });
+    request.validate().unwrap();
+
+    request.programs[0].file_system.bindings.push(FileBinding {
+      path: "tmp/data".into(),
+      file: "data".into(),
+      permissions: FilePermissions::Read,
+    });
+    validation_error(&request, "undeclared parent directory `tmp`");
+
+    request.programs[0].file_system.bindings[0].path = "tmp".into();
+    validation_error(&request, "duplicate file system path `tmp`");
+  }
+
+  #[test]
+  fn terminate_on_exit_names_unique_programs() {
+    let mut request = valid_request();
+    request.terminate_on_exit.push("main".into());
+    request.validate().unwrap();
+
+    request.terminate_on_exit.push("main".into());
+    validation_error(
+      &request,
+      "invalid or duplicate terminate_on_exit program name `main`",
+    );
+
+    request.terminate_on_exit = vec!["missing".into()];
+    validation_error(
+      &request,
+      "terminate_on_exit names unknown program `missing`",
+    );
+  }
+
+  #[test]
+  fn symlinks_take_unique_paths_with_relative_targets() {
+    let mut request = valid_request();
+    request.programs[0]
+      .file_system
+      .symlinks
+      .push(SymlinkBinding {
+        path: "data".into(),
+        target: "../shared/data".into(),
+      });
+    request.validate().unwrap();
+
+    request.programs[0].file_system.symlinks[0].target = "/shared/data".into();
+    validation_error(
+      &request,
+      "symlink `data` must have a nonempty relative target",
+    );
+
+    request.programs[0].file_system.symlinks[0].target = "shared".into();
+    request.programs[0].file_system.symlinks[0].path = "task/data".into();
+    validation_error(&request, "undeclared parent directory `task`");
+
+    request.programs[0].file_system.symlinks[0].path = ".".into();
+    validation_error(&request, "duplicate file system path `.`");
+  }
+
   #[test]
   fn file_allows_aliases_within_one_writer_program() {
     let mut request = valid_request();
@@ -1090,4 +1645,40 @@ mod tests {
       File::Regular { host_path: Some(path), .. } if path == Path::new("work/input")
     ));
   }
+
+  #[test]
+  fn results_without_optional_fields_deserialize() {
+    let result: ProgramResult = serde_json::from_str(
+      r#"{"program":"main","status":"accepted","tick":1,"memory":2,"exit_code":0,"error_message":null}"#,
+    )
+    .unwrap();
+    assert_eq!(result.stack, 0);
+    assert!(result.backtrace.is_empty());
+    let json = serde_json::to_string(&result).unwrap();
+    assert!(!json.contains("backtrace"), "{json}");
+  }
+
+  #[test]
+  fn diagnostic_lists_backtrace_frames() {
+    let mut result = ProgramResult::internal_error("main".into(), "wasm trap: unreachable".into());
+    assert_eq!(result.diagnostic(), "wasm trap: unreachable");
+    result.backtrace = vec![
+      BacktraceFrame {
+        function: "solve(int)".into(),
+        file: Some("main.cpp".into()),
+        line: Some(12),
+        column: Some(5),
+      },
+      BacktraceFrame {
+        function: "main".into(),
+        file: None,
+        line: None,
+        column: None,
+      },
+    ];
+    assert_eq!(
+      result.diagnostic(),
+      "wasm trap: unreachable\nBacktrace:\n   0: main.cpp:12 solve(int)\n   1: main"
+    );
+  }
 }
@@ -36,10 +36,18 @@ pub struct Schedule<T> {
   pub completed: Vec<Option<T>>,
   /// Request indices whose futures stopped making observable progress.
   pub waiting: Vec<usize>,
+  /// Request indices whose futures were dropped because a controlling future completed.
+  pub terminated: Vec<usize>,
 }
 
 /// Polls every runnable future once per round in stable request order.
-pub fn round_robin<T>(futures: Vec<Option<LocalBoxFuture<'static, T>>>) -> Schedule<T> {
+///
+/// Once a future marked in `controlling` completes, every future still pending is dropped without
+/// another poll.
+pub fn round_robin<T>(
+  futures: Vec<Option<LocalBoxFuture<'static, T>>>,
+  controlling: &[bool],
+) -> Schedule<T> {
   let mut tasks = futures
     .into_iter()
     .map(|future| {
@@ -54,6 +62,7 @@ pub fn round_robin<T>(futures: Vec<Option<LocalBoxFuture<'static, T>>>) -> Sched
   loop {
     let mut pending = 0;
     let mut runnable = false;
+    let mut terminating = false;
     for (index, slot) in tasks.iter_mut().enumerate() {
       let Some(task) = slot else { continue };
       pending += 1;
@@ -66,28 +75,46 @@ pub fn round_robin<T>(futures: Vec<Option<LocalBoxFuture<'static, T>>>) -> Sched
       if let Poll::Ready(value) = Pin::new(&mut task.future).poll(&mut context) {
         completed[index] = Some(value);
         *slot = None;
+        if controlling.get(index).copied().unwrap_or(false) {
+          terminating = true;
+          break;
+        }
       }
     }
 
+    if terminating {
+      return Schedule {
+        completed,
+        waiting: Vec::new(),
+        terminated: pending_indices(&tasks),
+      };
+    }
+
     if pending == 0 {
       return Schedule {
         completed,
         waiting: Vec::new(),
+        terminated: Vec::new(),
       };
     }
     if !runnable {
       return Schedule {
         completed,
-        waiting: tasks
-          .iter()
-          .enumerate()
-          .filter_map(|(index, task)| task.is_some().then_some(index))
-          .collect(),
+        waiting: pending_indices(&tasks),
+        terminated: Vec::new(),
       };
     }
   }
 }
 
+fn pending_indices<T>(tasks: &[Option<Task<T>>]) -> Vec<usize> {
+  tasks
+    .iter()
+    .enumerate()
+    .filter_map(|(index, task)| task.is_some().then_some(index))
+    .collect()
+}
+
 /// Builds stable connected wait components from pending programs and their pipe endpoints.
 pub fn deadlocks(request: &SessionRequest, waiting: &[usize]) -> Vec<Deadlock> {
   let waiting = waiting.iter().copied().collect::<BTreeSet<_>>();
@@ -137,6 +164,8 @@ pub fn deadlocks(request: &SessionRequest, waiting: &[usize]) -> Vec<Deadlock> {
         .filter(|file| component_pipes.contains(file.name()))
         .map(|file| file.name().to_owned())
         .collect(),
+      blocked: Vec::new(),
+      buffered: Vec::new(),
     });
   }
   components
@@ -159,7 +188,7 @@ fn endpoints(program: &ProgramRequest) -> impl Iterator<Item = &str> {
 mod tests {
   use super::super::request::{FilePermissions, InitialDescriptor};
   use super::*;
-  use crate::runner::{FileSizeLimit, FileSystem};
+  use crate::runner::{Clock, FileSizeLimit, FileSystem, IoCost};
   use futures::{FutureExt, future};
 
   fn program(name: &str, endpoints: &[(&str, FilePermissions)]) -> ProgramRequest {
@@ -181,12 +210,22 @@ mod tests {
       name: name.into(),
       wasm_path: "program.wasm".into(),
       arguments: Vec::new(),
+      environment: BTreeMap::new(),
       tick_limit: 1,
       memory_limit: 1,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: Vec::new(),
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors,
     }
@@ -194,20 +233,38 @@ mod tests {
 
   #[test]
   fn request_order_is_stable() {
-    let schedule = round_robin(vec![
-      Some(future::ready(1).boxed_local()),
-      Some(future::ready(2).boxed_local()),
-    ]);
+    let schedule = round_robin(
+      vec![
+        Some(future::ready(1).boxed_local()),
+        Some(future::ready(2).boxed_local()),
+      ],
+      &[],
+    );
     assert_eq!(schedule.completed, vec![Some(1), Some(2)]);
     assert!(schedule.waiting.is_empty());
   }
 
   #[test]
   fn unwoken_future_is_waiting() {
-    let schedule = round_robin(vec![Some(future::pending::<()>().boxed_local())]);
+    let schedule = round_robin(vec![Some(future::pending::<()>().boxed_local())], &[]);
     assert_eq!(schedule.waiting, vec![0]);
   }
 
+  #[test]
+  fn controlling_completion_terminates_pending_futures() {
+    let schedule = round_robin(
+      vec![
+        Some(future::pending().boxed_local()),
+        Some(future::ready(2).boxed_local()),
+        Some(future::pending().boxed_local()),
+      ],
+      &[false, true, false],
+    );
+    assert_eq!(schedule.completed, vec![None, Some(2), None]);
+    assert!(schedule.waiting.is_empty());
+    assert_eq!(schedule.terminated, vec![0, 2]);
+  }
+
   #[test]
   fn deadlock_components_follow_request_order() {
     let request = SessionRequest {
@@ -233,6 +290,7 @@ mod tests {
         ),
         program("independent", &[]),
       ],
+      terminate_on_exit: Vec::new(),
     };
 
     assert_eq!(
@@ -241,10 +299,14 @@ mod tests {
         Deadlock {
           programs: vec!["first".into(), "second".into()],
           pipes: vec!["right".into(), "left".into()],
+          blocked: Vec::new(),
+          buffered: Vec::new(),
         },
         Deadlock {
           programs: vec!["independent".into()],
           pipes: Vec::new(),
+          blocked: Vec::new(),
+          buffered: Vec::new(),
         },
       ]
     );
@@ -256,6 +318,7 @@ mod tests {
       report_path: "report.json".into(),
       files: vec![File::pipe("pipe", 1, FileSizeLimit::Bytes(1))],
       programs: vec![program("waiting", &[("pipe", FilePermissions::None)])],
+      terminate_on_exit: Vec::new(),
     };
 
     assert_eq!(
@@ -263,6 +326,8 @@ mod tests {
       vec![Deadlock {
         programs: vec!["waiting".into()],
         pipes: Vec::new(),
+        blocked: Vec::new(),
+        buffered: Vec::new(),
       }]
     );
   }
@@ -0,0 +1,254 @@
+use std::{
+  io::{BufRead, BufReader, Write},
+  os::unix::net::{UnixListener, UnixStream},
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex, PoisonError, mpsc},
+  thread,
+};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{SessionReport, SessionRequest, run_session};
+
+/// Environment variable naming a session server socket that `hull run-wasm` forwards to.
+pub const SESSION_SOCKET_ENVIRONMENT: &str = "HULL_RUN_WASM_SOCKET";
+
+/// One line written in response to one served request line.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ServeResponse {
+  /// Report of the executed session.
+  Report(SessionReport),
+  /// Why the line could not be executed as a session.
+  Error {
+    /// Parse or transport diagnostic.
+    error: String,
+  },
+}
+
+/// Executes newline-delimited session requests until `reader` ends.
+///
+/// Relative request paths resolve against `base`. Each report is written as one line to `writer`
+/// instead of to the request's `report_path`, and blank lines are ignored.
+pub fn serve_stream(reader: impl BufRead, mut writer: impl Write, base: &Path) -> Result<()> {
+  for line in reader.lines() {
+    let line = line.context("failed to read a session request line")?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    let response = match serde_json::from_str::<SessionRequest>(&line) {
+      Ok(mut request) => {
+        request.resolve_paths_from(base);
+        ServeResponse::Report(run_session(request))
+      }
+      Err(error) => ServeResponse::Error {
+        error: format!("failed to parse session request: {error}"),
+      },
+    };
+    serde_json::to_writer(&mut writer, &response)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+  }
+  Ok(())
+}
+
+/// Serves connections to `listener` on a pool of `workers` threads until accepting fails.
+///
+/// At most `workers` connections are served at once. Later connections wait unaccepted in the
+/// listen backlog, so a burst of clients cannot start more sessions than the pool allows.
+pub fn serve_socket(listener: UnixListener, base: PathBuf, workers: usize) -> Result<()> {
+  // A rendezvous channel only hands a connection over once a worker is free to serve it.
+  let (sender, receiver) = mpsc::sync_channel::<UnixStream>(0);
+  let receiver = Arc::new(Mutex::new(receiver));
+  let pool = (0..workers.max(1))
+    .map(|_| {
+      let receiver = Arc::clone(&receiver);
+      let base = base.clone();
+      thread::spawn(move || {
+        let next = || {
+          receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv()
+        };
+        while let Ok(stream) = next() {
+          let result = stream
+            .try_clone()
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| serve_stream(BufReader::new(reader), &stream, &base));
+          if let Err(error) = result {
+            warn!("Session connection failed: {error:#}");
+          }
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+  let accepted = loop {
+    match listener.accept() {
+      Ok((stream, _)) => {
+        if sender.send(stream).is_err() {
+          break Ok(());
+        }
+      }
+      Err(error) => break Err(error).context("failed to accept a session connection"),
+    }
+  };
+  drop(sender);
+  for worker in pool {
+    let _ = worker.join();
+  }
+  accepted
+}
+
+/// Executes one request on the session server listening at `socket`.
+///
+/// Relative request paths resolve against the server's base directory, so callers should resolve
+/// them first.
+pub fn forward(socket: &Path, request: &SessionRequest) -> Result<SessionReport> {
+  let mut stream = UnixStream::connect(socket)
+    .with_context(|| format!("failed to connect to session server {}", socket.display()))?;
+  serde_json::to_writer(&mut stream, request)?;
+  stream.write_all(b"\n")?;
+  stream.shutdown(std::net::Shutdown::Write)?;
+  let mut line = String::new();
+  BufReader::new(&stream).read_line(&mut line)?;
+  match serde_json::from_str(&line)
+    .with_context(|| format!("invalid response from session server {}", socket.display()))?
+  {
+    ServeResponse::Report(report) => Ok(report),
+    ServeResponse::Error { error } => Err(anyhow!(error)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::runner::{
+    Clock, DirectoryBinding, DirectoryPermissions, FilePermissions, FileSystem, InitialDescriptor,
+    IoCost, ProgramRequest, RunStatus,
+  };
+  use std::collections::BTreeMap;
+
+  fn request(wasm_path: &str) -> SessionRequest {
+    SessionRequest {
+      report_path: "report.json".into(),
+      files: Vec::new(),
+      programs: vec![ProgramRequest {
+        name: "main".into(),
+        wasm_path: wasm_path.into(),
+        arguments: Vec::new(),
+        environment: BTreeMap::new(),
+        tick_limit: 1_000_000,
+        memory_limit: 1 << 20,
+        stack_limit: None,
+        random_seed: 0,
+        clock: Clock::Frozen,
+        io_cost: IoCost::default(),
+        required_accepted: false,
+        coredump: false,
+        backtrace: false,
+        features: Vec::new(),
+        file_system: FileSystem {
+          directories: vec![DirectoryBinding {
+            path: ".".into(),
+            permissions: DirectoryPermissions::ReadExecute,
+          }],
+          bindings: Vec::new(),
+          symlinks: Vec::new(),
+          scratch: Vec::new(),
+        },
+        initial_descriptors: [
+          FilePermissions::Read,
+          FilePermissions::Write,
+          FilePermissions::Write,
+        ]
+        .into_iter()
+        .map(|permissions| InitialDescriptor {
+          file: None,
+          permissions,
+        })
+        .collect(),
+      }],
+      terminate_on_exit: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn stream_answers_each_line_in_order() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(
+      directory.path().join("main.wat"),
+      r#"(module (func (export "_start")))"#,
+    )
+    .unwrap();
+    let input = format!(
+      "{}\n\nnot json\n",
+      serde_json::to_string(&request("main.wat")).unwrap()
+    );
+    let mut output = Vec::new();
+    serve_stream(input.as_bytes(), &mut output, directory.path()).unwrap();
+
+    let lines = String::from_utf8(output).unwrap();
+    let responses = lines
+      .lines()
+      .map(|line| serde_json::from_str::<ServeResponse>(line).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(responses.len(), 2);
+    assert!(matches!(
+      &responses[0],
+      ServeResponse::Report(report) if report.results[0].status == RunStatus::Accepted
+    ));
+    assert!(matches!(
+      &responses[1],
+      ServeResponse::Error { error } if error.starts_with("failed to parse session request")
+    ));
+    assert!(!directory.path().join("report.json").exists());
+  }
+
+  #[test]
+  fn forward_runs_on_socket_server() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = directory.path().join("main.wat");
+    std::fs::write(&wasm, r#"(module (func (export "_start") unreachable))"#).unwrap();
+    let socket = directory.path().join("session.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let base = directory.path().to_path_buf();
+    thread::spawn(move || serve_socket(listener, base, 1));
+
+    let report = forward(&socket, &request(wasm.to_str().unwrap())).unwrap();
+    assert_eq!(report.results[0].status, RunStatus::RuntimeError);
+    assert_eq!(report, run_session(request(wasm.to_str().unwrap())));
+  }
+
+  #[test]
+  fn socket_server_queues_connections_beyond_its_workers() {
+    let directory = tempfile::tempdir().unwrap();
+    let wasm = directory.path().join("main.wat");
+    std::fs::write(&wasm, r#"(module (func (export "_start")))"#).unwrap();
+    let socket = directory.path().join("session.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let base = directory.path().to_path_buf();
+    thread::spawn(move || serve_socket(listener, base, 1));
+
+    // The first client holds the only worker until it closes its connection.
+    let idle = UnixStream::connect(&socket).unwrap();
+    let clients = (0..4)
+      .map(|_| {
+        let socket = socket.clone();
+        let request = request(wasm.to_str().unwrap());
+        thread::spawn(move || forward(&socket, &request).unwrap())
+      })
+      .collect::<Vec<_>>();
+    thread::sleep(std::time::Duration::from_millis(100));
+    assert!(clients.iter().all(|client| !client.is_finished()));
+    drop(idle);
+    for client in clients {
+      assert_eq!(
+        client.join().unwrap().results[0].status,
+        RunStatus::Accepted
+      );
+    }
+  }
+}
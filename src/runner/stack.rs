@@ -0,0 +1,209 @@
+use std::{cell::Cell, ops::Range, ptr};
+
+use wasmtime::{StackCreator, StackMemory};
+
+thread_local! {
+  static RELEASED: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Pages whose residency one `mincore` call reports while searching for the deepest frame.
+const RESIDENCY_CHUNK: usize = 4096;
+
+/// Allocates guest fiber stacks from fresh mappings that report their deepest use on release.
+///
+/// A store keeps its last fiber stack until it is dropped, so stacks of one engine can be shared
+/// by concurrent stores while each store's usage is read on the thread that drops it.
+#[derive(Debug, Default)]
+pub struct MeasuredStacks;
+
+unsafe impl StackCreator for MeasuredStacks {
+  fn new_stack(&self, size: usize, _zeroed: bool) -> wasmtime::Result<Box<dyn StackMemory>> {
+    Ok(Box::new(MeasuredStack::new(size)?))
+  }
+}
+
+/// Runs `release` and returns the deepest use in bytes of any stack it released on this thread.
+pub fn measure_released<T>(release: impl FnOnce() -> T) -> (T, u64) {
+  RELEASED.with(|peak| peak.set(0));
+  let value = release();
+  (value, RELEASED.with(Cell::take))
+}
+
+struct MeasuredStack {
+  mapping: *mut u8,
+  page: usize,
+  len: usize,
+}
+
+// The mapping is owned exclusively by this value and only touched by the fiber running on it.
+unsafe impl Send for MeasuredStack {}
+unsafe impl Sync for MeasuredStack {}
+
+impl MeasuredStack {
+  fn new(size: usize) -> wasmtime::Result<Self> {
+    let page = page_size();
+    let len = size
+      .checked_next_multiple_of(page)
+      .filter(|len| *len > 0)
+      .ok_or_else(|| wasmtime::format_err!("invalid fiber stack size {size}"))?;
+    let total = len
+      .checked_add(page)
+      .ok_or_else(|| wasmtime::format_err!("invalid fiber stack size {size}"))?;
+    let mapping = unsafe {
+      libc::mmap(
+        ptr::null_mut(),
+        total,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANON,
+        -1,
+        0,
+      )
+    };
+    if mapping == libc::MAP_FAILED {
+      return Err(wasmtime::format_err!(
+        "failed to map a {total}-byte fiber stack: {}",
+        std::io::Error::last_os_error()
+      ));
+    }
+    let stack = Self {
+      mapping: mapping.cast(),
+      page,
+      len,
+    };
+    // A transparent huge page would make a single touch resident for up to 2 MiB below it.
+    // Failure only costs precision, for example on kernels built without THP.
+    unsafe {
+      libc::madvise(mapping, total, libc::MADV_NOHUGEPAGE);
+    }
+    if unsafe { libc::mprotect(mapping, page, libc::PROT_NONE) } != 0 {
+      return Err(wasmtime::format_err!(
+        "failed to protect the fiber stack guard page: {}",
+        std::io::Error::last_os_error()
+      ));
+    }
+    Ok(stack)
+  }
+
+  fn usable(&self) -> *mut u8 {
+    unsafe { self.mapping.add(self.page) }
+  }
+
+  /// Returns the distance from the top to the lowest written byte, undercounting by less than a
+  /// page when the deepest frame wrote only zeros there.
+  fn used(&self) -> usize {
+    // Fresh anonymous pages stay nonresident until touched, so the lowest resident page holds the
+    // deepest frame. Zeros written by that frame look like untouched bytes, so its first nonzero
+    // byte is only a bound, and a page holding no nonzero byte still counts as reached.
+    // Residency is read in bounded chunks from the bottom, so a deep stack stops the search early
+    // and a huge tool stack never needs one flag byte per page at once.
+    let pages = self.len / self.page;
+    let mut resident = [0_u8; RESIDENCY_CHUNK];
+    let mut index = None;
+    for start in (0..pages).step_by(RESIDENCY_CHUNK) {
+      let count = RESIDENCY_CHUNK.min(pages - start);
+      let address = unsafe { self.usable().add(start * self.page) };
+      if unsafe {
+        libc::mincore(
+          address.cast(),
+          count * self.page,
+          resident.as_mut_ptr().cast(),
+        )
+      } != 0
+      {
+        return 0;
+      }
+      if let Some(position) = resident[..count].iter().position(|flags| flags & 1 != 0) {
+        index = Some(start + position);
+        break;
+      }
+    }
+    let Some(index) = index else {
+      return 0;
+    };
+    let offset = index * self.page;
+    let bytes = unsafe { std::slice::from_raw_parts(self.usable().add(offset), self.page) };
+    let position = bytes
+      .iter()
+      .position(|byte| *byte != 0)
+      .unwrap_or(self.page - 1);
+    self.len - offset - position
+  }
+}
+
+impl Drop for MeasuredStack {
+  fn drop(&mut self) {
+    let used = u64::try_from(self.used()).unwrap_or(u64::MAX);
+    RELEASED.with(|peak| peak.set(peak.get().max(used)));
+    unsafe {
+      libc::munmap(self.mapping.cast(), self.len + self.page);
+    }
+  }
+}
+
+unsafe impl StackMemory for MeasuredStack {
+  fn top(&self) -> *mut u8 {
+    unsafe { self.usable().add(self.len) }
+  }
+
+  fn range(&self) -> Range<usize> {
+    let start = self.usable() as usize;
+    start..start + self.len
+  }
+
+  fn guard_range(&self) -> Range<*mut u8> {
+    self.mapping..self.usable()
+  }
+}
+
+fn page_size() -> usize {
+  usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(4096)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn released_stack_reports_deepest_write() {
+    let stack = MeasuredStacks.new_stack(64 * 1024, true).unwrap();
+    let range = stack.range();
+    assert_eq!(range.end, stack.top() as usize);
+    assert_eq!(stack.guard_range().end as usize, range.start);
+    unsafe {
+      *stack.top().sub(5000) = 1;
+      *stack.top().sub(100) = 1;
+    }
+    let unused = MeasuredStacks.new_stack(64 * 1024, true).unwrap();
+    assert_eq!(measure_released(|| drop(stack)).1, 5000);
+    assert_eq!(measure_released(|| drop(unused)).1, 0);
+  }
+
+  #[test]
+  fn deepest_write_is_found_beyond_the_first_residency_chunk() {
+    let size = 3 * RESIDENCY_CHUNK * page_size();
+    let stack = MeasuredStacks.new_stack(size, true).unwrap();
+    unsafe {
+      *stack.top().sub(size - 10) = 1;
+      *stack.top().sub(100) = 1;
+    }
+    assert_eq!(measure_released(|| drop(stack)).1, size as u64 - 10);
+    let shallow = MeasuredStacks.new_stack(size, true).unwrap();
+    unsafe {
+      *shallow.top().sub(100) = 1;
+    }
+    assert_eq!(measure_released(|| drop(shallow)).1, 100);
+  }
+
+  #[test]
+  fn zero_filled_deep_frame_is_counted_within_a_page() {
+    let stack = MeasuredStacks.new_stack(64 * 1024, true).unwrap();
+    unsafe {
+      // A deep frame that only ever stored zeros, below a shallow frame with nonzero data.
+      ptr::write_bytes(stack.top().sub(20_000), 0, 10_000);
+      *stack.top().sub(100) = 1;
+    }
+    let used = measure_released(|| drop(stack)).1;
+    assert!(used <= 20_000, "{used}");
+    assert!(used > 20_000 - page_size() as u64, "{used}");
+  }
+}
@@ -1,23 +1,33 @@
 #![allow(clippy::too_many_arguments)]
 
 use std::{
+  borrow::Cow,
   collections::{BTreeMap, VecDeque},
-  fmt,
+  fmt::{self, Write as _},
   future::poll_fn,
-  io::{Read, Seek, SeekFrom, Write},
+  io::{BufWriter, Read, Seek, SeekFrom, Write},
   path::{Component, Path, PathBuf},
-  sync::{Arc, Mutex},
+  ptr::NonNull,
+  sync::{Arc, Mutex, Weak},
   task::{Poll, Waker},
 };
 
 use anyhow::{Context, Result, anyhow};
 use cap_std::{ambient_authority, fs::Dir};
 use rand::{SeedableRng, TryRng, rngs::Xoshiro256PlusPlus};
-use wasmtime::{Linker, ResourceLimiter};
+use serde::Serialize;
+use wasmtime::{CallHook, Linker, ResourceLimiter, Store, component::ResourceTable};
 use wiggle::{GuestMemory, GuestPtr};
 
-use super::request::{DirectoryPermissions, FilePermissions};
-use super::{File, ProgramRequest, ProgramResult, RunStatus, SessionRequest};
+use super::request::{DirectoryPermissions, FilePermissions, validate_environment};
+use super::{
+  BlockedCall, BlockedDescriptor, Clock, File, FileStatistics, IoCost, LocalProgramRequest,
+  MemoryGrowth, ProgramRequest, ProgramResult, RunStatus, SessionRequest,
+};
+
+mod preview2;
+
+pub use preview2::{add_to_linker as add_to_component_linker, run as run_component};
 
 wiggle::from_witx!({
   witx: ["src/runner/wasi/witx/wasi_snapshot_preview1.witx"],
@@ -47,12 +57,17 @@ type WasiResult<T> = std::result::Result<T, types::Error>;
 
 const COPY_BUFFER_SIZE: usize = 64 * 1024;
 const FILE_PAGE_SIZE: usize = 64 * 1024;
+/// Symlinks followed while resolving one path before it fails with `ELOOP`, as on Linux.
+const MAX_SYMLINK_HOPS: usize = 40;
 
 fn errno(errno: types::Errno) -> types::Error {
   errno.into()
 }
 
-/// Tracks the linear-memory ceiling and peak growth request.
+/// Most growths kept in a memory timeline before it is thinned.
+const MEMORY_TIMELINE_LENGTH: usize = 64;
+
+/// Tracks the linear-memory ceiling, peak growth request, and the growths that raised the peak.
 #[derive(Clone, Debug)]
 pub struct MemoryLimiter {
   limit: usize,
@@ -60,18 +75,50 @@ pub struct MemoryLimiter {
   pub peak: usize,
   /// Whether a growth request crossed the ceiling.
   pub exceeded: bool,
+  /// Growths that raised `peak`, oldest first.
+  pub timeline: Vec<MemoryGrowth>,
 }
 
-impl ResourceLimiter for MemoryLimiter {
+impl MemoryLimiter {
+  fn new(limit: usize) -> Self {
+    Self {
+      limit,
+      peak: 0,
+      exceeded: false,
+      timeline: Vec::new(),
+    }
+  }
+
+  fn grow(&mut self, tick: u64, desired: usize) -> bool {
+    if desired > self.peak {
+      if self.timeline.len() == MEMORY_TIMELINE_LENGTH {
+        // Dropping every second growth keeps the first one and the overall shape, and the
+        // growth pushed below keeps the last entry equal to the peak.
+        let mut index = 0;
+        self.timeline.retain(|_| {
+          index += 1;
+          index % 2 == 1
+        });
+      }
+      self.timeline.push(MemoryGrowth {
+        tick,
+        memory: u64::try_from(desired).unwrap_or(u64::MAX),
+      });
+      self.peak = desired;
+    }
+    self.exceeded |= desired > self.limit;
+    !self.exceeded
+  }
+}
+
+impl ResourceLimiter for State {
   fn memory_growing(
     &mut self,
     _current: usize,
     desired: usize,
     _maximum: Option<usize>,
   ) -> wasmtime::Result<bool> {
-    self.peak = self.peak.max(desired);
-    self.exceeded |= desired > self.limit;
-    Ok(!self.exceeded)
+    Ok(self.memory.grow(self.ticks(), desired))
   }
 
   fn table_growing(
@@ -90,6 +137,56 @@ enum SharedFile {
   Pipe(Pipe),
 }
 
+impl SharedFile {
+  fn io(&mut self, program: &str) -> &mut IoCounters {
+    let io = match self {
+      Self::Regular(file) => &mut file.io,
+      Self::Pipe(pipe) => &mut pipe.io,
+    };
+    if !io.contains_key(program) {
+      io.insert(program.to_owned(), IoCounters::default());
+    }
+    io.get_mut(program).unwrap()
+  }
+
+  fn record_read(&mut self, program: &str, count: u32) {
+    let io = self.io(program);
+    io.reads += 1;
+    io.bytes_read += u64::from(count);
+  }
+
+  fn record_write(&mut self, program: &str, count: u32) {
+    let io = self.io(program);
+    io.writes += 1;
+    io.bytes_written += u64::from(count);
+  }
+
+  fn statistics(&self, file: &str, program: &str) -> FileStatistics {
+    let (io, size) = match self {
+      Self::Regular(regular) => (&regular.io, regular.length),
+      Self::Pipe(pipe) => (&pipe.io, pipe.stream_length),
+    };
+    let io = io.get(program).copied().unwrap_or_default();
+    FileStatistics {
+      file: file.to_owned(),
+      bytes_read: io.bytes_read,
+      bytes_written: io.bytes_written,
+      reads: io.reads,
+      writes: io.writes,
+      size,
+    }
+  }
+}
+
+/// One program's successful reads and writes through one shared file.
+#[derive(Clone, Copy, Debug, Default)]
+struct IoCounters {
+  bytes_read: u64,
+  bytes_written: u64,
+  reads: u64,
+  writes: u64,
+}
+
 #[derive(Debug)]
 struct RegularFile {
   destination: Option<PathBuf>,
@@ -101,6 +198,54 @@ struct RegularFile {
   exceeded: bool,
   initial_exceeded: bool,
   pages: BTreeMap<u64, FilePage>,
+  io: BTreeMap<String, IoCounters>,
+  scratch: Option<ScratchCharge>,
+}
+
+/// Shared usage of one scratch directory against its request quota.
+#[derive(Debug)]
+struct ScratchQuota {
+  root: String,
+  size_limit: u64,
+  file_limit: u64,
+  size: u64,
+  files: u64,
+}
+
+impl ScratchQuota {
+  fn add_entry(&mut self) -> WasiResult<()> {
+    if self.files >= self.file_limit {
+      return Err(errno(types::Errno::Dquot));
+    }
+    self.files += 1;
+    Ok(())
+  }
+}
+
+/// Bytes of one scratch file charged to its quota until the file's last reference is dropped.
+#[derive(Debug)]
+struct ScratchCharge {
+  quota: Arc<Mutex<ScratchQuota>>,
+  bytes: u64,
+}
+
+impl ScratchCharge {
+  fn resize(&mut self, bytes: u64) -> WasiResult<()> {
+    let mut quota = self.quota.lock().unwrap();
+    let size = quota.size - self.bytes + bytes;
+    if bytes > self.bytes && size > quota.size_limit {
+      return Err(errno(types::Errno::Dquot));
+    }
+    quota.size = size;
+    self.bytes = bytes;
+    Ok(())
+  }
+}
+
+impl Drop for ScratchCharge {
+  fn drop(&mut self) {
+    self.quota.lock().unwrap().size -= self.bytes;
+  }
 }
 
 #[derive(Debug)]
@@ -121,6 +266,15 @@ impl RegularFile {
       exceeded: false,
       initial_exceeded: false,
       pages: BTreeMap::new(),
+      io: BTreeMap::new(),
+      scratch: None,
+    }
+  }
+
+  fn scratch(quota: Arc<Mutex<ScratchQuota>>) -> Self {
+    Self {
+      scratch: Some(ScratchCharge { quota, bytes: 0 }),
+      ..Self::empty(None, u64::MAX)
     }
   }
 
@@ -146,6 +300,8 @@ impl RegularFile {
         exceeded: true,
         initial_exceeded: true,
         pages: BTreeMap::new(),
+        io: BTreeMap::new(),
+        scratch: None,
       });
     }
     let mut backing = tempfile::tempfile()?;
@@ -166,6 +322,8 @@ impl RegularFile {
       exceeded: length > size_limit,
       initial_exceeded: length > size_limit,
       pages: BTreeMap::new(),
+      io: BTreeMap::new(),
+      scratch: None,
     })
   }
 
@@ -224,6 +382,9 @@ impl RegularFile {
       self.exceeded = true;
       return Err(errno(types::Errno::Fbig));
     }
+    if let Some(charge) = &mut self.scratch {
+      charge.resize(self.length.max(end))?;
+    }
     let mut copied = 0;
     while copied < bytes.len() {
       let position = offset + copied as u64;
@@ -256,6 +417,9 @@ impl RegularFile {
       self.exceeded = true;
       return Err(errno(types::Errno::Fbig));
     }
+    if let Some(charge) = &mut self.scratch {
+      charge.resize(size)?;
+    }
     if size < self.length {
       let first_removed = size.div_ceil(FILE_PAGE_SIZE as u64);
       self.pages.split_off(&first_removed);
@@ -323,6 +487,64 @@ struct Pipe {
   writers: usize,
   read_waiters: Vec<Waker>,
   write_waiters: Vec<Waker>,
+  transcript: Option<Transcript>,
+  io: BTreeMap<String, IoCounters>,
+}
+
+/// Pipe writes streamed to a transcript file as they happen.
+#[derive(Debug)]
+struct Transcript {
+  path: PathBuf,
+  writer: BufWriter<std::fs::File>,
+  error: Option<std::io::Error>,
+}
+
+impl Transcript {
+  fn create(path: PathBuf) -> Result<Self> {
+    let file = std::fs::File::create(&path)
+      .with_context(|| format!("failed to create pipe transcript {}", path.display()))?;
+    Ok(Self {
+      path,
+      writer: BufWriter::new(file),
+      error: None,
+    })
+  }
+
+  /// Appends one write, keeping the first I/O error for `Files::finish_transcripts`.
+  fn record(&mut self, program: &str, tick: u64, bytes: &[u8]) {
+    if self.error.is_some() {
+      return;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let hex = matches!(text, Cow::Owned(_)).then(|| {
+      bytes.iter().fold(String::new(), |mut output, byte| {
+        write!(output, "{byte:02x}").unwrap();
+        output
+      })
+    });
+    let write = TranscriptWrite {
+      program,
+      tick,
+      text,
+      hex,
+    };
+    let result = serde_json::to_writer(&mut self.writer, &write)
+      .map_err(std::io::Error::from)
+      .and_then(|()| self.writer.write_all(b"\n"));
+    self.error = result.err();
+  }
+}
+
+/// One successful pipe write, serialized as one transcript line.
+///
+/// `text` decodes the bytes lossily, and `hex` carries them exactly when they are not UTF-8.
+#[derive(Serialize)]
+struct TranscriptWrite<'a> {
+  program: &'a str,
+  tick: u64,
+  text: Cow<'a, str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  hex: Option<String>,
 }
 
 /// Shared snapshotted files for one session.
@@ -363,6 +585,7 @@ impl Files {
         File::Pipe {
           capacity,
           size_limit,
+          transcript_path,
           ..
         } => SharedFile::Pipe(Pipe {
           bytes: VecDeque::new(),
@@ -374,6 +597,11 @@ impl Files {
           writers: 0,
           read_waiters: Vec::new(),
           write_waiters: Vec::new(),
+          transcript: transcript_path
+            .clone()
+            .map(Transcript::create)
+            .transpose()?,
+          io: BTreeMap::new(),
         }),
       };
       entries.push(Arc::new(Mutex::new(entry)));
@@ -392,6 +620,73 @@ impl Files {
     Ok((index, Arc::clone(&self.entries[index])))
   }
 
+  /// Returns a program's I/O through each file it references, in request order.
+  pub fn statistics(&self, program: &ProgramRequest) -> Vec<FileStatistics> {
+    self
+      .names
+      .iter()
+      .zip(&self.entries)
+      .filter(|(name, _)| program.references_file(name))
+      .map(|(name, entry)| entry.lock().unwrap().statistics(name, &program.name))
+      .collect()
+  }
+
+  /// Names the session files behind a program's pending wait.
+  pub fn blocked_call(&self, program: &str, wait: &PendingWait) -> BlockedCall {
+    BlockedCall {
+      program: program.to_owned(),
+      hostcall: wait.hostcall.to_owned(),
+      descriptors: wait
+        .descriptors
+        .iter()
+        .map(|(fd, file)| BlockedDescriptor {
+          fd: *fd,
+          file: file.as_ref().and_then(|file| {
+            self
+              .entries
+              .iter()
+              .position(|entry| std::ptr::eq(Arc::as_ptr(entry), file.as_ptr()))
+              .map(|index| self.names[index].clone())
+          }),
+        })
+        .collect(),
+      tick: wait.tick,
+    }
+  }
+
+  /// Returns the unread bytes of the named pipe.
+  pub fn buffered(&self, name: &str) -> Option<u64> {
+    let index = self.names.iter().position(|candidate| candidate == name)?;
+    match &*self.entries[index].lock().unwrap() {
+      SharedFile::Pipe(pipe) => Some(pipe.bytes.len() as u64),
+      SharedFile::Regular(_) => None,
+    }
+  }
+
+  /// Flushes the transcript of every pipe that requested one, regardless of the session outcome.
+  pub fn finish_transcripts(&self) -> Result<()> {
+    for entry in &self.entries {
+      let SharedFile::Pipe(Pipe {
+        transcript: Some(transcript),
+        ..
+      }) = &mut *entry.lock().unwrap()
+      else {
+        continue;
+      };
+      let result = match transcript.error.take() {
+        Some(error) => Err(error),
+        None => transcript.writer.flush(),
+      };
+      result.with_context(|| {
+        format!(
+          "failed to write pipe transcript {}",
+          transcript.path.display()
+        )
+      })?;
+    }
+    Ok(())
+  }
+
   /// Publishes host-backed files and restores prior destinations on failure.
   pub fn commit(self) -> Result<()> {
     let mut files = Vec::new();
@@ -553,26 +848,58 @@ struct Node {
   permissions: FilePermissions,
   path_access: bool,
   inode: u64,
+  scratch: Option<Arc<Mutex<ScratchQuota>>>,
 }
 
 #[derive(Clone, Debug)]
 enum NodeKind {
   Directory,
   Regular { file: Arc<Mutex<SharedFile>> },
+  Symlink { target: String },
+}
+
+impl NodeKind {
+  fn filetype(&self) -> types::Filetype {
+    match self {
+      Self::Directory => types::Filetype::Directory,
+      Self::Regular { .. } => types::Filetype::RegularFile,
+      Self::Symlink { .. } => types::Filetype::SymbolicLink,
+    }
+  }
+}
+
+/// The most recent hostcall that suspended a program.
+///
+/// Files are held weakly so a stale wait never keeps a session file open past its commit.
+#[derive(Clone, Debug)]
+pub struct PendingWait {
+  hostcall: &'static str,
+  descriptors: Vec<(u32, Option<Weak<Mutex<SharedFile>>>)>,
+  tick: u64,
 }
 
 /// Store-local deterministic Preview1 state.
 #[derive(Debug)]
 pub struct State {
-  /// Linear-memory limiter used by the Wasmtime store.
+  /// Linear-memory ceiling and usage, enforced when the state is the store's limiter.
   pub memory: MemoryLimiter,
+  program: String,
+  io_cost: IoCost,
+  io_ticks: u64,
   arguments: Vec<Vec<u8>>,
+  environment: Vec<Vec<u8>>,
   random: Xoshiro256PlusPlus,
+  clock: Clock,
+  tick_limit: u64,
   descriptors: BTreeMap<u32, Descriptor>,
   nodes: BTreeMap<String, Node>,
   writable_files: Vec<usize>,
   initial_files: Vec<usize>,
   hostcall_fuel: usize,
+  wait: Option<PendingWait>,
+  store: StorePointer,
+  /// Preview 2 handles owned by a component guest.
+  resources: ResourceTable,
 }
 
 impl State {
@@ -662,9 +989,39 @@ impl State {
             DirectoryPermissions::Execute | DirectoryPermissions::ReadExecute
           ),
           inode: u64::try_from(inode + 1).unwrap(),
+          scratch: None,
         },
       );
     }
+    for scratch in &program.file_system.scratch {
+      let quota = ScratchQuota {
+        root: scratch.path.clone(),
+        size_limit: u64::try_from(scratch.size_limit.resolve()?).unwrap_or(u64::MAX),
+        file_limit: scratch.file_limit,
+        size: 0,
+        files: 0,
+      };
+      nodes.insert(
+        scratch.path.clone(),
+        scratch_directory(&scratch.path, Arc::new(Mutex::new(quota))),
+      );
+    }
+    for symlink in &program.file_system.symlinks {
+      nodes.insert(
+        symlink.path.clone(),
+        Node {
+          kind: NodeKind::Symlink {
+            target: symlink.target.clone(),
+          },
+          permissions: FilePermissions::None,
+          path_access: false,
+          inode: stable_inode(&symlink.path),
+          scratch: None,
+        },
+      );
+    }
+    // Bindings of one file are hard links, so they share the inode of its first binding.
+    let mut inodes = BTreeMap::new();
     for binding in &program.file_system.bindings {
       let (index, file) = files.entry(&binding.file)?;
       initial_files.push(index);
@@ -677,19 +1034,32 @@ impl State {
           kind: NodeKind::Regular { file },
           permissions: binding.permissions,
           path_access: false,
-          inode: stable_inode(&binding.path),
+          inode: *inodes
+            .entry(binding.file.as_str())
+            .or_insert_with(|| stable_inode(&binding.path)),
+          scratch: None,
         },
       );
     }
-    let root_rights = nodes
+    let mut root_rights = nodes
       .get(".")
       .map_or_else(types::Rights::empty, node_rights);
     let mut inheriting = nodes.values().fold(types::Rights::empty(), |rights, node| {
       rights | node_rights(node)
     });
-    if nodes
-      .values()
-      .any(|node| matches!(node.kind, NodeKind::Regular { .. }))
+    if !program.file_system.scratch.is_empty() {
+      // wasi-libc resolves every path from the root preopen, so the root must carry the mutation
+      // rights. Whether the target's parent is a scratch directory remains the authoritative check.
+      root_rights |= scratch_rights();
+    }
+    if !program.file_system.symlinks.is_empty() {
+      root_rights |= types::Rights::PATH_READLINK;
+      inheriting |= types::Rights::PATH_READLINK;
+    }
+    if !program.file_system.scratch.is_empty()
+      || nodes
+        .values()
+        .any(|node| matches!(node.kind, NodeKind::Regular { .. }))
     {
       // wasi-libc intersects open-mode rights with the preopen's inheriting rights before calling
       // path_open. Advertise the complete regular-file ABI here so O_RDWR reaches path_open
@@ -711,21 +1081,154 @@ impl State {
       },
     );
     Ok(Self {
-      memory: MemoryLimiter {
-        limit,
-        peak: 0,
-        exceeded: false,
-      },
+      memory: MemoryLimiter::new(limit),
+      program: program.name.clone(),
+      io_cost: program.io_cost,
+      io_ticks: 0,
       arguments,
-      random: Xoshiro256PlusPlus::seed_from_u64(0),
+      environment: encode_environment(&program.environment),
+      random: Xoshiro256PlusPlus::seed_from_u64(program.random_seed),
+      clock: program.clock,
+      tick_limit: program.tick_limit,
       descriptors,
       nodes,
       writable_files,
       initial_files,
       hostcall_fuel: 0,
+      wait: None,
+      store: StorePointer(None),
+      resources: ResourceTable::new(),
     })
   }
 
+  /// Returns the quota of the scratch directory that would contain a new entry at `path`.
+  fn scratch_parent(&self, path: &str) -> WasiResult<Arc<Mutex<ScratchQuota>>> {
+    if path == "." {
+      return Err(errno(types::Errno::Exist));
+    }
+    match self.nodes.get(parent_path(path)) {
+      None => Err(errno(types::Errno::Noent)),
+      Some(Node {
+        kind: NodeKind::Regular { .. },
+        ..
+      }) => Err(errno(types::Errno::Notdir)),
+      Some(Node {
+        scratch: Some(quota),
+        ..
+      }) => Ok(Arc::clone(quota)),
+      Some(_) => Err(errno(types::Errno::Rofs)),
+    }
+  }
+
+  /// Returns the quota of a removable scratch entry, which excludes the scratch roots.
+  fn scratch_entry(&self, path: &str) -> WasiResult<Arc<Mutex<ScratchQuota>>> {
+    let node = self
+      .nodes
+      .get(path)
+      .ok_or_else(|| errno(types::Errno::Noent))?;
+    match &node.scratch {
+      Some(quota) if quota.lock().unwrap().root != path => Ok(Arc::clone(quota)),
+      _ => Err(errno(types::Errno::Rofs)),
+    }
+  }
+
+  fn has_children(&self, path: &str) -> bool {
+    let prefix = format!("{path}/");
+    self
+      .nodes
+      .range(prefix.clone()..)
+      .next()
+      .is_some_and(|(candidate, _)| candidate.starts_with(&prefix))
+  }
+
+  /// Counts the guest paths bound to the same file as `node`.
+  fn links(&self, node: &Node) -> u64 {
+    let NodeKind::Regular { file } = &node.kind else {
+      return 1;
+    };
+    self
+      .nodes
+      .values()
+      .filter(|other| matches!(&other.kind, NodeKind::Regular { file: other } if Arc::ptr_eq(file, other)))
+      .count() as u64
+  }
+
+  /// Replaces declared symlinks in a normalized path with their targets.
+  ///
+  /// Intermediate components are always followed, and the final one only when `follow_final` is
+  /// set. A target's `..` components may climb out of the link's parent but never above the root.
+  fn follow_symlinks(&self, path: &str, follow_final: bool) -> WasiResult<String> {
+    let mut resolved = Vec::<&str>::new();
+    let mut pending = path.split('/').collect::<VecDeque<_>>();
+    let mut hops = 0;
+    while let Some(part) = pending.pop_front() {
+      match part {
+        "" | "." => continue,
+        ".." => {
+          resolved
+            .pop()
+            .ok_or_else(|| errno(types::Errno::Notcapable))?;
+          continue;
+        }
+        _ => resolved.push(part),
+      }
+      if pending.is_empty() && !follow_final {
+        break;
+      }
+      if let Some(Node {
+        kind: NodeKind::Symlink { target },
+        ..
+      }) = self.nodes.get(&resolved.join("/"))
+      {
+        hops += 1;
+        if hops > MAX_SYMLINK_HOPS {
+          return Err(errno(types::Errno::Loop));
+        }
+        resolved.pop();
+        for part in target.split('/').rev() {
+          pending.push_front(part);
+        }
+      }
+    }
+    Ok(if resolved.is_empty() {
+      ".".into()
+    } else {
+      resolved.join("/")
+    })
+  }
+
+  /// Follows a symlink in the final component of a resolved path when `flags` ask for it.
+  fn follow_final(&self, path: String, flags: types::Lookupflags) -> WasiResult<String> {
+    if flags.contains(types::Lookupflags::SYMLINK_FOLLOW) {
+      self.follow_symlinks(&path, true)
+    } else {
+      Ok(path)
+    }
+  }
+
+  /// Adds the I/O cost of one read or write call, charged when the hostcall returns.
+  fn charge_io(&mut self, result: &WasiResult<u32>) {
+    let bytes = result.as_ref().map_or(0, |count| u64::from(*count));
+    self.io_ticks = self.io_ticks.saturating_add(self.io_cost.ticks(bytes));
+  }
+
+  /// Returns the ticks consumed as of the program's latest call or return, or zero before the
+  /// state is bound to its store.
+  fn ticks(&self) -> u64 {
+    let Some(store) = self.store.0 else {
+      return 0;
+    };
+    // SAFETY: `bind_store` requires the store to stay in place for its whole life, and reading
+    // the fuel counter touches no part of the store borrowed as this state.
+    let fuel = unsafe { store.as_ref() }.get_fuel();
+    fuel.map_or(0, |fuel| self.tick_limit.saturating_sub(fuel))
+  }
+
+  /// Returns the latest blocking wait, which is current while the program is suspended in one.
+  pub fn wait(&self) -> Option<&PendingWait> {
+    self.wait.as_ref()
+  }
+
   /// Closes every live descriptor so connected peers observe EOF or broken pipes.
   pub fn close_descriptors(&mut self) {
     for descriptor in std::mem::take(&mut self.descriptors).into_values() {
@@ -735,15 +1238,11 @@ impl State {
   }
 
   /// Creates host-only state with live inherited streams and an optional ambient directory.
-  pub fn new_local(
-    arguments: &[String],
-    memory_limit: u64,
-    file_size_limit: usize,
-    cwd: Option<&Path>,
-  ) -> Result<Self> {
-    let limit = usize::try_from(memory_limit).context("memory_limit does not fit host")?;
+  pub fn new_local(request: &LocalProgramRequest) -> Result<Self> {
+    validate_environment(&request.environment)?;
+    let limit = usize::try_from(request.memory_limit).context("memory_limit does not fit host")?;
     let mut encoded_arguments = vec![b"arg0\0".to_vec()];
-    for argument in arguments {
+    for argument in &request.arguments {
       if argument.as_bytes().contains(&0) {
         return Err(anyhow!("argument contains NUL"));
       }
@@ -752,7 +1251,7 @@ impl State {
       encoded_arguments.push(bytes);
     }
     let file_size_limit =
-      u64::try_from(file_size_limit).context("file size limit does not fit u64")?;
+      u64::try_from(request.file_size_limit).context("file size limit does not fit u64")?;
     let mut descriptors = BTreeMap::from([
       (
         0,
@@ -783,7 +1282,7 @@ impl State {
         ),
       ),
     ]);
-    if let Some(cwd) = cwd {
+    if let Some(cwd) = request.cwd.as_deref() {
       let canonical = std::fs::canonicalize(cwd)
         .with_context(|| format!("failed to open ambient cwd {}", cwd.display()))?;
       if !canonical.is_dir() {
@@ -811,18 +1310,23 @@ impl State {
       );
     }
     Ok(Self {
-      memory: MemoryLimiter {
-        limit,
-        peak: 0,
-        exceeded: false,
-      },
+      memory: MemoryLimiter::new(limit),
+      program: String::new(),
+      io_cost: IoCost::default(),
+      io_ticks: 0,
       arguments: encoded_arguments,
-      random: Xoshiro256PlusPlus::seed_from_u64(0),
+      environment: encode_environment(&request.environment),
+      random: Xoshiro256PlusPlus::seed_from_u64(request.random_seed),
+      clock: request.clock,
+      tick_limit: request.tick_limit,
       descriptors,
       nodes: BTreeMap::new(),
       writable_files: Vec::new(),
       initial_files: Vec::new(),
       hostcall_fuel: 0,
+      wait: None,
+      store: StorePointer(None),
+      resources: ResourceTable::new(),
     })
   }
 
@@ -855,6 +1359,26 @@ impl State {
       .ok_or_else(|| errno(types::Errno::Badf))
   }
 
+  /// Returns how many bytes a write to `fd` accepts without blocking, or `None` without a bound.
+  ///
+  /// A pipe with no reader or no size allowance left fails like the write itself would.
+  fn write_capacity(&self, fd: u32) -> WasiResult<Option<u64>> {
+    let DescriptorKind::File { file, .. } = &self.descriptor(fd)?.kind else {
+      return Ok(None);
+    };
+    match &*file.lock().unwrap() {
+      SharedFile::Regular(_) => Ok(None),
+      SharedFile::Pipe(pipe) if pipe.readers == 0 => Err(errno(types::Errno::Pipe)),
+      SharedFile::Pipe(pipe) if pipe.stream_length == pipe.size_limit => {
+        Err(errno(types::Errno::Fbig))
+      }
+      SharedFile::Pipe(pipe) => Ok(Some(
+        (pipe.capacity.saturating_sub(pipe.bytes.len()) as u64)
+          .min(pipe.size_limit - pipe.stream_length),
+      )),
+    }
+  }
+
   fn descriptor_mut(&mut self, fd: u32) -> WasiResult<&mut Descriptor> {
     self
       .descriptors
@@ -919,7 +1443,8 @@ impl State {
     } else {
       format!("{base}/{path}")
     };
-    normalize(&joined).ok_or_else(|| errno(types::Errno::Notcapable))
+    let path = normalize(&joined).ok_or_else(|| errno(types::Errno::Notcapable))?;
+    self.follow_symlinks(&path, false)
   }
 
   async fn read_file(
@@ -980,6 +1505,7 @@ impl State {
     let DescriptorKind::File { file, .. } = descriptor.kind else {
       return Err(errno(types::Errno::Isdir));
     };
+    let tick = self.ticks();
     let bytes = poll_fn(|context| {
       let mut guard = file.lock().unwrap();
       match &mut *guard {
@@ -1001,12 +1527,18 @@ impl State {
         }
         SharedFile::Pipe(pipe) => {
           remember_waker(&mut pipe.read_waiters, context.waker());
+          self.wait = Some(PendingWait {
+            hostcall: "fd_read",
+            descriptors: vec![(fd, Some(Arc::downgrade(&file)))],
+            tick,
+          });
           Poll::Pending
         }
       }
     })
     .await?;
     let count = write_iov(memory, iovs, &bytes)?;
+    file.lock().unwrap().record_read(&self.program, count);
     if advance {
       self.descriptor_mut(fd)?.position = offset
         .checked_add(u64::from(count))
@@ -1099,6 +1631,7 @@ impl State {
     };
     let position = offset.unwrap_or(descriptor.position);
     let mut effective_position = position;
+    let tick = self.ticks();
     let count = poll_fn(|context| {
       let mut guard = file.lock().unwrap();
       match &mut *guard {
@@ -1129,11 +1662,19 @@ impl State {
               return Poll::Ready(Err(errno(types::Errno::Again)));
             }
             remember_waker(&mut pipe.write_waiters, context.waker());
+            self.wait = Some(PendingWait {
+              hostcall: "fd_write",
+              descriptors: vec![(fd, Some(Arc::downgrade(&file)))],
+              tick,
+            });
             return Poll::Pending;
           }
           let count = available.min(bytes.len()).min(remaining as usize);
           pipe.bytes.extend(&bytes[..count]);
           pipe.stream_length += count as u64;
+          if let Some(transcript) = &mut pipe.transcript {
+            transcript.record(&self.program, tick, &bytes[..count]);
+          }
           if count < bytes.len() && pipe.stream_length == pipe.size_limit {
             pipe.exceeded = true;
           }
@@ -1143,6 +1684,7 @@ impl State {
       }
     })
     .await?;
+    file.lock().unwrap().record_write(&self.program, count);
     if offset.is_none() {
       self.descriptor_mut(fd)?.position = effective_position
         .checked_add(u64::from(count))
@@ -1226,9 +1768,49 @@ fn node_rights(node: &Node) -> types::Rights {
   if node.path_access {
     rights |= types::Rights::PATH_OPEN | types::Rights::PATH_FILESTAT_GET;
   }
+  if directory && node.scratch.is_some() {
+    rights |= scratch_rights();
+  }
   rights
 }
 
+fn scratch_rights() -> types::Rights {
+  types::Rights::PATH_CREATE_DIRECTORY
+    | types::Rights::PATH_CREATE_FILE
+    | types::Rights::PATH_RENAME_SOURCE
+    | types::Rights::PATH_RENAME_TARGET
+    | types::Rights::PATH_UNLINK_FILE
+    | types::Rights::PATH_REMOVE_DIRECTORY
+}
+
+fn scratch_directory(path: &str, quota: Arc<Mutex<ScratchQuota>>) -> Node {
+  Node {
+    kind: NodeKind::Directory,
+    permissions: FilePermissions::Read,
+    path_access: true,
+    inode: stable_inode(path),
+    scratch: Some(quota),
+  }
+}
+
+fn scratch_file(path: &str, quota: Arc<Mutex<ScratchQuota>>) -> Node {
+  Node {
+    kind: NodeKind::Regular {
+      file: Arc::new(Mutex::new(SharedFile::Regular(RegularFile::scratch(
+        Arc::clone(&quota),
+      )))),
+    },
+    permissions: FilePermissions::ReadWrite,
+    path_access: false,
+    inode: stable_inode(path),
+    scratch: Some(quota),
+  }
+}
+
+fn parent_path(path: &str) -> &str {
+  path.rsplit_once('/').map_or(".", |(parent, _)| parent)
+}
+
 fn host_descriptor(kind: DescriptorKind, rights: types::Rights) -> Descriptor {
   Descriptor {
     kind,
@@ -1350,8 +1932,9 @@ fn poll_subscription(
         },
         0,
         types::Eventrwflags::empty(),
-        // Deterministic judging has no advancing clock. Zero-time waits are ready immediately;
-        // every nonzero timeout remains pending and can only be bypassed by another ready event.
+        // Clocks only advance with consumed ticks, and waiting consumes none. Zero-time waits are
+        // ready immediately; every nonzero timeout remains pending and can only be bypassed by
+        // another ready event.
         valid && clock.timeout != 0,
       )
     }
@@ -1642,6 +2225,47 @@ pub fn add_to_linker(linker: &mut Linker<State>) -> Result<()> {
   Ok(())
 }
 
+/// Points a store's state back at the store it lives in.
+#[derive(Debug)]
+struct StorePointer(Option<NonNull<Store<State>>>);
+
+// SAFETY: the pointer is only dereferenced by the state inside the store it points to, so it
+// crosses threads together with that store.
+unsafe impl Send for StorePointer {}
+unsafe impl Sync for StorePointer {}
+
+/// Binds the state to its store, so it reads the store's live fuel, and makes it the limiter.
+///
+/// The store must not move until it is dropped, as a pinned box guarantees.
+pub fn bind_store(store: &mut Store<State>) {
+  let pointer = NonNull::from(&*store);
+  store.data_mut().store = StorePointer(Some(pointer));
+  store.limiter(|state| state);
+}
+
+/// Charges the I/O cost of hostcalls when they return.
+///
+/// The hook is only installed when I/O has a cost.
+pub fn install_hostcall_hook(store: &mut Store<State>) {
+  if store.data().io_cost.is_free() {
+    return;
+  }
+  // Compiled code reloads its fuel counter on return from the host, so the charge is exact.
+  store.call_hook(|mut context, hook| {
+    if let CallHook::ReturningFromHost = hook {
+      let charge = std::mem::take(&mut context.data_mut().io_ticks);
+      if charge > 0 {
+        let fuel = context.get_fuel()?;
+        context.set_fuel(fuel.saturating_sub(charge))?;
+        if charge > fuel {
+          return Err(wasmtime::Trap::OutOfFuel.into());
+        }
+      }
+    }
+    Ok(())
+  });
+}
+
 fn write_bytes(
   memory: &mut GuestMemory<'_>,
   pointer: GuestPtr<u8>,
@@ -1654,6 +2278,45 @@ fn write_bytes(
   Ok(())
 }
 
+/// Writes NUL-terminated strings and their pointer table for `args_get` and `environ_get`.
+fn write_strings(
+  memory: &mut GuestMemory<'_>,
+  mut pointers: GuestPtr<GuestPtr<u8>>,
+  mut buffer: GuestPtr<u8>,
+  strings: &[Vec<u8>],
+) -> WasiResult<()> {
+  for string in strings {
+    memory.write(pointers, buffer)?;
+    pointers = pointers.add(1)?;
+    write_bytes(memory, buffer, string)?;
+    buffer = buffer.add(u32::try_from(string.len()).map_err(|_| errno(types::Errno::Overflow))?)?;
+  }
+  Ok(())
+}
+
+fn string_sizes(strings: &[Vec<u8>]) -> WasiResult<(u32, u32)> {
+  let count = strings
+    .len()
+    .try_into()
+    .map_err(|_| errno(types::Errno::Overflow))?;
+  let bytes = strings
+    .iter()
+    .try_fold(0_u32, |sum, string| {
+      sum.checked_add(string.len().try_into().ok()?)
+    })
+    .ok_or_else(|| errno(types::Errno::Overflow))?;
+  Ok((count, bytes))
+}
+
+/// Encodes `KEY=VALUE` entries, already checked by `validate_environment`, in the stable order of
+/// the request map.
+fn encode_environment(environment: &BTreeMap<String, String>) -> Vec<Vec<u8>> {
+  environment
+    .iter()
+    .map(|(name, value)| format!("{name}={value}\0").into_bytes())
+    .collect()
+}
+
 fn read_ciov(memory: &GuestMemory<'_>, iovs: types::CiovecArray) -> WasiResult<Vec<u8>> {
   // WASI permits short writes, so one hostcall copies only a bounded prefix. A guest controls the
   // aggregate iovec length, and allocating that length on the host would bypass its memory limit.
@@ -1693,15 +2356,12 @@ fn write_iov(
   u32::try_from(position).map_err(|_| errno(types::Errno::Overflow))
 }
 
-fn filestat(node: &Node, size: u64) -> types::Filestat {
+fn filestat(node: &Node, size: u64, nlink: u64) -> types::Filestat {
   types::Filestat {
     dev: 0,
     ino: node.inode,
-    filetype: match node.kind {
-      NodeKind::Directory => types::Filetype::Directory,
-      NodeKind::Regular { .. } => types::Filetype::RegularFile,
-    },
-    nlink: 1,
+    filetype: node.kind.filetype(),
+    nlink,
     size,
     atim: 0,
     mtim: 0,
@@ -1765,6 +2425,7 @@ pub fn classify(
   file_error: bool,
   tick: u64,
   memory: u64,
+  stack: u64,
   program: &str,
 ) -> ProgramResult {
   let (status, exit_code, error_message) = match result {
@@ -1796,18 +2457,49 @@ pub fn classify(
       Some(exit.0),
       Some(format!("Nonzero exit code: {}", exit.0)),
     ),
-    Err(error) => (RunStatus::RuntimeError, None, Some(error.to_string())),
+    Err(error)
+      if error.downcast_ref::<wasmtime::Trap>() == Some(&wasmtime::Trap::StackOverflow) =>
+    {
+      (
+        RunStatus::RuntimeError,
+        None,
+        Some("Stack limit exceeded".into()),
+      )
+    }
+    Err(error) => (RunStatus::RuntimeError, None, Some(trap_message(error))),
+  };
+  let backtrace = match result {
+    Err(error) if status == RunStatus::RuntimeError => error
+      .downcast_ref::<wasmtime::WasmBacktrace>()
+      .map(super::backtrace::symbolize)
+      .unwrap_or_default(),
+    _ => Vec::new(),
   };
   ProgramResult {
     program: program.into(),
     status,
     tick,
     memory,
+    stack,
     exit_code,
     error_message,
+    backtrace,
+    coredump_path: None,
+    files: Vec::new(),
+    memory_timeline: state.memory.timeline.clone(),
   }
 }
 
+// Wasmtime attaches the backtrace and core dump as outer contexts; both are reported separately.
+fn trap_message(error: &wasmtime::Error) -> String {
+  let contexts = usize::from(error.downcast_ref::<wasmtime::WasmBacktrace>().is_some())
+    + usize::from(error.downcast_ref::<wasmtime::WasmCoreDump>().is_some());
+  error
+    .chain()
+    .nth(contexts)
+    .map_or_else(|| error.to_string(), ToString::to_string)
+}
+
 impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
   fn set_hostcall_fuel(&mut self, fuel: usize) {
     self.hostcall_fuel = fuel;
@@ -1816,45 +2508,27 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
   async fn args_get(
     &mut self,
     memory: &mut GuestMemory<'_>,
-    mut argv: GuestPtr<GuestPtr<u8>>,
-    mut buffer: GuestPtr<u8>,
+    argv: GuestPtr<GuestPtr<u8>>,
+    buffer: GuestPtr<u8>,
   ) -> WasiResult<()> {
-    for argument in &self.arguments {
-      memory.write(argv, buffer)?;
-      argv = argv.add(1)?;
-      write_bytes(memory, buffer, argument)?;
-      buffer =
-        buffer.add(u32::try_from(argument.len()).map_err(|_| errno(types::Errno::Overflow))?)?;
-    }
-    Ok(())
+    write_strings(memory, argv, buffer, &self.arguments)
   }
 
   async fn args_sizes_get(&mut self, _memory: &mut GuestMemory<'_>) -> WasiResult<(u32, u32)> {
-    let count = self
-      .arguments
-      .len()
-      .try_into()
-      .map_err(|_| errno(types::Errno::Overflow))?;
-    let bytes = self
-      .arguments
-      .iter()
-      .try_fold(0_u32, |sum, argument| {
-        sum.checked_add(argument.len().try_into().ok()?)
-      })
-      .ok_or_else(|| errno(types::Errno::Overflow))?;
-    Ok((count, bytes))
+    string_sizes(&self.arguments)
   }
 
   async fn environ_get(
     &mut self,
-    _memory: &mut GuestMemory<'_>,
-    _environ: GuestPtr<GuestPtr<u8>>,
-    _buffer: GuestPtr<u8>,
+    memory: &mut GuestMemory<'_>,
+    environ: GuestPtr<GuestPtr<u8>>,
+    buffer: GuestPtr<u8>,
   ) -> WasiResult<()> {
-    Ok(())
+    write_strings(memory, environ, buffer, &self.environment)
   }
+
   async fn environ_sizes_get(&mut self, _memory: &mut GuestMemory<'_>) -> WasiResult<(u32, u32)> {
-    Ok((0, 0))
+    string_sizes(&self.environment)
   }
 
   async fn clock_res_get(
@@ -1863,7 +2537,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     id: types::Clockid,
   ) -> WasiResult<u64> {
     match id {
-      types::Clockid::Realtime | types::Clockid::Monotonic => Ok(1),
+      types::Clockid::Realtime | types::Clockid::Monotonic => Ok(self.clock.resolution()),
       _ => Err(errno(types::Errno::Badf)),
     }
   }
@@ -1875,7 +2549,9 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     _precision: u64,
   ) -> WasiResult<u64> {
     match id {
-      types::Clockid::Realtime | types::Clockid::Monotonic => Ok(0),
+      types::Clockid::Realtime | types::Clockid::Monotonic => {
+        Ok(self.clock.nanoseconds(self.ticks()))
+      }
       _ => Err(errno(types::Errno::Badf)),
     }
   }
@@ -2055,6 +2731,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
           .get(path)
           .ok_or_else(|| errno(types::Errno::Noent))?,
         0,
+        1,
       )),
       DescriptorKind::File { file, .. } => {
         let (filetype, size) = match &*file.lock().unwrap() {
@@ -2148,7 +2825,9 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     iovs: types::IovecArray,
     offset: u64,
   ) -> WasiResult<u32> {
-    self.read_file(memory, fd.into(), iovs, offset, false).await
+    let result = self.read_file(memory, fd.into(), iovs, offset, false).await;
+    self.charge_io(&result);
+    result
   }
 
   async fn fd_prestat_get(
@@ -2194,7 +2873,9 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     iovs: types::CiovecArray,
     offset: u64,
   ) -> WasiResult<u32> {
-    self.write_file(memory, fd.into(), iovs, Some(offset)).await
+    let result = self.write_file(memory, fd.into(), iovs, Some(offset)).await;
+    self.charge_io(&result);
+    result
   }
 
   async fn fd_read(
@@ -2204,8 +2885,15 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     iovs: types::IovecArray,
   ) -> WasiResult<u32> {
     let raw = fd.into();
-    let offset = self.descriptor(raw)?.position;
-    self.read_file(memory, raw, iovs, offset, true).await
+    let result = match self.descriptor(raw) {
+      Ok(descriptor) => {
+        let offset = descriptor.position;
+        self.read_file(memory, raw, iovs, offset, true).await
+      }
+      Err(error) => Err(error),
+    };
+    self.charge_io(&result);
+    result
   }
 
   async fn fd_readdir(
@@ -2286,16 +2974,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       if rest.is_empty() || rest.contains('/') {
         continue;
       }
-      entries.insert(
-        rest.to_owned(),
-        (
-          node.inode,
-          match node.kind {
-            NodeKind::Directory => types::Filetype::Directory,
-            NodeKind::Regular { .. } => types::Filetype::RegularFile,
-          },
-        ),
-      );
+      entries.insert(rest.to_owned(), (node.inode, node.kind.filetype()));
     }
     let mut encoded = Vec::new();
     for (index, (name, (inode, filetype))) in entries.into_iter().enumerate().skip(cookie as usize)
@@ -2397,7 +3076,9 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     fd: types::Fd,
     iovs: types::CiovecArray,
   ) -> WasiResult<u32> {
-    self.write_file(memory, fd.into(), iovs, None).await
+    let result = self.write_file(memory, fd.into(), iovs, None).await;
+    self.charge_io(&result);
+    result
   }
 
   async fn path_create_directory(
@@ -2406,20 +3087,27 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     fd: types::Fd,
     path: GuestPtr<str>,
   ) -> WasiResult<()> {
-    self.resolve_path(
+    let path = self.resolve_path(
       memory,
       fd.into(),
       path,
       types::Rights::PATH_CREATE_DIRECTORY,
     )?;
-    Err(errno(types::Errno::Rofs))
+    if self.nodes.contains_key(&path) {
+      return Err(errno(types::Errno::Exist));
+    }
+    let quota = self.scratch_parent(&path)?;
+    quota.lock().unwrap().add_entry()?;
+    let node = scratch_directory(&path, quota);
+    self.nodes.insert(path, node);
+    Ok(())
   }
 
   async fn path_filestat_get(
     &mut self,
     memory: &mut GuestMemory<'_>,
     fd: types::Fd,
-    _flags: types::Lookupflags,
+    flags: types::Lookupflags,
     path: GuestPtr<str>,
   ) -> WasiResult<types::Filestat> {
     if let Some((root, path)) =
@@ -2429,6 +3117,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       return Ok(host_filestat(metadata, &path));
     }
     let path = self.resolve_path(memory, fd.into(), path, types::Rights::PATH_FILESTAT_GET)?;
+    let path = self.follow_final(path, flags)?;
     let node = self
       .nodes
       .get(&path)
@@ -2436,15 +3125,16 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     let size = match &node.kind {
       NodeKind::Directory => 0,
       NodeKind::Regular { file, .. } => file_size(file)?,
+      NodeKind::Symlink { target } => target.len() as u64,
     };
-    Ok(filestat(node, size))
+    Ok(filestat(node, size, self.links(node)))
   }
 
   async fn path_filestat_set_times(
     &mut self,
     memory: &mut GuestMemory<'_>,
     fd: types::Fd,
-    _flags: types::Lookupflags,
+    flags: types::Lookupflags,
     path: GuestPtr<str>,
     _atim: u64,
     _mtim: u64,
@@ -2457,6 +3147,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       path,
       types::Rights::PATH_FILESTAT_SET_TIMES,
     )?;
+    let path = self.follow_final(path, flags)?;
     if !self.nodes.contains_key(&path) {
       return Err(errno(types::Errno::Noent));
     }
@@ -2467,7 +3158,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     &mut self,
     memory: &mut GuestMemory<'_>,
     old_fd: types::Fd,
-    _old_flags: types::Lookupflags,
+    old_flags: types::Lookupflags,
     old_path: GuestPtr<str>,
     new_fd: types::Fd,
     new_path: GuestPtr<str>,
@@ -2478,23 +3169,41 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       old_path,
       types::Rights::PATH_LINK_SOURCE,
     )?;
-    self.resolve_path(
+    let old_path = self.follow_final(old_path, old_flags)?;
+    let new_path = self.resolve_path(
       memory,
       new_fd.into(),
       new_path,
       types::Rights::PATH_LINK_TARGET,
     )?;
-    if !self.nodes.contains_key(&old_path) {
-      return Err(errno(types::Errno::Noent));
+    let node = self
+      .nodes
+      .get(&old_path)
+      .ok_or_else(|| errno(types::Errno::Noent))?;
+    let (NodeKind::Regular { .. }, Some(quota)) = (&node.kind, &node.scratch) else {
+      return Err(errno(if matches!(node.kind, NodeKind::Directory) {
+        types::Errno::Perm
+      } else {
+        types::Errno::Rofs
+      }));
+    };
+    if self.nodes.contains_key(&new_path) {
+      return Err(errno(types::Errno::Exist));
     }
-    Err(errno(types::Errno::Rofs))
+    if !Arc::ptr_eq(quota, &self.scratch_parent(&new_path)?) {
+      return Err(errno(types::Errno::Xdev));
+    }
+    quota.lock().unwrap().add_entry()?;
+    let node = node.clone();
+    self.nodes.insert(new_path, node);
+    Ok(())
   }
 
   async fn path_open(
     &mut self,
     memory: &mut GuestMemory<'_>,
     fd: types::Fd,
-    _dirflags: types::Lookupflags,
+    dirflags: types::Lookupflags,
     path: GuestPtr<str>,
     oflags: types::Oflags,
     requested: types::Rights,
@@ -2565,16 +3274,31 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       return Ok(new_fd.into());
     }
     let path = self.resolve_path(memory, fd.into(), path, types::Rights::PATH_OPEN)?;
-    let Some(node) = self.nodes.get(&path).cloned() else {
-      return Err(errno(if oflags.contains(types::Oflags::CREAT) {
-        types::Errno::Rofs
-      } else {
-        types::Errno::Noent
-      }));
+    let path = self.follow_final(path, dirflags)?;
+    let node = match self.nodes.get(&path).cloned() {
+      Some(Node {
+        kind: NodeKind::Symlink { .. },
+        ..
+      }) => return Err(errno(types::Errno::Loop)),
+      Some(_) if oflags.contains(types::Oflags::CREAT | types::Oflags::EXCL) => {
+        return Err(errno(types::Errno::Exist));
+      }
+      Some(node) => node,
+      None if oflags.contains(types::Oflags::CREAT) => {
+        let quota = self.scratch_parent(&path)?;
+        if !parent.rights.contains(types::Rights::PATH_CREATE_FILE) {
+          return Err(errno(types::Errno::Notcapable));
+        }
+        if oflags.contains(types::Oflags::DIRECTORY) {
+          return Err(errno(types::Errno::Inval));
+        }
+        quota.lock().unwrap().add_entry()?;
+        let node = scratch_file(&path, quota);
+        self.nodes.insert(path.clone(), node.clone());
+        node
+      }
+      None => return Err(errno(types::Errno::Noent)),
     };
-    if oflags.contains(types::Oflags::CREAT | types::Oflags::EXCL) {
-      return Err(errno(types::Errno::Exist));
-    }
     let directory = matches!(node.kind, NodeKind::Directory);
     let mut available = node_rights(&node);
     if !directory {
@@ -2606,6 +3330,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     let kind = match node.kind {
       NodeKind::Directory => DescriptorKind::Directory { path },
       NodeKind::Regular { file, .. } => DescriptorKind::File { file },
+      NodeKind::Symlink { .. } => unreachable!("symlinks are rejected before opening"),
     };
     let new_fd = self.next_fd();
     self.descriptors.insert(
@@ -2628,14 +3353,21 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     memory: &mut GuestMemory<'_>,
     fd: types::Fd,
     path: GuestPtr<str>,
-    _buf: GuestPtr<u8>,
-    _buf_len: u32,
+    buf: GuestPtr<u8>,
+    buf_len: u32,
   ) -> WasiResult<u32> {
     let path = self.resolve_path(memory, fd.into(), path, types::Rights::PATH_READLINK)?;
-    if !self.nodes.contains_key(&path) {
-      return Err(errno(types::Errno::Noent));
-    }
-    Err(errno(types::Errno::Inval))
+    let node = self
+      .nodes
+      .get(&path)
+      .ok_or_else(|| errno(types::Errno::Noent))?;
+    let NodeKind::Symlink { target } = &node.kind else {
+      return Err(errno(types::Errno::Inval));
+    };
+    // Like readlink(2), a short buffer receives a truncated target without a terminator.
+    let bytes = &target.as_bytes()[..target.len().min(buf_len as usize)];
+    write_bytes(memory, buf, bytes)?;
+    Ok(bytes.len() as u32)
   }
 
   async fn path_remove_directory(
@@ -2652,8 +3384,16 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     )?;
     match self.nodes.get(&path).map(|node| &node.kind) {
       None => Err(errno(types::Errno::Noent)),
-      Some(NodeKind::Regular { .. }) => Err(errno(types::Errno::Notdir)),
-      Some(NodeKind::Directory) => Err(errno(types::Errno::Rofs)),
+      Some(NodeKind::Regular { .. } | NodeKind::Symlink { .. }) => Err(errno(types::Errno::Notdir)),
+      Some(NodeKind::Directory) => {
+        let quota = self.scratch_entry(&path)?;
+        if self.has_children(&path) {
+          return Err(errno(types::Errno::Notempty));
+        }
+        self.nodes.remove(&path);
+        quota.lock().unwrap().files -= 1;
+        Ok(())
+      }
     }
   }
 
@@ -2671,16 +3411,56 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
       old_path,
       types::Rights::PATH_RENAME_SOURCE,
     )?;
-    self.resolve_path(
+    let new_path = self.resolve_path(
       memory,
       new_fd.into(),
       new_path,
       types::Rights::PATH_RENAME_TARGET,
     )?;
-    if !self.nodes.contains_key(&old_path) {
-      return Err(errno(types::Errno::Noent));
+    let quota = self.scratch_entry(&old_path)?;
+    if !Arc::ptr_eq(&quota, &self.scratch_parent(&new_path)?) {
+      return Err(errno(types::Errno::Xdev));
     }
-    Err(errno(types::Errno::Rofs))
+    if old_path == new_path {
+      return Ok(());
+    }
+    let directory = matches!(self.nodes[&old_path].kind, NodeKind::Directory);
+    if directory && new_path.starts_with(&format!("{old_path}/")) {
+      return Err(errno(types::Errno::Inval));
+    }
+    if let Some(target) = self.nodes.get(&new_path) {
+      match (&target.kind, directory) {
+        (NodeKind::Directory, false) => return Err(errno(types::Errno::Isdir)),
+        (NodeKind::Regular { .. }, true) => return Err(errno(types::Errno::Notdir)),
+        (NodeKind::Directory, true) if self.has_children(&new_path) => {
+          return Err(errno(types::Errno::Notempty));
+        }
+        _ => {}
+      }
+      self.nodes.remove(&new_path);
+      quota.lock().unwrap().files -= 1;
+    }
+    let old_prefix = format!("{old_path}/");
+    let moved = self
+      .nodes
+      .keys()
+      .filter(|path| **path == old_path || path.starts_with(&old_prefix))
+      .cloned()
+      .collect::<Vec<_>>();
+    for path in moved {
+      let node = self.nodes.remove(&path).unwrap();
+      self
+        .nodes
+        .insert(format!("{new_path}{}", &path[old_path.len()..]), node);
+    }
+    for descriptor in self.descriptors.values_mut() {
+      if let DescriptorKind::Directory { path } = &mut descriptor.kind
+        && (*path == old_path || path.starts_with(&old_prefix))
+      {
+        *path = format!("{new_path}{}", &path[old_path.len()..]);
+      }
+    }
+    Ok(())
   }
 
   async fn path_symlink(
@@ -2705,7 +3485,13 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     match self.nodes.get(&path).map(|node| &node.kind) {
       None => Err(errno(types::Errno::Noent)),
       Some(NodeKind::Directory) => Err(errno(types::Errno::Isdir)),
-      Some(NodeKind::Regular { .. }) => Err(errno(types::Errno::Rofs)),
+      Some(NodeKind::Regular { .. } | NodeKind::Symlink { .. }) => {
+        let quota = self.scratch_entry(&path)?;
+        // Open descriptors keep the file, and its bytes stay charged until they close.
+        self.nodes.remove(&path);
+        quota.lock().unwrap().files -= 1;
+        Ok(())
+      }
     }
   }
 
@@ -2721,6 +3507,7 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
     }
     // Scan guest subscriptions in place instead of mirroring guest-controlled arrays in host
     // Vecs. The guest cannot mutate linear memory while this async hostcall is suspended.
+    let tick = self.ticks();
     poll_fn(|context| {
       let result = (|| {
         let mut ready_count = 0_u32;
@@ -2735,7 +3522,30 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
         Ok(ready_count)
       })();
       match result {
-        Ok(0) => Poll::Pending,
+        Ok(0) => {
+          let descriptors = (0..count)
+            .filter_map(|index| memory.read(subscriptions.add(index).ok()?).ok())
+            .filter_map(|subscription| match subscription.u {
+              types::SubscriptionU::FdRead(wait) | types::SubscriptionU::FdWrite(wait) => {
+                Some(u32::from(wait.file_descriptor))
+              }
+              types::SubscriptionU::Clock(_) => None,
+            })
+            .map(|fd| {
+              let file = match self.descriptors.get(&fd).map(|descriptor| &descriptor.kind) {
+                Some(DescriptorKind::File { file }) => Some(Arc::downgrade(file)),
+                _ => None,
+              };
+              (fd, file)
+            })
+            .collect();
+          self.wait = Some(PendingWait {
+            hostcall: "poll_oneoff",
+            descriptors,
+            tick,
+          });
+          Poll::Pending
+        }
         Ok(ready_count) => Poll::Ready(Ok(ready_count)),
         Err(error) => Poll::Ready(Err(error)),
       }
@@ -2820,6 +3630,28 @@ impl wasi_snapshot_preview1::WasiSnapshotPreview1 for State {
 mod tests {
   use super::*;
 
+  #[test]
+  fn memory_timeline_keeps_first_growth_and_peak_when_thinned() {
+    let mut limiter = MemoryLimiter::new(1 << 30);
+    for page in 1..=100 {
+      assert!(limiter.grow(page, page as usize * 65536));
+      assert!(limiter.grow(page, 65536));
+    }
+    assert!(!limiter.grow(101, 1 << 31));
+
+    assert!(limiter.timeline.len() <= MEMORY_TIMELINE_LENGTH);
+    assert_eq!(
+      limiter.timeline[0],
+      MemoryGrowth {
+        tick: 1,
+        memory: 65536,
+      }
+    );
+    assert_eq!(limiter.timeline.last().unwrap().memory, 1 << 31);
+    assert!(limiter.timeline.is_sorted_by_key(|growth| growth.tick));
+    assert!(limiter.exceeded);
+  }
+
   #[test]
   fn truncate_hides_discarded_snapshot_bytes_after_growth() {
     let mut source = tempfile::NamedTempFile::new().unwrap();
@@ -0,0 +1,1480 @@
+//! `wasi:cli/command` components served by the Preview1 implementation.
+//!
+//! Every Preview 2 import is answered by the same [`State`] hostcalls that serve core modules,
+//! reached through a host-owned scratch memory. Components therefore see the same files, pipes,
+//! clocks, random stream, tick charges, and deadlock reports as modules.
+
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use wasmtime::{
+  Store,
+  component::{Component, HasSelf, Linker, Resource},
+};
+use wiggle::{GuestMemory, GuestPtr, GuestType};
+
+use super::{
+  COPY_BUFFER_SIZE, ProcessExit, State, WasiResult, errno, types,
+  wasi_snapshot_preview1::WasiSnapshotPreview1,
+};
+use bindings::wasi::{
+  cli::{
+    environment, exit, stderr, stdin, stdout, terminal_input, terminal_output, terminal_stderr,
+    terminal_stdin, terminal_stdout,
+  },
+  clocks::{monotonic_clock, wall_clock},
+  filesystem::{preopens, types as filesystem},
+  io::{error, poll, streams},
+  random::{insecure, insecure_seed, random},
+};
+
+mod bindings {
+  wasmtime::component::bindgen!({
+    path: "src/runner/wasi/wit",
+    world: "hull:runner/command",
+    imports: { default: async | trappable },
+    exports: { default: async },
+    with: {
+      "wasi:io/error.error": crate::runner::wasi::preview2::IoError,
+      "wasi:io/poll.pollable": crate::runner::wasi::preview2::Pollable,
+      "wasi:io/streams.input-stream": crate::runner::wasi::preview2::InputStream,
+      "wasi:io/streams.output-stream": crate::runner::wasi::preview2::OutputStream,
+      "wasi:filesystem/types.descriptor": crate::runner::wasi::preview2::OpenDescriptor,
+      "wasi:filesystem/types.directory-entry-stream":
+        crate::runner::wasi::preview2::DirectoryEntries,
+    },
+    trappable_error_type: {
+      "wasi:io/streams.stream-error" => crate::runner::wasi::preview2::StreamFailure,
+      "wasi:filesystem/types.error-code" => crate::runner::wasi::types::Error,
+    },
+  });
+}
+
+/// Bytes a stream promises to accept before the next write; pipes never reject a write.
+const WRITE_PERMIT: u64 = COPY_BUFFER_SIZE as u64;
+
+/// Failed Preview1 call behind a `wasi:io/error` resource.
+pub struct IoError(types::Errno);
+
+/// Why a stream operation failed.
+pub enum StreamFailure {
+  /// The stream reached its end or lost its last reader.
+  Closed,
+  /// A Preview1 call failed or trapped.
+  Failed(types::Error),
+}
+
+impl From<types::Error> for StreamFailure {
+  fn from(error: types::Error) -> Self {
+    if error.downcast_ref() == Some(&types::Errno::Pipe) {
+      Self::Closed
+    } else {
+      Self::Failed(error)
+    }
+  }
+}
+
+/// Event a pollable waits for.
+#[derive(Clone, Copy)]
+pub enum Pollable {
+  /// Always ready, as for streams over regular files.
+  Ready,
+  /// Data or end of stream on a descriptor.
+  Read(u32),
+  /// Room in a descriptor.
+  Write(u32),
+  /// The monotonic clock reaching this many nanoseconds.
+  Instant(u64),
+}
+
+/// Byte stream read from a descriptor.
+#[derive(Clone, Copy)]
+pub struct InputStream {
+  fd: u32,
+  /// Position of the next positional read, or `None` to read at the descriptor's own cursor.
+  offset: Option<u64>,
+}
+
+/// Byte stream written to a descriptor.
+#[derive(Clone, Copy)]
+pub struct OutputStream {
+  fd: u32,
+  position: WritePosition,
+}
+
+#[derive(Clone, Copy)]
+enum WritePosition {
+  Cursor,
+  Offset(u64),
+  Append,
+}
+
+/// Descriptor handed to a component, closed on drop unless it is a preopen.
+pub struct OpenDescriptor {
+  fd: u32,
+  preopen: bool,
+}
+
+/// Directory entries read when the stream was opened.
+pub struct DirectoryEntries(VecDeque<filesystem::DirectoryEntry>);
+
+/// Host-owned linear memory through which Preview 2 calls reach the Preview1 implementation.
+#[derive(Default)]
+struct Scratch(Vec<u8>);
+
+impl Scratch {
+  /// Reserves zeroed, aligned room for `count` values.
+  fn reserve<T: GuestType>(&mut self, count: u32) -> WasiResult<GuestPtr<[T]>> {
+    let start = self.0.len().next_multiple_of(T::guest_align());
+    let end = (T::guest_size() as usize)
+      .checked_mul(count as usize)
+      .and_then(|size| start.checked_add(size))
+      .filter(|end| u32::try_from(*end).is_ok())
+      .ok_or_else(|| errno(types::Errno::Overflow))?;
+    self.0.resize(end, 0);
+    Ok(GuestPtr::new((start as u32, count)))
+  }
+
+  fn push<T: GuestType>(&mut self, values: Vec<T>) -> WasiResult<GuestPtr<[T]>> {
+    let count = u32::try_from(values.len()).map_err(|_| errno(types::Errno::Overflow))?;
+    let array = self.reserve::<T>(count)?;
+    for (index, value) in (0..count).zip(values) {
+      self.memory().write(array.get(index).unwrap(), value)?;
+    }
+    Ok(array)
+  }
+
+  fn push_str(&mut self, value: &str) -> WasiResult<GuestPtr<str>> {
+    let bytes = self.push(value.as_bytes().to_vec())?;
+    Ok(GuestPtr::new((bytes.offset_base(), bytes.len())))
+  }
+
+  fn bytes(&self, array: GuestPtr<[u8]>, count: u32) -> Vec<u8> {
+    let start = array.offset_base() as usize;
+    self.0[start..start + count as usize].to_vec()
+  }
+
+  fn memory(&mut self) -> GuestMemory<'_> {
+    GuestMemory::Unshared(&mut self.0)
+  }
+}
+
+/// Registers Hull's deterministic `wasi:cli/command` imports.
+pub fn add_to_linker(linker: &mut Linker<State>) -> Result<()> {
+  bindings::Command::add_to_linker::<State, HasSelf<State>>(linker, |state| state)?;
+  Ok(())
+}
+
+/// Instantiates a command component and calls its `wasi:cli/run` export.
+///
+/// Returns the run outcome and, when the component never started, the setup diagnostic.
+pub async fn run(
+  store: &mut Store<State>,
+  linker: &Linker<State>,
+  component: &Component,
+) -> (wasmtime::Result<()>, Option<String>) {
+  let command = match bindings::Command::instantiate_async(&mut *store, component, linker).await {
+    Ok(command) => command,
+    Err(error) => {
+      let message = error.to_string();
+      return (Err(error), Some(message));
+    }
+  };
+  let result = match command.wasi_cli_run().call_run(&mut *store).await {
+    Ok(Ok(())) => Ok(()),
+    Ok(Err(())) => Err(ProcessExit(1).into()),
+    Err(error) => Err(error),
+  };
+  (result, None)
+}
+
+fn trap(error: impl Into<wasmtime::Error>) -> types::Error {
+  types::Error::trap(error.into())
+}
+
+/// Turns a Preview1 failure inside an infallible Preview 2 call into a trap.
+fn into_trap(error: types::Error) -> wasmtime::Error {
+  match error.downcast() {
+    Ok(errno) => wasmtime::format_err!("unexpected Preview1 error {errno:?}"),
+    Err(trap) => trap,
+  }
+}
+
+fn nanoseconds(datetime: wall_clock::Datetime) -> u64 {
+  datetime
+    .seconds
+    .saturating_mul(1_000_000_000)
+    .saturating_add(u64::from(datetime.nanoseconds))
+}
+
+fn datetime(nanoseconds: u64) -> wall_clock::Datetime {
+  wall_clock::Datetime {
+    seconds: nanoseconds / 1_000_000_000,
+    nanoseconds: (nanoseconds % 1_000_000_000) as u32,
+  }
+}
+
+fn lookup_flags(flags: filesystem::PathFlags) -> types::Lookupflags {
+  if flags.contains(filesystem::PathFlags::SYMLINK_FOLLOW) {
+    types::Lookupflags::SYMLINK_FOLLOW
+  } else {
+    types::Lookupflags::empty()
+  }
+}
+
+fn descriptor_type(filetype: types::Filetype) -> filesystem::DescriptorType {
+  match filetype {
+    types::Filetype::BlockDevice => filesystem::DescriptorType::BlockDevice,
+    types::Filetype::CharacterDevice => filesystem::DescriptorType::CharacterDevice,
+    types::Filetype::Directory => filesystem::DescriptorType::Directory,
+    types::Filetype::RegularFile => filesystem::DescriptorType::RegularFile,
+    types::Filetype::SocketDgram | types::Filetype::SocketStream => {
+      filesystem::DescriptorType::Socket
+    }
+    types::Filetype::SymbolicLink => filesystem::DescriptorType::SymbolicLink,
+    types::Filetype::Unknown => filesystem::DescriptorType::Unknown,
+  }
+}
+
+fn descriptor_stat(stat: types::Filestat) -> filesystem::DescriptorStat {
+  filesystem::DescriptorStat {
+    type_: descriptor_type(stat.filetype),
+    link_count: stat.nlink,
+    size: stat.size,
+    data_access_timestamp: Some(datetime(stat.atim)),
+    data_modification_timestamp: Some(datetime(stat.mtim)),
+    status_change_timestamp: Some(datetime(stat.ctim)),
+  }
+}
+
+/// Returns the Preview1 flags and time for one timestamp, shifted to its access or modification
+/// position.
+fn new_timestamp(
+  timestamp: filesystem::NewTimestamp,
+  set: types::Fstflags,
+  now: types::Fstflags,
+) -> (types::Fstflags, u64) {
+  match timestamp {
+    filesystem::NewTimestamp::NoChange => (types::Fstflags::empty(), 0),
+    filesystem::NewTimestamp::Now => (now, 0),
+    filesystem::NewTimestamp::Timestamp(time) => (set, nanoseconds(time)),
+  }
+}
+
+fn set_times_flags(
+  access: filesystem::NewTimestamp,
+  modification: filesystem::NewTimestamp,
+) -> (types::Fstflags, u64, u64) {
+  let (access_flags, access) =
+    new_timestamp(access, types::Fstflags::ATIM, types::Fstflags::ATIM_NOW);
+  let (modification_flags, modification) = new_timestamp(
+    modification,
+    types::Fstflags::MTIM,
+    types::Fstflags::MTIM_NOW,
+  );
+  (access_flags | modification_flags, access, modification)
+}
+
+fn error_code(errno: types::Errno) -> filesystem::ErrorCode {
+  use filesystem::ErrorCode;
+  use types::Errno;
+  match errno {
+    Errno::Acces | Errno::Notcapable => ErrorCode::Access,
+    Errno::Again => ErrorCode::WouldBlock,
+    Errno::Already => ErrorCode::Already,
+    Errno::Badf => ErrorCode::BadDescriptor,
+    Errno::Busy => ErrorCode::Busy,
+    Errno::Deadlk => ErrorCode::Deadlock,
+    Errno::Dquot => ErrorCode::Quota,
+    Errno::Exist => ErrorCode::Exist,
+    Errno::Fbig => ErrorCode::FileTooLarge,
+    Errno::Ilseq => ErrorCode::IllegalByteSequence,
+    Errno::Inprogress => ErrorCode::InProgress,
+    Errno::Intr => ErrorCode::Interrupted,
+    Errno::Inval => ErrorCode::Invalid,
+    Errno::Isdir => ErrorCode::IsDirectory,
+    Errno::Loop => ErrorCode::Loop,
+    Errno::Mlink => ErrorCode::TooManyLinks,
+    Errno::Msgsize => ErrorCode::MessageSize,
+    Errno::Nametoolong => ErrorCode::NameTooLong,
+    Errno::Nodev => ErrorCode::NoDevice,
+    Errno::Noent => ErrorCode::NoEntry,
+    Errno::Nolck => ErrorCode::NoLock,
+    Errno::Nomem => ErrorCode::InsufficientMemory,
+    Errno::Nospc => ErrorCode::InsufficientSpace,
+    Errno::Notdir => ErrorCode::NotDirectory,
+    Errno::Notempty => ErrorCode::NotEmpty,
+    Errno::Notrecoverable => ErrorCode::NotRecoverable,
+    Errno::Nosys | Errno::Notsup => ErrorCode::Unsupported,
+    Errno::Notty => ErrorCode::NoTty,
+    Errno::Nxio => ErrorCode::NoSuchDevice,
+    Errno::Overflow => ErrorCode::Overflow,
+    Errno::Perm => ErrorCode::NotPermitted,
+    Errno::Pipe => ErrorCode::Pipe,
+    Errno::Rofs => ErrorCode::ReadOnly,
+    Errno::Spipe => ErrorCode::InvalidSeek,
+    Errno::Txtbsy => ErrorCode::TextFileBusy,
+    Errno::Xdev => ErrorCode::CrossDevice,
+    _ => ErrorCode::Io,
+  }
+}
+
+impl State {
+  fn resource<T: 'static>(&self, resource: &Resource<T>) -> WasiResult<&T> {
+    self.resources.get(resource).map_err(trap)
+  }
+
+  fn descriptor_fd(&self, descriptor: &Resource<OpenDescriptor>) -> WasiResult<types::Fd> {
+    Ok(self.resource(descriptor)?.fd.into())
+  }
+
+  /// Reads up to one copy buffer at `offset`, or at the descriptor's cursor.
+  async fn read_bytes(&mut self, fd: u32, offset: Option<u64>, len: u64) -> WasiResult<Vec<u8>> {
+    let capacity = len.min(WRITE_PERMIT) as u32;
+    let mut scratch = Scratch::default();
+    let buffer = scratch.reserve::<u8>(capacity)?;
+    let iovs = scratch.push(vec![types::Iovec {
+      buf: buffer.as_ptr(),
+      buf_len: capacity,
+    }])?;
+    let mut memory = scratch.memory();
+    let count = match offset {
+      Some(offset) => self.fd_pread(&mut memory, fd.into(), iovs, offset).await?,
+      None => self.fd_read(&mut memory, fd.into(), iovs).await?,
+    };
+    Ok(scratch.bytes(buffer, count))
+  }
+
+  /// Reads like [`Self::read_bytes`] but reports `EAGAIN` instead of waiting for a pipe.
+  async fn read_ready_bytes(&mut self, stream: InputStream, len: u64) -> WasiResult<Vec<u8>> {
+    if stream.offset.is_some() {
+      return self.read_bytes(stream.fd, stream.offset, len).await;
+    }
+    let descriptor = self.descriptor_mut(stream.fd)?;
+    let flags = descriptor.flags;
+    descriptor.flags |= types::Fdflags::NONBLOCK;
+    let result = self.read_bytes(stream.fd, None, len).await;
+    if let Ok(descriptor) = self.descriptor_mut(stream.fd) {
+      descriptor.flags = flags;
+    }
+    result
+  }
+
+  async fn write_bytes(&mut self, fd: u32, offset: Option<u64>, bytes: &[u8]) -> WasiResult<u32> {
+    let mut scratch = Scratch::default();
+    let buffer = scratch.push(bytes.to_vec())?;
+    let iovs = scratch.push(vec![types::Ciovec {
+      buf: buffer.as_ptr(),
+      buf_len: buffer.len(),
+    }])?;
+    let mut memory = scratch.memory();
+    match offset {
+      Some(offset) => self.fd_pwrite(&mut memory, fd.into(), iovs, offset).await,
+      None => self.fd_write(&mut memory, fd.into(), iovs).await,
+    }
+  }
+
+  async fn read_stream(
+    &mut self,
+    stream: Resource<InputStream>,
+    len: u64,
+    blocking: bool,
+  ) -> Result<Vec<u8>, StreamFailure> {
+    let input = *self.resource(&stream)?;
+    if len == 0 {
+      return Ok(Vec::new());
+    }
+    let bytes = if blocking {
+      self.read_bytes(input.fd, input.offset, len).await
+    } else {
+      match self.read_ready_bytes(input, len).await {
+        Err(error) if error.downcast_ref() == Some(&types::Errno::Again) => return Ok(Vec::new()),
+        result => result,
+      }
+    }?;
+    if bytes.is_empty() {
+      return Err(StreamFailure::Closed);
+    }
+    if let Some(offset) = input.offset {
+      self.resources.get_mut(&stream).map_err(trap)?.offset = Some(offset + bytes.len() as u64);
+    }
+    Ok(bytes)
+  }
+
+  async fn write_stream(
+    &mut self,
+    stream: Resource<OutputStream>,
+    contents: &[u8],
+  ) -> Result<(), StreamFailure> {
+    let output = *self.resource(&stream)?;
+    let mut scratch = Scratch::default();
+    let mut offset = match output.position {
+      WritePosition::Cursor => None,
+      WritePosition::Offset(offset) => Some(offset),
+      WritePosition::Append => Some(
+        self
+          .fd_filestat_get(&mut scratch.memory(), output.fd.into())
+          .await?
+          .size,
+      ),
+    };
+    let mut written = 0;
+    while written < contents.len() {
+      let count = self
+        .write_bytes(output.fd, offset, &contents[written..])
+        .await? as usize;
+      if count == 0 {
+        return Err(StreamFailure::Closed);
+      }
+      written += count;
+      offset = offset.map(|offset| offset + count as u64);
+    }
+    if let (WritePosition::Offset(_), Some(offset)) = (output.position, offset) {
+      self.resources.get_mut(&stream).map_err(trap)?.position = WritePosition::Offset(offset);
+    }
+    Ok(())
+  }
+
+  /// Returns the indices of ready pollables, waiting for one unless `nonblocking`.
+  async fn poll_ready(
+    &mut self,
+    pollables: &[Pollable],
+    nonblocking: bool,
+  ) -> WasiResult<Vec<u32>> {
+    let now = self.clock.nanoseconds(self.ticks());
+    let mut subscriptions = pollables
+      .iter()
+      .zip(0..)
+      .map(|(pollable, userdata)| types::Subscription {
+        userdata,
+        u: match *pollable {
+          Pollable::Ready => clock_subscription(0),
+          Pollable::Read(fd) => types::SubscriptionU::FdRead(types::SubscriptionFdReadwrite {
+            file_descriptor: fd.into(),
+          }),
+          Pollable::Write(fd) => types::SubscriptionU::FdWrite(types::SubscriptionFdReadwrite {
+            file_descriptor: fd.into(),
+          }),
+          Pollable::Instant(when) => clock_subscription(when.saturating_sub(now)),
+        },
+      })
+      .collect::<Vec<_>>();
+    if nonblocking {
+      subscriptions.push(types::Subscription {
+        userdata: u64::MAX,
+        u: clock_subscription(0),
+      });
+    }
+    let mut scratch = Scratch::default();
+    let count = u32::try_from(subscriptions.len()).map_err(|_| errno(types::Errno::Overflow))?;
+    let subscriptions = scratch.push(subscriptions)?;
+    let events = scratch.reserve::<types::Event>(count)?;
+    let ready = self
+      .poll_oneoff(
+        &mut scratch.memory(),
+        subscriptions.as_ptr(),
+        events.as_ptr(),
+        count,
+      )
+      .await?;
+    let memory = scratch.memory();
+    let mut indices = (0..ready)
+      .map(|index| Ok(memory.read(events.get(index).unwrap())?.userdata))
+      .filter(|userdata| !matches!(userdata, Ok(u64::MAX)))
+      .map(|userdata| userdata.map(|userdata| userdata as u32))
+      .collect::<WasiResult<Vec<_>>>()?;
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+  }
+
+  fn random_bytes(&mut self, len: u64) -> wasmtime::Result<Vec<u8>> {
+    use rand::TryRng;
+    if len > self.memory.limit as u64 {
+      return Err(wasmtime::format_err!(
+        "requested {len} random bytes beyond the memory limit"
+      ));
+    }
+    let mut bytes = vec![0; len as usize];
+    self.random.try_fill_bytes(&mut bytes)?;
+    Ok(bytes)
+  }
+}
+
+fn clock_subscription(timeout: u64) -> types::SubscriptionU {
+  types::SubscriptionU::Clock(types::SubscriptionClock {
+    id: types::Clockid::Monotonic,
+    timeout,
+    precision: 0,
+    flags: types::Subclockflags::empty(),
+  })
+}
+
+impl error::Host for State {}
+
+impl error::HostError for State {
+  async fn to_debug_string(&mut self, error: Resource<IoError>) -> wasmtime::Result<String> {
+    Ok(format!("{:?}", self.resources.get(&error)?.0))
+  }
+
+  async fn drop(&mut self, error: Resource<IoError>) -> wasmtime::Result<()> {
+    self.resources.delete(error)?;
+    Ok(())
+  }
+}
+
+impl poll::Host for State {
+  async fn poll(&mut self, pollables: Vec<Resource<Pollable>>) -> wasmtime::Result<Vec<u32>> {
+    if pollables.is_empty() {
+      return Err(wasmtime::format_err!(
+        "cannot poll an empty list of pollables"
+      ));
+    }
+    let pollables = pollables
+      .iter()
+      .map(|pollable| self.resources.get(pollable).copied())
+      .collect::<Result<Vec<_>, _>>()?;
+    self.poll_ready(&pollables, false).await.map_err(into_trap)
+  }
+}
+
+impl poll::HostPollable for State {
+  async fn ready(&mut self, pollable: Resource<Pollable>) -> wasmtime::Result<bool> {
+    let pollable = *self.resources.get(&pollable)?;
+    let ready = self
+      .poll_ready(&[pollable], true)
+      .await
+      .map_err(into_trap)?;
+    Ok(!ready.is_empty())
+  }
+
+  async fn block(&mut self, pollable: Resource<Pollable>) -> wasmtime::Result<()> {
+    let pollable = *self.resources.get(&pollable)?;
+    self
+      .poll_ready(&[pollable], false)
+      .await
+      .map_err(into_trap)?;
+    Ok(())
+  }
+
+  async fn drop(&mut self, pollable: Resource<Pollable>) -> wasmtime::Result<()> {
+    self.resources.delete(pollable)?;
+    Ok(())
+  }
+}
+
+impl streams::Host for State {
+  fn convert_stream_error(
+    &mut self,
+    error: StreamFailure,
+  ) -> wasmtime::Result<streams::StreamError> {
+    match error {
+      StreamFailure::Closed => Ok(streams::StreamError::Closed),
+      StreamFailure::Failed(error) => {
+        let error = IoError(error.downcast()?);
+        Ok(streams::StreamError::LastOperationFailed(
+          self.resources.push(error)?,
+        ))
+      }
+    }
+  }
+}
+
+impl streams::HostInputStream for State {
+  async fn read(
+    &mut self,
+    stream: Resource<InputStream>,
+    len: u64,
+  ) -> Result<Vec<u8>, StreamFailure> {
+    self.read_stream(stream, len, false).await
+  }
+
+  async fn blocking_read(
+    &mut self,
+    stream: Resource<InputStream>,
+    len: u64,
+  ) -> Result<Vec<u8>, StreamFailure> {
+    self.read_stream(stream, len, true).await
+  }
+
+  async fn skip(&mut self, stream: Resource<InputStream>, len: u64) -> Result<u64, StreamFailure> {
+    Ok(self.read_stream(stream, len, false).await?.len() as u64)
+  }
+
+  async fn blocking_skip(
+    &mut self,
+    stream: Resource<InputStream>,
+    len: u64,
+  ) -> Result<u64, StreamFailure> {
+    Ok(self.read_stream(stream, len, true).await?.len() as u64)
+  }
+
+  async fn subscribe(
+    &mut self,
+    stream: Resource<InputStream>,
+  ) -> wasmtime::Result<Resource<Pollable>> {
+    let pollable = match *self.resources.get(&stream)? {
+      InputStream {
+        offset: Some(_), ..
+      } => Pollable::Ready,
+      InputStream { fd, offset: None } => Pollable::Read(fd),
+    };
+    Ok(self.resources.push(pollable)?)
+  }
+
+  async fn drop(&mut self, stream: Resource<InputStream>) -> wasmtime::Result<()> {
+    self.resources.delete(stream)?;
+    Ok(())
+  }
+}
+
+impl streams::HostOutputStream for State {
+  async fn check_write(&mut self, stream: Resource<OutputStream>) -> Result<u64, StreamFailure> {
+    let output = *self.resource(&stream)?;
+    let capacity = self.write_capacity(output.fd)?;
+    Ok(capacity.map_or(WRITE_PERMIT, |capacity| capacity.min(WRITE_PERMIT)))
+  }
+
+  async fn write(
+    &mut self,
+    stream: Resource<OutputStream>,
+    contents: Vec<u8>,
+  ) -> Result<(), StreamFailure> {
+    self.write_stream(stream, &contents).await
+  }
+
+  async fn blocking_write_and_flush(
+    &mut self,
+    stream: Resource<OutputStream>,
+    contents: Vec<u8>,
+  ) -> Result<(), StreamFailure> {
+    self.write_stream(stream, &contents).await
+  }
+
+  async fn flush(&mut self, stream: Resource<OutputStream>) -> Result<(), StreamFailure> {
+    self.resource(&stream)?;
+    Ok(())
+  }
+
+  async fn blocking_flush(&mut self, stream: Resource<OutputStream>) -> Result<(), StreamFailure> {
+    self.resource(&stream)?;
+    Ok(())
+  }
+
+  async fn subscribe(
+    &mut self,
+    stream: Resource<OutputStream>,
+  ) -> wasmtime::Result<Resource<Pollable>> {
+    let pollable = Pollable::Write(self.resources.get(&stream)?.fd);
+    Ok(self.resources.push(pollable)?)
+  }
+
+  async fn write_zeroes(
+    &mut self,
+    stream: Resource<OutputStream>,
+    len: u64,
+  ) -> Result<(), StreamFailure> {
+    let len = usize::try_from(len).map_err(|_| errno(types::Errno::Overflow))?;
+    self.write_stream(stream, &vec![0; len]).await
+  }
+
+  async fn blocking_write_zeroes_and_flush(
+    &mut self,
+    stream: Resource<OutputStream>,
+    len: u64,
+  ) -> Result<(), StreamFailure> {
+    streams::HostOutputStream::write_zeroes(self, stream, len).await
+  }
+
+  async fn splice(
+    &mut self,
+    stream: Resource<OutputStream>,
+    source: Resource<InputStream>,
+    len: u64,
+  ) -> Result<u64, StreamFailure> {
+    let bytes = self.read_stream(source, len, false).await?;
+    self.write_stream(stream, &bytes).await?;
+    Ok(bytes.len() as u64)
+  }
+
+  async fn blocking_splice(
+    &mut self,
+    stream: Resource<OutputStream>,
+    source: Resource<InputStream>,
+    len: u64,
+  ) -> Result<u64, StreamFailure> {
+    let bytes = self.read_stream(source, len, true).await?;
+    self.write_stream(stream, &bytes).await?;
+    Ok(bytes.len() as u64)
+  }
+
+  async fn drop(&mut self, stream: Resource<OutputStream>) -> wasmtime::Result<()> {
+    self.resources.delete(stream)?;
+    Ok(())
+  }
+}
+
+impl monotonic_clock::Host for State {
+  async fn now(&mut self) -> wasmtime::Result<u64> {
+    Ok(self.clock.nanoseconds(self.ticks()))
+  }
+
+  async fn resolution(&mut self) -> wasmtime::Result<u64> {
+    Ok(self.clock.resolution())
+  }
+
+  async fn subscribe_instant(&mut self, when: u64) -> wasmtime::Result<Resource<Pollable>> {
+    Ok(self.resources.push(Pollable::Instant(when))?)
+  }
+
+  async fn subscribe_duration(&mut self, duration: u64) -> wasmtime::Result<Resource<Pollable>> {
+    let when = self
+      .clock
+      .nanoseconds(self.ticks())
+      .saturating_add(duration);
+    Ok(self.resources.push(Pollable::Instant(when))?)
+  }
+}
+
+impl wall_clock::Host for State {
+  async fn now(&mut self) -> wasmtime::Result<wall_clock::Datetime> {
+    Ok(datetime(self.clock.nanoseconds(self.ticks())))
+  }
+
+  async fn resolution(&mut self) -> wasmtime::Result<wall_clock::Datetime> {
+    Ok(datetime(self.clock.resolution()))
+  }
+}
+
+impl random::Host for State {
+  async fn get_random_bytes(&mut self, len: u64) -> wasmtime::Result<Vec<u8>> {
+    self.random_bytes(len)
+  }
+
+  async fn get_random_u64(&mut self) -> wasmtime::Result<u64> {
+    Ok(u64::from_le_bytes(
+      self.random_bytes(8)?.try_into().unwrap(),
+    ))
+  }
+}
+
+impl insecure::Host for State {
+  async fn get_insecure_random_bytes(&mut self, len: u64) -> wasmtime::Result<Vec<u8>> {
+    self.random_bytes(len)
+  }
+
+  async fn get_insecure_random_u64(&mut self) -> wasmtime::Result<u64> {
+    random::Host::get_random_u64(self).await
+  }
+}
+
+impl insecure_seed::Host for State {
+  async fn insecure_seed(&mut self) -> wasmtime::Result<(u64, u64)> {
+    Ok((
+      random::Host::get_random_u64(self).await?,
+      random::Host::get_random_u64(self).await?,
+    ))
+  }
+}
+
+impl environment::Host for State {
+  async fn get_environment(&mut self) -> wasmtime::Result<Vec<(String, String)>> {
+    Ok(
+      self
+        .environment
+        .iter()
+        .map(|variable| {
+          let variable = String::from_utf8_lossy(variable.strip_suffix(b"\0").unwrap_or(variable));
+          let (name, value) = variable.split_once('=').unwrap_or((&variable, ""));
+          (name.to_owned(), value.to_owned())
+        })
+        .collect(),
+    )
+  }
+
+  async fn get_arguments(&mut self) -> wasmtime::Result<Vec<String>> {
+    Ok(
+      self
+        .arguments
+        .iter()
+        .map(|argument| {
+          String::from_utf8_lossy(argument.strip_suffix(b"\0").unwrap_or(argument)).into_owned()
+        })
+        .collect(),
+    )
+  }
+
+  async fn initial_cwd(&mut self) -> wasmtime::Result<Option<String>> {
+    Ok(None)
+  }
+}
+
+impl exit::Host for State {
+  async fn exit(&mut self, status: Result<(), ()>) -> wasmtime::Result<()> {
+    Err(ProcessExit(i32::from(status.is_err())).into())
+  }
+}
+
+impl stdin::Host for State {
+  async fn get_stdin(&mut self) -> wasmtime::Result<Resource<InputStream>> {
+    Ok(self.resources.push(InputStream {
+      fd: 0,
+      offset: None,
+    })?)
+  }
+}
+
+impl stdout::Host for State {
+  async fn get_stdout(&mut self) -> wasmtime::Result<Resource<OutputStream>> {
+    Ok(self.resources.push(OutputStream {
+      fd: 1,
+      position: WritePosition::Cursor,
+    })?)
+  }
+}
+
+impl stderr::Host for State {
+  async fn get_stderr(&mut self) -> wasmtime::Result<Resource<OutputStream>> {
+    Ok(self.resources.push(OutputStream {
+      fd: 2,
+      position: WritePosition::Cursor,
+    })?)
+  }
+}
+
+impl terminal_input::Host for State {}
+
+impl terminal_input::HostTerminalInput for State {
+  async fn drop(
+    &mut self,
+    terminal: Resource<terminal_input::TerminalInput>,
+  ) -> wasmtime::Result<()> {
+    self.resources.delete(terminal)?;
+    Ok(())
+  }
+}
+
+impl terminal_output::Host for State {}
+
+impl terminal_output::HostTerminalOutput for State {
+  async fn drop(
+    &mut self,
+    terminal: Resource<terminal_output::TerminalOutput>,
+  ) -> wasmtime::Result<()> {
+    self.resources.delete(terminal)?;
+    Ok(())
+  }
+}
+
+impl terminal_stdin::Host for State {
+  async fn get_terminal_stdin(
+    &mut self,
+  ) -> wasmtime::Result<Option<Resource<terminal_input::TerminalInput>>> {
+    Ok(None)
+  }
+}
+
+impl terminal_stdout::Host for State {
+  async fn get_terminal_stdout(
+    &mut self,
+  ) -> wasmtime::Result<Option<Resource<terminal_output::TerminalOutput>>> {
+    Ok(None)
+  }
+}
+
+impl terminal_stderr::Host for State {
+  async fn get_terminal_stderr(
+    &mut self,
+  ) -> wasmtime::Result<Option<Resource<terminal_output::TerminalOutput>>> {
+    Ok(None)
+  }
+}
+
+impl preopens::Host for State {
+  async fn get_directories(&mut self) -> wasmtime::Result<Vec<(Resource<OpenDescriptor>, String)>> {
+    let preopens = self
+      .descriptors
+      .iter()
+      .filter_map(|(fd, descriptor)| Some((*fd, descriptor.preopen.clone()?)))
+      .collect::<Vec<_>>();
+    preopens
+      .into_iter()
+      .map(|(fd, name)| {
+        let descriptor = OpenDescriptor { fd, preopen: true };
+        Ok((self.resources.push(descriptor)?, name))
+      })
+      .collect()
+  }
+}
+
+impl filesystem::Host for State {
+  fn convert_error_code(&mut self, error: types::Error) -> wasmtime::Result<filesystem::ErrorCode> {
+    Ok(error_code(error.downcast()?))
+  }
+
+  async fn filesystem_error_code(
+    &mut self,
+    error: Resource<IoError>,
+  ) -> wasmtime::Result<Option<filesystem::ErrorCode>> {
+    Ok(Some(error_code(self.resources.get(&error)?.0)))
+  }
+}
+
+impl filesystem::HostDirectoryEntryStream for State {
+  async fn read_directory_entry(
+    &mut self,
+    entries: Resource<DirectoryEntries>,
+  ) -> WasiResult<Option<filesystem::DirectoryEntry>> {
+    Ok(
+      self
+        .resources
+        .get_mut(&entries)
+        .map_err(trap)?
+        .0
+        .pop_front(),
+    )
+  }
+
+  async fn drop(&mut self, entries: Resource<DirectoryEntries>) -> wasmtime::Result<()> {
+    self.resources.delete(entries)?;
+    Ok(())
+  }
+}
+
+impl filesystem::HostDescriptor for State {
+  async fn read_via_stream(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    offset: u64,
+  ) -> WasiResult<Resource<InputStream>> {
+    let fd = self.resource(&descriptor)?.fd;
+    self
+      .resources
+      .push(InputStream {
+        fd,
+        offset: Some(offset),
+      })
+      .map_err(trap)
+  }
+
+  async fn write_via_stream(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    offset: u64,
+  ) -> WasiResult<Resource<OutputStream>> {
+    let fd = self.resource(&descriptor)?.fd;
+    self
+      .resources
+      .push(OutputStream {
+        fd,
+        position: WritePosition::Offset(offset),
+      })
+      .map_err(trap)
+  }
+
+  async fn append_via_stream(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<Resource<OutputStream>> {
+    let fd = self.resource(&descriptor)?.fd;
+    self
+      .resources
+      .push(OutputStream {
+        fd,
+        position: WritePosition::Append,
+      })
+      .map_err(trap)
+  }
+
+  async fn advise(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    offset: u64,
+    length: u64,
+    advice: filesystem::Advice,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let advice = match advice {
+      filesystem::Advice::Normal => types::Advice::Normal,
+      filesystem::Advice::Sequential => types::Advice::Sequential,
+      filesystem::Advice::Random => types::Advice::Random,
+      filesystem::Advice::WillNeed => types::Advice::Willneed,
+      filesystem::Advice::DontNeed => types::Advice::Dontneed,
+      filesystem::Advice::NoReuse => types::Advice::Noreuse,
+    };
+    self
+      .fd_advise(&mut Scratch::default().memory(), fd, offset, length, advice)
+      .await
+  }
+
+  async fn sync_data(&mut self, descriptor: Resource<OpenDescriptor>) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    self.fd_datasync(&mut Scratch::default().memory(), fd).await
+  }
+
+  async fn get_flags(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<filesystem::DescriptorFlags> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let stat = self
+      .fd_fdstat_get(&mut Scratch::default().memory(), fd)
+      .await?;
+    let mut flags = filesystem::DescriptorFlags::empty();
+    if stat
+      .fs_rights_base
+      .intersects(types::Rights::FD_READ | types::Rights::FD_READDIR)
+    {
+      flags |= filesystem::DescriptorFlags::READ;
+    }
+    if stat.fs_rights_base.contains(types::Rights::FD_WRITE) {
+      flags |= filesystem::DescriptorFlags::WRITE;
+    }
+    if stat.fs_rights_base.intersects(
+      types::Rights::PATH_CREATE_DIRECTORY
+        | types::Rights::PATH_CREATE_FILE
+        | types::Rights::PATH_RENAME_SOURCE
+        | types::Rights::PATH_UNLINK_FILE
+        | types::Rights::PATH_REMOVE_DIRECTORY,
+    ) {
+      flags |= filesystem::DescriptorFlags::MUTATE_DIRECTORY;
+    }
+    if stat.fs_flags.contains(types::Fdflags::SYNC) {
+      flags |= filesystem::DescriptorFlags::FILE_INTEGRITY_SYNC;
+    }
+    if stat.fs_flags.contains(types::Fdflags::DSYNC) {
+      flags |= filesystem::DescriptorFlags::DATA_INTEGRITY_SYNC;
+    }
+    if stat.fs_flags.contains(types::Fdflags::RSYNC) {
+      flags |= filesystem::DescriptorFlags::REQUESTED_WRITE_SYNC;
+    }
+    Ok(flags)
+  }
+
+  async fn get_type(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<filesystem::DescriptorType> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let stat = self
+      .fd_fdstat_get(&mut Scratch::default().memory(), fd)
+      .await?;
+    Ok(descriptor_type(stat.fs_filetype))
+  }
+
+  async fn set_size(&mut self, descriptor: Resource<OpenDescriptor>, size: u64) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    self
+      .fd_filestat_set_size(&mut Scratch::default().memory(), fd, size)
+      .await
+  }
+
+  async fn set_times(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    access: filesystem::NewTimestamp,
+    modification: filesystem::NewTimestamp,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let (flags, access, modification) = set_times_flags(access, modification);
+    self
+      .fd_filestat_set_times(
+        &mut Scratch::default().memory(),
+        fd,
+        access,
+        modification,
+        flags,
+      )
+      .await
+  }
+
+  async fn read(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    length: u64,
+    offset: u64,
+  ) -> WasiResult<(Vec<u8>, bool)> {
+    let fd = self.resource(&descriptor)?.fd;
+    if length == 0 {
+      return Ok((Vec::new(), false));
+    }
+    let bytes = self.read_bytes(fd, Some(offset), length).await?;
+    let end = bytes.is_empty();
+    Ok((bytes, end))
+  }
+
+  async fn write(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    buffer: Vec<u8>,
+    offset: u64,
+  ) -> WasiResult<u64> {
+    let fd = self.resource(&descriptor)?.fd;
+    Ok(u64::from(
+      self.write_bytes(fd, Some(offset), &buffer).await?,
+    ))
+  }
+
+  async fn read_directory(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<Resource<DirectoryEntries>> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let header = types::Dirent::guest_size();
+    let mut entries = VecDeque::new();
+    let mut cookie = 0;
+    loop {
+      let mut scratch = Scratch::default();
+      let buffer = scratch.reserve::<u8>(WRITE_PERMIT as u32)?;
+      let used = self
+        .fd_readdir(
+          &mut scratch.memory(),
+          fd,
+          buffer.as_ptr(),
+          buffer.len(),
+          cookie,
+        )
+        .await?;
+      let memory = scratch.memory();
+      let mut position = 0;
+      let mut progressed = false;
+      while position + header <= used {
+        let dirent = memory.read(buffer.as_ptr().add(position)?.cast::<types::Dirent>())?;
+        let end = position + header + dirent.d_namlen;
+        if end > used {
+          break;
+        }
+        let name = memory.to_vec(
+          buffer
+            .as_ptr()
+            .add(position + header)?
+            .as_array(dirent.d_namlen),
+        )?;
+        let name = String::from_utf8(name).map_err(|_| errno(types::Errno::Ilseq))?;
+        if name != "." && name != ".." {
+          entries.push_back(filesystem::DirectoryEntry {
+            type_: descriptor_type(dirent.d_type),
+            name,
+          });
+        }
+        cookie = dirent.d_next;
+        position = end;
+        progressed = true;
+      }
+      if used < buffer.len() || !progressed {
+        break;
+      }
+    }
+    self.resources.push(DirectoryEntries(entries)).map_err(trap)
+  }
+
+  async fn sync(&mut self, descriptor: Resource<OpenDescriptor>) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    self.fd_sync(&mut Scratch::default().memory(), fd).await
+  }
+
+  async fn create_directory_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    self
+      .path_create_directory(&mut scratch.memory(), fd, path)
+      .await
+  }
+
+  async fn stat(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<filesystem::DescriptorStat> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let stat = self
+      .fd_filestat_get(&mut Scratch::default().memory(), fd)
+      .await?;
+    Ok(descriptor_stat(stat))
+  }
+
+  async fn stat_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    flags: filesystem::PathFlags,
+    path: String,
+  ) -> WasiResult<filesystem::DescriptorStat> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    let stat = self
+      .path_filestat_get(&mut scratch.memory(), fd, lookup_flags(flags), path)
+      .await?;
+    Ok(descriptor_stat(stat))
+  }
+
+  async fn set_times_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    flags: filesystem::PathFlags,
+    path: String,
+    access: filesystem::NewTimestamp,
+    modification: filesystem::NewTimestamp,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    let (time_flags, access, modification) = set_times_flags(access, modification);
+    self
+      .path_filestat_set_times(
+        &mut scratch.memory(),
+        fd,
+        lookup_flags(flags),
+        path,
+        access,
+        modification,
+        time_flags,
+      )
+      .await
+  }
+
+  async fn link_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    old_flags: filesystem::PathFlags,
+    old_path: String,
+    new_descriptor: Resource<OpenDescriptor>,
+    new_path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let new_fd = self.descriptor_fd(&new_descriptor)?;
+    let mut scratch = Scratch::default();
+    let old_path = scratch.push_str(&old_path)?;
+    let new_path = scratch.push_str(&new_path)?;
+    self
+      .path_link(
+        &mut scratch.memory(),
+        fd,
+        lookup_flags(old_flags),
+        old_path,
+        new_fd,
+        new_path,
+      )
+      .await
+  }
+
+  async fn open_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    path_flags: filesystem::PathFlags,
+    path: String,
+    open_flags: filesystem::OpenFlags,
+    flags: filesystem::DescriptorFlags,
+  ) -> WasiResult<Resource<OpenDescriptor>> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let parent = self.fd_fdstat_get(&mut scratch.memory(), fd).await?;
+    // Request rights exactly as wasi-libc does for the matching `openat` access mode, so
+    // components and modules open the same files.
+    let mut requested = !(types::Rights::FD_DATASYNC
+      | types::Rights::FD_READ
+      | types::Rights::FD_WRITE
+      | types::Rights::FD_ALLOCATE
+      | types::Rights::FD_READDIR
+      | types::Rights::FD_FILESTAT_SET_SIZE);
+    if flags.contains(filesystem::DescriptorFlags::READ) {
+      requested |= types::Rights::FD_READ | types::Rights::FD_READDIR;
+    }
+    if flags.contains(filesystem::DescriptorFlags::WRITE) {
+      requested |= types::Rights::FD_DATASYNC
+        | types::Rights::FD_WRITE
+        | types::Rights::FD_ALLOCATE
+        | types::Rights::FD_FILESTAT_SET_SIZE;
+    }
+    let mut oflags = types::Oflags::empty();
+    for (open_flag, oflag) in [
+      (filesystem::OpenFlags::CREATE, types::Oflags::CREAT),
+      (filesystem::OpenFlags::DIRECTORY, types::Oflags::DIRECTORY),
+      (filesystem::OpenFlags::EXCLUSIVE, types::Oflags::EXCL),
+      (filesystem::OpenFlags::TRUNCATE, types::Oflags::TRUNC),
+    ] {
+      if open_flags.contains(open_flag) {
+        oflags |= oflag;
+      }
+    }
+    let mut fdflags = types::Fdflags::empty();
+    for (flag, fdflag) in [
+      (
+        filesystem::DescriptorFlags::FILE_INTEGRITY_SYNC,
+        types::Fdflags::SYNC,
+      ),
+      (
+        filesystem::DescriptorFlags::DATA_INTEGRITY_SYNC,
+        types::Fdflags::DSYNC,
+      ),
+      (
+        filesystem::DescriptorFlags::REQUESTED_WRITE_SYNC,
+        types::Fdflags::RSYNC,
+      ),
+    ] {
+      if flags.contains(flag) {
+        fdflags |= fdflag;
+      }
+    }
+    let path = scratch.push_str(&path)?;
+    let opened = self
+      .path_open(
+        &mut scratch.memory(),
+        fd,
+        lookup_flags(path_flags),
+        path,
+        oflags,
+        requested & parent.fs_rights_inheriting,
+        parent.fs_rights_inheriting,
+        fdflags,
+      )
+      .await?;
+    self
+      .resources
+      .push(OpenDescriptor {
+        fd: opened.into(),
+        preopen: false,
+      })
+      .map_err(trap)
+  }
+
+  async fn readlink_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    path: String,
+  ) -> WasiResult<String> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut capacity = 256;
+    loop {
+      let mut scratch = Scratch::default();
+      let path = scratch.push_str(&path)?;
+      let buffer = scratch.reserve::<u8>(capacity)?;
+      let count = self
+        .path_readlink(&mut scratch.memory(), fd, path, buffer.as_ptr(), capacity)
+        .await?;
+      if count < capacity {
+        return String::from_utf8(scratch.bytes(buffer, count))
+          .map_err(|_| errno(types::Errno::Ilseq));
+      }
+      capacity = capacity
+        .checked_mul(2)
+        .ok_or_else(|| errno(types::Errno::Nametoolong))?;
+    }
+  }
+
+  async fn remove_directory_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    self
+      .path_remove_directory(&mut scratch.memory(), fd, path)
+      .await
+  }
+
+  async fn rename_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    old_path: String,
+    new_descriptor: Resource<OpenDescriptor>,
+    new_path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let new_fd = self.descriptor_fd(&new_descriptor)?;
+    let mut scratch = Scratch::default();
+    let old_path = scratch.push_str(&old_path)?;
+    let new_path = scratch.push_str(&new_path)?;
+    self
+      .path_rename(&mut scratch.memory(), fd, old_path, new_fd, new_path)
+      .await
+  }
+
+  async fn symlink_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    old_path: String,
+    new_path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let old_path = scratch.push_str(&old_path)?;
+    let new_path = scratch.push_str(&new_path)?;
+    self
+      .path_symlink(&mut scratch.memory(), old_path, fd, new_path)
+      .await
+  }
+
+  async fn unlink_file_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    path: String,
+  ) -> WasiResult<()> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    self.path_unlink_file(&mut scratch.memory(), fd, path).await
+  }
+
+  async fn is_same_object(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    other: Resource<OpenDescriptor>,
+  ) -> wasmtime::Result<bool> {
+    let first = filesystem::HostDescriptor::metadata_hash(self, descriptor).await;
+    let second = filesystem::HostDescriptor::metadata_hash(self, other).await;
+    Ok(match (first, second) {
+      (Ok(first), Ok(second)) => (first.lower, first.upper) == (second.lower, second.upper),
+      _ => false,
+    })
+  }
+
+  async fn metadata_hash(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+  ) -> WasiResult<filesystem::MetadataHashValue> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let stat = self
+      .fd_filestat_get(&mut Scratch::default().memory(), fd)
+      .await?;
+    Ok(filesystem::MetadataHashValue {
+      lower: stat.ino,
+      upper: stat.dev,
+    })
+  }
+
+  async fn metadata_hash_at(
+    &mut self,
+    descriptor: Resource<OpenDescriptor>,
+    flags: filesystem::PathFlags,
+    path: String,
+  ) -> WasiResult<filesystem::MetadataHashValue> {
+    let fd = self.descriptor_fd(&descriptor)?;
+    let mut scratch = Scratch::default();
+    let path = scratch.push_str(&path)?;
+    let stat = self
+      .path_filestat_get(&mut scratch.memory(), fd, lookup_flags(flags), path)
+      .await?;
+    Ok(filesystem::MetadataHashValue {
+      lower: stat.ino,
+      upper: stat.dev,
+    })
+  }
+
+  async fn drop(&mut self, descriptor: Resource<OpenDescriptor>) -> wasmtime::Result<()> {
+    let descriptor = self.resources.delete(descriptor)?;
+    if !descriptor.preopen {
+      // Closing can only fail for an fd the component already lost, which leaves nothing to free.
+      let _ = self
+        .fd_close(&mut Scratch::default().memory(), descriptor.fd.into())
+        .await;
+    }
+    Ok(())
+  }
+}
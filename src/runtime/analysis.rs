@@ -15,30 +15,38 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
+use std::os::unix::net::UnixListener;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use anyhow::{Context, Result, bail};
 use rayon::{ThreadPoolBuilder, prelude::*};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use super::artifact::realize_artifact;
+use super::cache::{CacheKey, CacheKeyBuilder};
+use super::prediction::{
+  PredictionChecker, PredictionQuery, complete_prediction_outcomes, ensure_predictions_hold,
+};
 use super::types::{
   ArtifactSpec, CheckerReport, CheckerRuntimeData, JudgeReport, JudgeStatus, PreparedSolutionSpec,
-  ProblemSpec, RuntimeData, RuntimeOptions, RuntimeSolutionData, RuntimeTestCaseData,
-  RuntimeTestCaseFiles, ScoringMethod, SolutionSpec, SubtaskRuntimeReport, TestCaseSpec,
-  ValidationReport, ValidatorRuntimeData,
+  ProblemSpec, ProgramSpec, RuntimeData, RuntimeOptions, RuntimeSolutionData, RuntimeTestCaseData,
+  RuntimeTestCaseFiles, ScoringMethod, SolutionSpec, SubtaskRuntimeReport, SubtaskSpec,
+  TestCaseSpec, ValidationReport, ValidatorRuntimeData,
 };
 use super::workspace::RuntimeWorkspace;
 use crate::interactive::{ProblemProgressHandle, TaskHandle, TaskItemReport, TaskKind};
 use crate::runner::{
-  DirectoryBinding, DirectoryPermissions, File, FileBinding, FilePermissions, FileSizeLimit,
-  FileSystem, InitialDescriptor, ProgramRequest, ProgramResult, RunStatus, SessionRequest,
-  TOOL_MEMORY_LIMIT, TOOL_TICK_LIMIT, ToolLimit, run_session,
+  Clock, DirectoryBinding, DirectoryPermissions, File, FileBinding, FilePermissions, FileSizeLimit,
+  FileSystem, InitialDescriptor, IoCost, ProgramRequest, ProgramResult, RunStatus,
+  SESSION_SOCKET_ENVIRONMENT, SessionRequest, TOOL_MEMORY_LIMIT, TOOL_TICK_LIMIT, ToolLimit,
+  run_session, serve_socket,
 };
 
-type TestCaseRunMap = BTreeMap<String, (RuntimeTestCaseData, BTreeMap<String, JudgeReport>)>;
+/// Validated testcases with their judge reports, or `None` when the test selection skipped them.
+type TestCaseRunMap =
+  BTreeMap<String, (RuntimeTestCaseData, Option<BTreeMap<String, JudgeReport>>)>;
 type TestCaseTraitsMap = BTreeMap<String, BTreeMap<String, bool>>;
 
 struct JudgerInvocation<'a> {
@@ -57,6 +65,7 @@ struct JudgerInvocation<'a> {
 struct LiveScoreState {
   per_solution_reports: BTreeMap<String, BTreeMap<String, JudgeReport>>,
   test_case_traits: TestCaseTraitsMap,
+  submitted_predictions: BTreeSet<(String, usize)>,
 }
 
 #[derive(Clone)]
@@ -136,6 +145,7 @@ fn analyze_problem_in_pool(
   workspace: &RuntimeWorkspace,
   options: &RuntimeOptions,
 ) -> Result<RuntimeData> {
+  options.test_selection.check(problem)?;
   let judged_solutions = problem
     .solutions
     .iter()
@@ -172,6 +182,16 @@ fn analyze_problem_in_pool(
       )
     })?;
 
+  let predictions = options
+    .prediction_source
+    .clone()
+    .filter(|_| {
+      judged_solutions
+        .iter()
+        .any(|solution| !solution.subtask_predictions.is_empty())
+    })
+    .map(|source| PredictionChecker::spawn(source.nix_evaluator(), options.clone()));
+
   let (validator_test_results, (checker_test_results, test_case_outputs)) = rayon::join(
     || run_validator_tests(problem, workspace, options),
     || {
@@ -191,6 +211,7 @@ fn analyze_problem_in_pool(
             workspace,
             &prepared_judged_solutions,
             main_solution,
+            predictions.as_ref(),
             options,
           )
         },
@@ -198,23 +219,44 @@ fn analyze_problem_in_pool(
     },
   );
 
+  let checked_predictions = predictions
+    .map(PredictionChecker::finish)
+    .unwrap_or_default();
+  if options.stop_on_failure {
+    // A violated prediction is what stopped sibling work, so report it over their cancellations.
+    ensure_predictions_hold(&problem.name, &checked_predictions)?;
+  }
+
   let validator_test_results = validator_test_results?;
   let checker_test_results = checker_test_results?;
   let test_case_outputs = test_case_outputs?;
 
-  let test_case_runtime = test_case_outputs
+  // Testcases skipped by a subtask filter keep their traits so subtasks can still report them as
+  // skipped. Those excluded by name were never validated and have no traits.
+  let test_case_traits = test_case_outputs
     .iter()
-    .map(|(test_case_name, (runtime, _))| (test_case_name.clone(), runtime.clone()))
+    .map(|(name, (test_case, _))| (name.clone(), test_case.input_validation.traits.clone()))
     .collect::<BTreeMap<_, _>>();
-
-  let solution_reports_by_test_case = test_case_outputs
-    .into_iter()
-    .map(|(test_case_name, (_, reports))| (test_case_name, reports))
-    .collect::<BTreeMap<_, _>>();
-  let test_case_traits = test_case_runtime
+  let skipped_test_cases = problem
+    .test_cases
     .iter()
-    .map(|(name, test_case)| (name.clone(), test_case.input_validation.traits.clone()))
-    .collect::<BTreeMap<_, _>>();
+    .filter(|test_case| {
+      test_case_outputs
+        .get(&test_case.name)
+        .is_none_or(|(_, reports)| reports.is_none())
+    })
+    .map(|test_case| test_case.name.clone())
+    .collect::<BTreeSet<_>>();
+  let (test_case_runtime, solution_reports_by_test_case): (BTreeMap<_, _>, BTreeMap<_, _>) =
+    test_case_outputs
+      .into_iter()
+      .filter_map(|(test_case_name, (runtime, reports))| {
+        Some((
+          (test_case_name.clone(), runtime),
+          (test_case_name, reports?),
+        ))
+      })
+      .unzip();
 
   let solutions_runtime = prepared_judged_solutions
     .iter()
@@ -232,8 +274,12 @@ fn analyze_problem_in_pool(
         })
         .collect::<Result<BTreeMap<_, _>>>()?;
 
-      let subtask_results =
-        aggregate_subtask_results(problem, &test_case_results, &test_case_traits);
+      let subtask_results = aggregate_subtask_results(
+        problem,
+        &test_case_results,
+        &test_case_traits,
+        &skipped_test_cases,
+      );
       let score = subtask_results
         .iter()
         .map(|result| result.scaled_score)
@@ -249,6 +295,9 @@ fn analyze_problem_in_pool(
     })
     .collect::<Result<BTreeMap<_, _>>>()?;
 
+  let subtask_predictions =
+    complete_prediction_outcomes(checked_predictions, &judged_solutions, &solutions_runtime);
+
   Ok(RuntimeData {
     checker: CheckerRuntimeData {
       test_inputs: checker_test_results
@@ -261,6 +310,7 @@ fn analyze_problem_in_pool(
         .collect(),
     },
     test_cases: test_case_runtime,
+    skipped_test_cases,
     validator: ValidatorRuntimeData {
       test_inputs: validator_test_results
         .iter()
@@ -272,6 +322,7 @@ fn analyze_problem_in_pool(
         .collect(),
     },
     solutions: solutions_runtime,
+    subtask_predictions,
   })
 }
 
@@ -303,7 +354,7 @@ fn run_validator_tests(
         test.arguments.as_deref(),
         &format!("validator-test-{}", test.name),
       )?;
-      let report = run_validator(problem, &input_path, 1)?;
+      let report = cached_validator(problem, &input_path, 1, options)?;
       log_validation_result("Finished validator test", &test.name, &report);
       if let Some(guard) = guard {
         guard.finish(
@@ -372,10 +423,13 @@ fn run_checker_tests(
             input_file: Some(input_path.to_string_lossy().into_owned()),
             tick_limit: problem.tick_limit,
             memory_limit: problem.memory_limit,
+            stack_limit: problem.stack_limit,
+            io_cost: problem.io_cost,
             groups: Vec::new(),
             trait_hints: BTreeMap::new(),
             generator: None,
             arguments: None,
+            random_seed: None,
           };
           let outputs_dir = run_generate_outputs(
             problem,
@@ -405,10 +459,13 @@ fn run_checker_tests(
         input_file: Some(input_path.to_string_lossy().into_owned()),
         tick_limit: problem.tick_limit,
         memory_limit: problem.memory_limit,
+        stack_limit: problem.stack_limit,
+        io_cost: problem.io_cost,
         groups: Vec::new(),
         trait_hints: BTreeMap::new(),
         generator: None,
         arguments: None,
+        random_seed: None,
       };
       let answer_dir = run_generate_outputs(
         problem,
@@ -417,11 +474,12 @@ fn run_checker_tests(
         &main_solution.prepared,
         workspace,
       )?;
-      let report = run_checker(
+      let report = cached_checker(
         problem,
         &input_path,
         &output_path,
         &answer_dir.join(&test.output_name),
+        options,
       )?;
       log_checker_result("Finished checker test", &test.name, &report);
       if let Some(guard) = guard {
@@ -455,6 +513,7 @@ fn run_test_cases(
   workspace: &RuntimeWorkspace,
   solutions: &[PreparedSolutionEntry],
   main_solution: &PreparedSolutionEntry,
+  predictions: Option<&PredictionChecker>,
   options: &RuntimeOptions,
 ) -> Result<TestCaseRunMap> {
   let progress = Some(&options.progress);
@@ -479,16 +538,47 @@ fn run_test_cases(
         .collect::<BTreeMap<_, _>>()
     })
   };
+  let solution_keys = match &options.result_cache {
+    Some(_) => solutions
+      .par_iter()
+      .filter_map(|solution| {
+        let key = usable_cache_key(&solution.solution.name, || {
+          solution_cache_key(problem, solution)
+        })?;
+        Some((solution.solution.name.clone(), key))
+      })
+      .collect::<BTreeMap<_, _>>(),
+    None => BTreeMap::new(),
+  };
   let live_scores = Arc::new(Mutex::new(LiveScoreState {
     per_solution_reports: BTreeMap::new(),
     test_case_traits: BTreeMap::new(),
+    submitted_predictions: BTreeSet::new(),
   }));
+  let mark_skipped = |test_case: &TestCaseSpec| {
+    info!("Skipping unselected test case {}", test_case.name);
+    for handle in solution_handles.iter().flat_map(BTreeMap::values) {
+      handle.item(test_case.name.clone()).finish(
+        true,
+        TaskItemReport {
+          status: Some("skipped".to_string()),
+          ..TaskItemReport::default()
+        },
+      );
+    }
+  };
 
+  // Testcases excluded by name are neither generated nor validated. A subtask filter needs the
+  // validator traits, so it can only skip judging.
   problem
     .test_cases
     .par_iter()
     .map(|test_case| {
       ensure_not_stopped(options)?;
+      if !options.test_selection.matches_name(&test_case.name) {
+        mark_skipped(test_case);
+        return Ok(None);
+      }
       info!("Preparing test case {}", test_case.name);
       let input_path = resolve_test_input(
         problem,
@@ -504,7 +594,7 @@ fn run_test_cases(
       } else {
         1
       };
-      let validation = run_validator(problem, Path::new(&input_path), trace_level)?;
+      let validation = cached_validator(problem, Path::new(&input_path), trace_level, options)?;
       log_validation_result("Validated test case", &test_case.name, &validation);
       if !validation.status.is_valid() {
         options.request_stop();
@@ -525,6 +615,26 @@ fn run_test_cases(
         live_scores
           .test_case_traits
           .insert(test_case.name.clone(), validation.traits.clone());
+        submit_completed_predictions(problem, solutions, &mut live_scores, predictions);
+      }
+      if !options
+        .test_selection
+        .selects(problem, &test_case.name, &validation.traits)
+      {
+        mark_skipped(test_case);
+        return Ok(Some((
+          test_case.name.clone(),
+          (
+            RuntimeTestCaseData {
+              data: RuntimeTestCaseFiles {
+                input: input_path_string,
+                outputs: String::new(),
+              },
+              input_validation: validation,
+            },
+            None,
+          ),
+        )));
       }
       let outputs_dir = run_generate_outputs(
         problem,
@@ -535,6 +645,11 @@ fn run_test_cases(
       )?;
       info!("Prepared test case {}", test_case.name);
       let outputs_path = outputs_dir.to_string_lossy().into_owned();
+      let test_case_key = options.result_cache.as_ref().and_then(|_| {
+        usable_cache_key(&test_case.name, || {
+          test_case_cache_key(&concrete_test_case, &input_path, &outputs_dir)
+        })
+      });
 
       let solution_reports = solutions
         .par_iter()
@@ -548,13 +663,23 @@ fn run_test_cases(
             .as_ref()
             .and_then(|handles| handles.get(&solution.solution.name))
             .map(|handle| handle.item(test_case.name.clone()));
-          let report = match run_judge(
+          let cache_key = solution_keys
+            .get(&solution.solution.name)
+            .zip(test_case_key.as_ref())
+            .map(|(solution_key, test_case_key)| {
+              CacheKeyBuilder::new("judge")
+                .key("solution", solution_key)
+                .key("test_case", test_case_key)
+                .finish()
+            });
+          let report = match cached_judge(
             problem,
             &concrete_test_case,
-            &solution.solution.name,
-            &solution.prepared,
+            solution,
             Path::new(&outputs_path),
             workspace,
+            options,
+            cache_key,
           ) {
             Ok(report) => report,
             Err(err) => {
@@ -569,30 +694,32 @@ fn run_test_cases(
             }
           };
           log_judge_result(&solution.solution.name, &test_case.name, &report);
+          let current_score = {
+            let mut live_scores = live_scores.lock().unwrap();
+            live_scores
+              .per_solution_reports
+              .entry(solution.solution.name.clone())
+              .or_default()
+              .insert(test_case.name.clone(), report.clone());
+            submit_completed_predictions(problem, solutions, &mut live_scores, predictions);
+
+            aggregate_subtask_results(
+              problem,
+              live_scores
+                .per_solution_reports
+                .get(&solution.solution.name)
+                .expect("live score reports must exist after insertion"),
+              &live_scores.test_case_traits,
+              &BTreeSet::new(),
+            )
+            .iter()
+            .map(|result| result.scaled_score)
+            .sum::<f64>()
+          };
           if let Some(handle) = solution_handles
             .as_ref()
             .and_then(|handles| handles.get(&solution.solution.name))
           {
-            let current_score = {
-              let mut live_scores = live_scores.lock().unwrap();
-              live_scores
-                .per_solution_reports
-                .entry(solution.solution.name.clone())
-                .or_default()
-                .insert(test_case.name.clone(), report.clone());
-
-              aggregate_subtask_results(
-                problem,
-                live_scores
-                  .per_solution_reports
-                  .get(&solution.solution.name)
-                  .expect("live score reports must exist after insertion"),
-                &live_scores.test_case_traits,
-              )
-              .iter()
-              .map(|result| result.scaled_score)
-              .sum()
-            };
             if let Some(guard) = guard {
               guard.finish(
                 !report.status.is_fatal(),
@@ -621,7 +748,7 @@ fn run_test_cases(
         })
         .collect::<Result<BTreeMap<_, _>>>()?;
 
-      Ok((
+      Ok(Some((
         test_case.name.clone(),
         (
           RuntimeTestCaseData {
@@ -631,13 +758,90 @@ fn run_test_cases(
             },
             input_validation: validation,
           },
-          solution_reports,
+          Some(solution_reports),
         ),
-      ))
+      )))
     })
+    .filter_map(Result::transpose)
     .collect()
 }
 
+/// Submits each prediction whose subtask has become fully judged.
+///
+/// Subtask membership follows validator traits. A subtask is complete once every validated member
+/// has been judged and every testcase still awaiting validation is ruled out by its trait hints,
+/// which the final build requires to match the validator. Subtasks with skipped testcases never
+/// complete.
+fn submit_completed_predictions(
+  problem: &ProblemSpec,
+  solutions: &[PreparedSolutionEntry],
+  live_scores: &mut LiveScoreState,
+  predictions: Option<&PredictionChecker>,
+) {
+  let Some(predictions) = predictions else {
+    return;
+  };
+  for query in completed_predictions(problem, solutions, live_scores) {
+    predictions.submit(query);
+  }
+}
+
+fn completed_predictions(
+  problem: &ProblemSpec,
+  solutions: &[PreparedSolutionEntry],
+  live_scores: &mut LiveScoreState,
+) -> Vec<PredictionQuery> {
+  let no_reports = BTreeMap::new();
+  let mut queries = Vec::new();
+  for solution in solutions {
+    let name = &solution.solution.name;
+    let reports = live_scores
+      .per_solution_reports
+      .get(name)
+      .unwrap_or(&no_reports);
+    let mut subtask_results = None;
+    for &index in &solution.solution.subtask_predictions {
+      let Some(subtask) = problem.subtasks.get(index) else {
+        continue;
+      };
+      if live_scores
+        .submitted_predictions
+        .contains(&(name.clone(), index))
+      {
+        continue;
+      }
+      let complete = problem.test_cases.iter().all(|test_case| {
+        let traits = live_scores.test_case_traits.get(&test_case.name);
+        !may_belong(subtask, test_case, traits)
+          || (traits.is_some() && reports.contains_key(&test_case.name))
+      });
+      if !complete {
+        continue;
+      }
+      let result = &subtask_results.get_or_insert_with(|| {
+        aggregate_subtask_results(
+          problem,
+          reports,
+          &live_scores.test_case_traits,
+          &BTreeSet::new(),
+        )
+      })[index];
+      queries.push(PredictionQuery {
+        solution: name.clone(),
+        subtask: index,
+        score: result.raw_score,
+        statuses: result.statuses.clone(),
+      });
+    }
+  }
+  for query in &queries {
+    live_scores
+      .submitted_predictions
+      .insert((query.solution.clone(), query.subtask));
+  }
+  queries
+}
+
 fn log_validation_result(action: &str, name: &str, report: &ValidationReport) {
   if report.status.needs_detailed_log() {
     let details = detail_lines(&[("message", report.message.trim())]);
@@ -735,7 +939,7 @@ fn ensure_generator_succeeded(
     generator_name,
     temp_name,
     result.status,
-    result.error_message.as_deref().unwrap_or_default(),
+    result.diagnostic(),
     String::from_utf8_lossy(stderr).trim()
   )
 }
@@ -833,15 +1037,25 @@ pub fn run_validator(
       name: "validator".to_string(),
       wasm_path: PathBuf::from(validator_wasm),
       arguments: vec![format!("--reader-trace-level={reader_trace_level}")],
+      environment: BTreeMap::new(),
       tick_limit: TOOL_TICK_LIMIT,
       memory_limit: TOOL_MEMORY_LIMIT,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".to_string(),
           permissions: DirectoryPermissions::ReadExecute,
         }],
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: vec![
         descriptor(Some("input"), FilePermissions::Read),
@@ -849,6 +1063,7 @@ pub fn run_validator(
         descriptor(Some("stderr"), FilePermissions::Write),
       ],
     }],
+    terminate_on_exit: Vec::new(),
   })?;
   let stderr = read_tool_output(&stderr_path, "validator report")?;
 
@@ -856,7 +1071,7 @@ pub fn run_validator(
     bail!(
       "Validator runner failed with status {:?}: {}",
       result.status,
-      result.error_message.as_deref().unwrap_or_default()
+      result.diagnostic()
     );
   }
 
@@ -897,9 +1112,17 @@ fn run_checker(
         "output".to_string(),
         "answer".to_string(),
       ],
+      environment: BTreeMap::new(),
       tick_limit: TOOL_TICK_LIMIT,
       memory_limit: TOOL_MEMORY_LIMIT,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".to_string(),
@@ -913,6 +1136,8 @@ fn run_checker(
             permissions: FilePermissions::Read,
           })
           .collect(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: vec![
         descriptor(None, FilePermissions::Read),
@@ -920,6 +1145,7 @@ fn run_checker(
         descriptor(Some("stderr"), FilePermissions::Write),
       ],
     }],
+    terminate_on_exit: Vec::new(),
   })?;
   let stderr = read_tool_output(&stderr_path, "checker report")?;
 
@@ -927,7 +1153,7 @@ fn run_checker(
     bail!(
       "Checker runner failed with status {:?}: {}",
       result.status,
-      result.error_message.as_deref().unwrap_or_default()
+      result.diagnostic()
     );
   }
 
@@ -1121,6 +1347,171 @@ pub fn run_judge(
   Ok(report)
 }
 
+/// Judges through the result cache when `cache_key` is given, restoring cached outputs into the
+/// same workspace directory a fresh run would have written.
+fn cached_judge(
+  problem: &ProblemSpec,
+  test_case: &TestCaseSpec,
+  solution: &PreparedSolutionEntry,
+  official_outputs_dir: &Path,
+  workspace: &RuntimeWorkspace,
+  options: &RuntimeOptions,
+  cache_key: Option<CacheKey>,
+) -> Result<JudgeReport> {
+  let judge = || {
+    run_judge(
+      problem,
+      test_case,
+      &solution.solution.name,
+      &solution.prepared,
+      official_outputs_dir,
+      workspace,
+    )
+  };
+  let (Some(cache), Some(key)) = (&options.result_cache, cache_key) else {
+    return judge();
+  };
+  let outputs_dir = workspace
+    .case_dir(
+      "judge",
+      &format!("{}-{}", solution.solution.name, test_case.name),
+    )?
+    .join("outputs");
+  if let Some(mut report) = cache.load::<JudgeReport>(&key, Some(&outputs_dir)) {
+    info!(
+      "Reused cached judge report for solution {} on {}",
+      solution.solution.name, test_case.name
+    );
+    report.outputs = outputs_dir.to_string_lossy().into_owned();
+    return Ok(report);
+  }
+  let report = judge()?;
+  if !report.status.is_fatal() {
+    cache.store(&key, &report, Some(Path::new(&report.outputs)));
+  }
+  Ok(report)
+}
+
+/// Digests everything a judge run reads from one prepared solution.
+fn solution_cache_key(problem: &ProblemSpec, solution: &PreparedSolutionEntry) -> Result<CacheKey> {
+  let executable = solution
+    .prepared
+    .executable
+    .as_ref()
+    .map(realize_artifact)
+    .transpose()?;
+  Ok(
+    CacheKeyBuilder::new("solution")
+      .contents(
+        "judge_runner",
+        Path::new(&realize_artifact(&problem.judger.judge_runner)?),
+      )?
+      .text("name", &solution.solution.name)
+      .optional_contents("executable", executable.as_deref().map(Path::new))?
+      .contents("src", Path::new(&solution.prepared.src))?
+      .number("file_size_limit", problem.file_size_limit)
+      .finish(),
+  )
+}
+
+/// Digests everything a judge run reads from one test case.
+fn test_case_cache_key(
+  test_case: &TestCaseSpec,
+  input_path: &Path,
+  official_outputs_dir: &Path,
+) -> Result<CacheKey> {
+  Ok(
+    CacheKeyBuilder::new("test_case")
+      .text("name", &test_case.name)
+      .contents("input", input_path)?
+      .contents("official_outputs", official_outputs_dir)?
+      .number("tick_limit", test_case.tick_limit)
+      .number("memory_limit", test_case.memory_limit)
+      .number("stack_limit", test_case.stack_limit())
+      .number("io_ticks_per_call", test_case.io_cost.ticks_per_call)
+      .number("io_ticks_per_byte", test_case.io_cost.ticks_per_byte)
+      .number("random_seed", test_case.random_seed())
+      .finish(),
+  )
+}
+
+fn cached_validator(
+  problem: &ProblemSpec,
+  input_path: &Path,
+  reader_trace_level: u8,
+  options: &RuntimeOptions,
+) -> Result<ValidationReport> {
+  let Some(cache) = &options.result_cache else {
+    return run_validator(problem, input_path, reader_trace_level);
+  };
+  let Some(key) = usable_cache_key(&input_path.display().to_string(), || {
+    Ok(
+      CacheKeyBuilder::new("validator")
+        .contents("validator", &tool_wasm(&problem.validator, "Validator")?)?
+        .contents("input", input_path)?
+        .number("reader_trace_level", u64::from(reader_trace_level))
+        .finish(),
+    )
+  }) else {
+    return run_validator(problem, input_path, reader_trace_level);
+  };
+  if let Some(report) = cache.load(&key, None) {
+    return Ok(report);
+  }
+  let report = run_validator(problem, input_path, reader_trace_level)?;
+  if !report.status.is_fatal() {
+    cache.store(&key, &report, None);
+  }
+  Ok(report)
+}
+
+fn cached_checker(
+  problem: &ProblemSpec,
+  input_path: &Path,
+  output_path: &Path,
+  answer_path: &Path,
+  options: &RuntimeOptions,
+) -> Result<CheckerReport> {
+  let Some(cache) = &options.result_cache else {
+    return run_checker(problem, input_path, output_path, answer_path);
+  };
+  let Some(key) = usable_cache_key(&output_path.display().to_string(), || {
+    Ok(
+      CacheKeyBuilder::new("checker")
+        .contents("checker", &tool_wasm(&problem.checker, "Checker")?)?
+        .contents("input", input_path)?
+        .contents("output", output_path)?
+        .contents("answer", answer_path)?
+        .finish(),
+    )
+  }) else {
+    return run_checker(problem, input_path, output_path, answer_path);
+  };
+  if let Some(report) = cache.load(&key, None) {
+    return Ok(report);
+  }
+  let report = run_checker(problem, input_path, output_path, answer_path)?;
+  if !report.status.is_fatal() {
+    cache.store(&key, &report, None);
+  }
+  Ok(report)
+}
+
+/// Computes a cache key, falling back to an uncached run when its inputs cannot be read.
+fn usable_cache_key(subject: &str, key: impl FnOnce() -> Result<CacheKey>) -> Option<CacheKey> {
+  key()
+    .inspect_err(|error| warn!("Not caching results for {subject}: {error:#}"))
+    .ok()
+}
+
+fn tool_wasm(program: &ProgramSpec, role: &str) -> Result<PathBuf> {
+  let wasm = program
+    .wasm
+    .as_ref()
+    .with_context(|| format!("{role} metadata is missing `wasm`"))?;
+  realize_artifact(wasm).map(PathBuf::from)
+}
+
 fn run_judger_script(invocation: JudgerInvocation<'_>) -> Result<Output> {
   // Packaged runners write helper files into a separate working directory for each invocation.
   let runner = realize_artifact(invocation.runner)?;
@@ -1140,6 +1531,22 @@ fn run_judger_script(invocation: JudgerInvocation<'_>) -> Result<Output> {
       "HULL_MEMORY_LIMIT",
       invocation.test_case.memory_limit.to_string(),
     )
+    .env(
+      "HULL_STACK_LIMIT",
+      invocation.test_case.stack_limit().to_string(),
+    )
+    .env(
+      "HULL_IO_TICKS_PER_CALL",
+      invocation.test_case.io_cost.ticks_per_call.to_string(),
+    )
+    .env(
+      "HULL_IO_TICKS_PER_BYTE",
+      invocation.test_case.io_cost.ticks_per_byte.to_string(),
+    )
+    .env(
+      "HULL_RANDOM_SEED",
+      invocation.test_case.random_seed().to_string(),
+    )
     .env(
       "HULL_FILE_SIZE_LIMIT",
       invocation.file_size_limit.to_string(),
@@ -1147,6 +1554,9 @@ fn run_judger_script(invocation: JudgerInvocation<'_>) -> Result<Output> {
     .env("HULL_SOLUTION_SRC", &invocation.prepared_solution.src)
     .env("HULL_OUTPUTS_DIR", invocation.outputs_dir);
   set_module_cache_environment(&mut command);
+  if let Some(socket) = session_server_socket() {
+    command.env(SESSION_SOCKET_ENVIRONMENT, socket);
+  }
 
   if let Some(executable) = &invocation.prepared_solution.executable {
     command.env("HULL_SOLUTION_EXECUTABLE", realize_artifact(executable)?);
@@ -1187,6 +1597,33 @@ fn set_module_cache_environment(command: &mut Command) {
   }
 }
 
+/// Returns the socket of this process's session server, starting it on first use.
+///
+/// Judger scripts forward their `hull run-wasm` requests here, so their sessions reuse the
+/// engines and modules already loaded by this process. The server serves as many connections at
+/// once as the analysis thread pool that starts it has threads, which is how many judgers can
+/// run at a time.
+fn session_server_socket() -> Option<&'static Path> {
+  static SERVER: OnceLock<Option<(tempfile::TempDir, PathBuf)>> = OnceLock::new();
+  SERVER
+    .get_or_init(|| {
+      let started = (|| {
+        let directory = tempfile::Builder::new().prefix("hull-session-").tempdir()?;
+        let socket = directory.path().join("run-wasm.sock");
+        let listener = UnixListener::bind(&socket)?;
+        let base = std::env::current_dir()?;
+        let workers = rayon::current_num_threads();
+        std::thread::spawn(move || serve_socket(listener, base, workers));
+        Ok::<_, anyhow::Error>((directory, socket))
+      })();
+      started
+        .inspect_err(|error| warn!("Judger sessions will run in separate processes: {error:#}"))
+        .ok()
+    })
+    .as_ref()
+    .map(|(_, socket)| socket.as_path())
+}
+
 fn judger_output_details(output: &Output) -> String {
   format!(
     "Judger stdout:\n{}\nJudger stderr:\n{}",
@@ -1195,12 +1632,33 @@ fn judger_output_details(output: &Output) -> String {
   )
 }
 
+/// Returns whether a testcase can belong to `subtask`: by its validator traits once known,
+/// otherwise unless its trait hints require a different value of a subtask trait.
+fn may_belong(
+  subtask: &SubtaskSpec,
+  test_case: &TestCaseSpec,
+  traits: Option<&BTreeMap<String, bool>>,
+) -> bool {
+  match traits {
+    Some(traits) => subtask.contains(traits),
+    None => subtask
+      .traits
+      .iter()
+      .all(|(name, value)| test_case.trait_hints.get(name) != Some(&!value)),
+  }
+}
+
 /// Aggregates test case judge reports into subtask runtime reports.
+///
+/// A skipped testcase without traits was never validated, so it counts as skipped in every
+/// subtask it may belong to.
 pub fn aggregate_subtask_results(
   problem: &ProblemSpec,
   test_case_results: &BTreeMap<String, JudgeReport>,
   test_case_traits: &TestCaseTraitsMap,
+  skipped_test_cases: &BTreeSet<String>,
 ) -> Vec<SubtaskRuntimeReport> {
+  let no_traits = BTreeMap::new();
   problem
     .subtasks
     .iter()
@@ -1209,12 +1667,7 @@ pub fn aggregate_subtask_results(
         .test_cases
         .iter()
         .filter(|test_case| {
-          subtask.traits.iter().all(|(name, value)| {
-            test_case_traits
-              .get(&test_case.name)
-              .and_then(|traits| traits.get(name))
-              == Some(value)
-          })
+          subtask.contains(test_case_traits.get(&test_case.name).unwrap_or(&no_traits))
         })
         .map(|test_case| test_case.name.clone())
         .collect::<Vec<_>>();
@@ -1228,6 +1681,15 @@ pub fn aggregate_subtask_results(
             .map(|report| (test_case_name.clone(), report))
         })
         .collect();
+      let skipped = problem
+        .test_cases
+        .iter()
+        .filter(|test_case| {
+          skipped_test_cases.contains(&test_case.name)
+            && may_belong(subtask, test_case, test_case_traits.get(&test_case.name))
+        })
+        .map(|test_case| test_case.name.clone())
+        .collect();
 
       let statuses: Vec<JudgeStatus> = BTreeSet::from_iter(
         test_cases
@@ -1254,6 +1716,7 @@ pub fn aggregate_subtask_results(
         statuses,
         raw_score,
         scaled_score: raw_score * subtask.full_score,
+        skipped_test_cases: skipped,
       }
     })
     .collect()
@@ -1300,15 +1763,25 @@ fn resolve_test_input(
       name: "generator".to_string(),
       wasm_path: PathBuf::from(generator_wasm),
       arguments: arguments.unwrap_or(&[]).to_vec(),
+      environment: BTreeMap::new(),
       tick_limit: TOOL_TICK_LIMIT,
       memory_limit: TOOL_MEMORY_LIMIT,
+      stack_limit: None,
+      random_seed: 0,
+      clock: Clock::Frozen,
+      io_cost: IoCost::default(),
       required_accepted: false,
+      coredump: false,
+      backtrace: false,
+      features: Vec::new(),
       file_system: FileSystem {
         directories: vec![DirectoryBinding {
           path: ".".to_string(),
           permissions: DirectoryPermissions::ReadExecute,
         }],
         bindings: Vec::new(),
+        symlinks: Vec::new(),
+        scratch: Vec::new(),
       },
       initial_descriptors: vec![
         descriptor(None, FilePermissions::Read),
@@ -1316,6 +1789,7 @@ fn resolve_test_input(
         descriptor(Some("stderr"), FilePermissions::Write),
       ],
     }],
+    terminate_on_exit: Vec::new(),
   })?;
   let stderr = read_tool_output(&stderr_path, "generator stderr")?;
   ensure_generator_succeeded(generator_name, temp_name, &result, &stderr)?;
@@ -1325,9 +1799,7 @@ fn resolve_test_input(
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::runtime::types::{
-    ArtifactSpec, JudgerSpec, ProgramSpec, ScoringMethod, SubtaskSpec, ValidationStatus,
-  };
+  use crate::runtime::types::{ScoringMethod, SubtaskSpec, TestSelection, ValidationStatus};
 
   fn judge_report(status: JudgeStatus, score: f64) -> JudgeReport {
     JudgeReport {
@@ -1336,74 +1808,11 @@ mod tests {
       message: String::new(),
       tick: 0,
       memory: 0,
+      backtrace: Vec::new(),
       outputs: String::new(),
     }
   }
 
-  fn problem_with_subtasks(scoring_method: ScoringMethod) -> ProblemSpec {
-    ProblemSpec {
-      name: "p".to_string(),
-      tick_limit: 1,
-      memory_limit: 1,
-      file_size_limit: 1,
-      full_score: 1.0,
-      checker: ProgramSpec {
-        src: None,
-        wasm: None,
-      },
-      validator: ProgramSpec {
-        src: None,
-        wasm: None,
-      },
-      generators: BTreeMap::new(),
-      main_correct_solution: "std".to_string(),
-      judger: JudgerSpec {
-        prepare_solution_runner: ArtifactSpec {
-          path: String::new(),
-          drv_path: None,
-        },
-        generate_outputs_runner: Some(ArtifactSpec {
-          path: String::new(),
-          drv_path: None,
-        }),
-        judge_runner: ArtifactSpec {
-          path: String::new(),
-          drv_path: None,
-        },
-      },
-      test_cases: vec![
-        TestCaseSpec {
-          name: "a".to_string(),
-          input_file: None,
-          tick_limit: 1,
-          memory_limit: 1,
-          groups: Vec::new(),
-          trait_hints: BTreeMap::new(),
-          generator: None,
-          arguments: None,
-        },
-        TestCaseSpec {
-          name: "b".to_string(),
-          input_file: None,
-          tick_limit: 1,
-          memory_limit: 1,
-          groups: Vec::new(),
-          trait_hints: BTreeMap::new(),
-          generator: None,
-          arguments: None,
-        },
-      ],
-      subtasks: vec![SubtaskSpec {
-        full_score: 0.5,
-        scoring_method,
-        traits: BTreeMap::new(),
-      }],
-      solutions: Vec::new(),
-      checker_tests: Vec::new(),
-      validator_tests: Vec::new(),
-    }
-  }
-
   #[test]
   fn judge_failure_message() {
     let message = judge_failure_context("aplusb", "wa", "sample-1");
@@ -1452,7 +1861,7 @@ mod tests {
 
   #[test]
   fn subtask_min() {
-    let problem = problem_with_subtasks(ScoringMethod::Min);
+    let problem = ProblemSpec::fixture(ScoringMethod::Min);
     let reports = BTreeMap::from([
       ("a".to_string(), judge_report(JudgeStatus::Accepted, 1.0)),
       (
@@ -1465,7 +1874,7 @@ mod tests {
       ("b".to_string(), BTreeMap::new()),
     ]);
 
-    let result = aggregate_subtask_results(&problem, &reports, &traits);
+    let result = aggregate_subtask_results(&problem, &reports, &traits, &BTreeSet::new());
     assert_eq!(result.len(), 1);
     assert!((result[0].raw_score - 0.25).abs() < 1e-9);
     assert!((result[0].scaled_score - 0.125).abs() < 1e-9);
@@ -1477,7 +1886,7 @@ mod tests {
 
   #[test]
   fn subtask_sum_average() {
-    let problem = problem_with_subtasks(ScoringMethod::Sum);
+    let problem = ProblemSpec::fixture(ScoringMethod::Sum);
     let reports = BTreeMap::from([
       ("a".to_string(), judge_report(JudgeStatus::Accepted, 1.0)),
       (
@@ -1490,7 +1899,7 @@ mod tests {
       ("b".to_string(), BTreeMap::new()),
     ]);
 
-    let result = aggregate_subtask_results(&problem, &reports, &traits);
+    let result = aggregate_subtask_results(&problem, &reports, &traits, &BTreeSet::new());
     assert_eq!(result.len(), 1);
     assert!((result[0].raw_score - 0.75).abs() < 1e-9);
     assert!((result[0].scaled_score - 0.375).abs() < 1e-9);
@@ -1498,22 +1907,183 @@ mod tests {
 
   #[test]
   fn subtask_ignore_missing() {
-    let problem = problem_with_subtasks(ScoringMethod::Min);
+    let problem = ProblemSpec::fixture(ScoringMethod::Min);
     let reports = BTreeMap::from([("a".to_string(), judge_report(JudgeStatus::Accepted, 1.0))]);
     let traits = BTreeMap::from([
       ("a".to_string(), BTreeMap::new()),
       ("b".to_string(), BTreeMap::new()),
     ]);
 
-    let result = aggregate_subtask_results(&problem, &reports, &traits);
+    let result = aggregate_subtask_results(&problem, &reports, &traits, &BTreeSet::new());
     assert_eq!(result[0].test_cases.len(), 1);
     assert_eq!(result[0].statuses, vec![JudgeStatus::Accepted]);
     assert!((result[0].raw_score - 1.0).abs() < 1e-9);
   }
 
+  #[test]
+  fn subtask_skipped_test_cases() {
+    let mut problem = ProblemSpec::fixture(ScoringMethod::Min);
+    problem.subtasks.push(SubtaskSpec {
+      full_score: 0.5,
+      scoring_method: ScoringMethod::Min,
+      traits: BTreeMap::from([("large".to_string(), true)]),
+    });
+    let reports = BTreeMap::from([("a".to_string(), judge_report(JudgeStatus::Accepted, 1.0))]);
+    let traits = BTreeMap::from([
+      ("a".to_string(), BTreeMap::new()),
+      (
+        "b".to_string(),
+        BTreeMap::from([("large".to_string(), true)]),
+      ),
+    ]);
+
+    let result = aggregate_subtask_results(
+      &problem,
+      &reports,
+      &traits,
+      &BTreeSet::from(["b".to_string()]),
+    );
+    assert_eq!(result[0].raw_score, 1.0);
+    assert_eq!(
+      result[0].skipped_test_cases,
+      BTreeSet::from(["b".to_string()])
+    );
+    assert!(result[1].test_cases.is_empty());
+    assert_eq!(result[1].scaled_score, 0.0);
+    assert_eq!(
+      result[1].skipped_test_cases,
+      BTreeSet::from(["b".to_string()])
+    );
+
+    // `b` was excluded by name, so it has no traits and may belong to either subtask.
+    let unvalidated = BTreeMap::from([("a".to_string(), BTreeMap::new())]);
+    let skipped = BTreeSet::from(["b".to_string()]);
+    let result = aggregate_subtask_results(&problem, &reports, &unvalidated, &skipped);
+    assert_eq!(result[0].skipped_test_cases, skipped);
+    assert_eq!(result[1].skipped_test_cases, skipped);
+    problem.test_cases[1].trait_hints = BTreeMap::from([("large".to_string(), false)]);
+    let result = aggregate_subtask_results(&problem, &reports, &unvalidated, &skipped);
+    assert_eq!(result[0].skipped_test_cases, skipped);
+    assert!(result[1].skipped_test_cases.is_empty());
+  }
+
+  #[test]
+  fn predictions_wait_for_complete_subtasks() {
+    let mut problem = ProblemSpec::fixture(ScoringMethod::Min);
+    problem.subtasks.push(SubtaskSpec {
+      full_score: 0.5,
+      scoring_method: ScoringMethod::Min,
+      traits: BTreeMap::from([("large".to_string(), true)]),
+    });
+    let solutions = [PreparedSolutionEntry {
+      solution: SolutionSpec {
+        name: "brute".to_string(),
+        src: String::new(),
+        main_correct_solution: false,
+        participant_visibility: false,
+        subtask_predictions: vec![0, 1],
+      },
+      prepared: PreparedSolutionSpec {
+        src: String::new(),
+        executable: None,
+      },
+    }];
+    let mut live_scores = LiveScoreState {
+      per_solution_reports: BTreeMap::from([(
+        "brute".to_string(),
+        BTreeMap::from([("a".to_string(), judge_report(JudgeStatus::Accepted, 1.0))]),
+      )]),
+      test_case_traits: BTreeMap::from([("a".to_string(), BTreeMap::new())]),
+      submitted_predictions: BTreeSet::new(),
+    };
+    assert!(
+      completed_predictions(&problem, &solutions, &mut live_scores).is_empty(),
+      "an unvalidated testcase without hints may belong to any subtask"
+    );
+
+    problem.test_cases[1].trait_hints = BTreeMap::from([("large".to_string(), false)]);
+    let queries = completed_predictions(&problem, &solutions, &mut live_scores);
+    assert_eq!(queries.len(), 1, "hints rule `b` out of the large subtask");
+    assert_eq!(queries[0].subtask, 1);
+    assert!(queries[0].statuses.is_empty());
+
+    live_scores.test_case_traits.insert(
+      "b".to_string(),
+      BTreeMap::from([("large".to_string(), false)]),
+    );
+    assert!(
+      completed_predictions(&problem, &solutions, &mut live_scores).is_empty(),
+      "the first subtask still waits for `b` to be judged"
+    );
+
+    live_scores
+      .per_solution_reports
+      .get_mut("brute")
+      .unwrap()
+      .insert(
+        "b".to_string(),
+        judge_report(JudgeStatus::TimeLimitExceeded, 0.0),
+      );
+    let queries = completed_predictions(&problem, &solutions, &mut live_scores);
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].subtask, 0);
+    assert_eq!(queries[0].score, 0.0);
+    assert_eq!(
+      queries[0].statuses,
+      vec![JudgeStatus::Accepted, JudgeStatus::TimeLimitExceeded]
+    );
+    assert!(completed_predictions(&problem, &solutions, &mut live_scores).is_empty());
+  }
+
+  #[test]
+  fn test_selection_globs_and_subtasks() {
+    let mut problem = ProblemSpec::fixture(ScoringMethod::Min);
+    problem.subtasks.push(SubtaskSpec {
+      full_score: 0.5,
+      scoring_method: ScoringMethod::Min,
+      traits: BTreeMap::from([("large".to_string(), true)]),
+    });
+    let large = BTreeMap::from([("large".to_string(), true)]);
+    let small = BTreeMap::new();
+
+    let everything = TestSelection::default();
+    assert!(everything.is_everything());
+    assert!(everything.selects(&problem, "a", &small));
+
+    let glob = TestSelection::new(["a*".to_string(), "?x".to_string()], []).unwrap();
+    assert!(glob.selects(&problem, "a", &small));
+    assert!(glob.selects(&problem, "a.b", &small));
+    assert!(glob.selects(&problem, "bx", &small));
+    assert!(!glob.selects(&problem, "b", &small));
+    assert!(!glob.selects(&problem, "xbx", &small));
+
+    let subtask = TestSelection::new(["b".to_string()], [1]).unwrap();
+    assert!(subtask.selects(&problem, "b", &large));
+    assert!(!subtask.selects(&problem, "b", &small));
+    assert!(!subtask.selects(&problem, "a", &large));
+    subtask.check(&problem).unwrap();
+
+    let missing_subtask = TestSelection::new([], [2]).unwrap();
+    assert!(
+      missing_subtask
+        .check(&problem)
+        .unwrap_err()
+        .to_string()
+        .contains("no subtask 2")
+    );
+    let missing_test = TestSelection::new(["c*".to_string()], []).unwrap();
+    assert!(
+      missing_test
+        .check(&problem)
+        .unwrap_err()
+        .to_string()
+        .contains("`c*`")
+    );
+  }
+
   #[test]
   fn subtask_trait_subset() {
-    let mut problem = problem_with_subtasks(ScoringMethod::Min);
+    let mut problem = ProblemSpec::fixture(ScoringMethod::Min);
     problem.subtasks = vec![SubtaskSpec {
       full_score: 1.0,
       scoring_method: ScoringMethod::Min,
@@ -1535,7 +2105,7 @@ mod tests {
       ("b".to_string(), BTreeMap::from([("y".to_string(), true)])),
     ]);
 
-    let result = aggregate_subtask_results(&problem, &reports, &traits);
+    let result = aggregate_subtask_results(&problem, &reports, &traits, &BTreeSet::new());
     assert_eq!(result[0].test_cases.len(), 1);
     assert!(result[0].test_cases.contains_key("a"));
     assert_eq!(result[0].statuses, vec![JudgeStatus::Accepted]);
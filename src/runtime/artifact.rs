@@ -365,6 +365,7 @@ mod tests {
   use super::*;
   use std::collections::BTreeMap;
 
+  use crate::runner::IoCost;
   use crate::runtime::types::{
     CheckerTestSpec, JudgerSpec, ProblemSpec, ProgramSpec, ScoringMethod, SolutionSpec,
     SubtaskSpec, TestCaseSpec, ValidatorTestSpec,
@@ -406,6 +407,8 @@ mod tests {
       name: "aPlusB".to_string(),
       tick_limit: 1,
       memory_limit: 1,
+      stack_limit: None,
+      io_cost: IoCost::default(),
       file_size_limit: 1,
       full_score: 1.0,
       checker: ProgramSpec {
@@ -443,10 +446,13 @@ mod tests {
         input_file: None,
         tick_limit: 1,
         memory_limit: 1,
+        stack_limit: None,
+        io_cost: IoCost::default(),
         groups: Vec::new(),
         trait_hints: BTreeMap::new(),
         generator: None,
         arguments: None,
+        random_seed: None,
       }],
       subtasks: vec![SubtaskSpec {
         full_score: 1.0,
@@ -459,12 +465,14 @@ mod tests {
           src: "std.cpp".to_string(),
           main_correct_solution: true,
           participant_visibility: true,
+          subtask_predictions: Vec::new(),
         },
         SolutionSpec {
           name: "dup".to_string(),
           src: "dup.cpp".to_string(),
           main_correct_solution: false,
           participant_visibility: true,
+          subtask_predictions: Vec::new(),
         },
       ],
       checker_tests: vec![CheckerTestSpec {
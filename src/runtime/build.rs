@@ -20,18 +20,21 @@ use std::time::{Duration, Instant};
 use anyhow::{Context, Result, bail};
 use rayon::prelude::*;
 use tempfile::NamedTempFile;
-use tracing::info;
+use tracing::{info, warn};
 
 use super::analysis::{analyze_problem, install_with_pool};
 use super::artifact::{
   collect_problem_realize_builds, collect_problems_realize_builds, storeify_runtime_data,
 };
 use super::metadata::{load_contest_spec, load_problem_spec};
+use super::prediction::{PredictionSource, ensure_predictions_hold};
 use super::types::{RuntimeData, RuntimeOptions};
 use super::workspace::RuntimeWorkspace;
 use crate::interactive::ProblemProgressHandle;
 use crate::interactive::{PhaseKind, TaskItemReport, TaskKind};
 use crate::nix::{get_flake_url, run_build_commands};
+use crate::report::{JudgeCliReport, render_subtask_predictions};
+use crate::runner::registry_statistics;
 
 struct PhaseTiming {
   name: &'static str,
@@ -75,10 +78,14 @@ impl BuildTimings {
   }
 
   fn log_summary(&self, kind: &str, name: &str) {
+    let registry = registry_statistics();
     info!(
-      "{kind} `{name}` build timing: total={}, phases: {}",
+      "{kind} `{name}` build timing: total={}, phases: {}, runner registry: engines={}, module loads={}, module reuses={}",
       format_duration(self.started.elapsed()),
-      format_phase_timings(&self.phases)
+      format_phase_timings(&self.phases),
+      registry.engines,
+      registry.module_loads,
+      registry.module_reuses
     );
   }
 }
@@ -95,6 +102,15 @@ fn format_phase_timings(timings: &[PhaseTiming]) -> String {
     .join(", ")
 }
 
+fn print_subtask_predictions(label: &str, runtime: &RuntimeData) {
+  if !runtime.subtask_predictions.is_empty() {
+    println!(
+      "Subtask predictions for {label}:\n{}\n",
+      render_subtask_predictions(&runtime.subtask_predictions)
+    );
+  }
+}
+
 fn prepare_runtime_store_paths(
   label: &str,
   runtime: &mut RuntimeData,
@@ -205,6 +221,8 @@ pub fn build_problem(
   options: RuntimeOptions,
   nix_args: &[String],
 ) -> Result<()> {
+  let options =
+    options.with_prediction_source(Some(PredictionSource::Problem(problem.to_string())));
   let mut timings = BuildTimings::new();
   let result = (|| {
     let spec = timings.run_phase(
@@ -238,6 +256,21 @@ pub fn build_problem(
       },
     )?;
 
+    print_subtask_predictions(&format!("problem `{problem}`"), &runtime);
+    let predictions = ensure_predictions_hold(&spec.name, &runtime.subtask_predictions);
+    if !options.test_selection.is_everything() {
+      // Packages must cover every testcase, so a selective analysis only reports its results.
+      warn!("Skipping target packaging because the test selection left testcases unjudged");
+      for (name, solution) in &runtime.solutions {
+        println!(
+          "Solution {name}:\n{}\n",
+          JudgeCliReport::from_runtime_solution(&spec, solution).render_human_readable()
+        );
+      }
+      return predictions;
+    }
+    predictions?;
+
     timings.run_phase("target packaging", None, || {
       prepare_runtime_store_paths(
         &format!("problem `{problem}`"),
@@ -311,14 +344,19 @@ pub fn build_contest(
                 .progress
                 .child_scope(&contest_spec.name)
                 .child_scope(&spec.name);
+              let problem_options = options
+                .single_job_child(problem_progress)
+                .with_prediction_source(Some(PredictionSource::ContestProblem {
+                  contest: contest.to_string(),
+                  problem: spec.name.clone(),
+                }));
               let runtime =
-                analyze_problem(spec, &workspace, options.single_job_child(problem_progress))
-                  .with_context(|| {
-                    format!(
-                      "Runtime analysis failed for contest `{contest}`, problem `{}`",
-                      spec.name
-                    )
-                  })?;
+                analyze_problem(spec, &workspace, problem_options).with_context(|| {
+                  format!(
+                    "Runtime analysis failed for contest `{contest}`, problem `{}`",
+                    spec.name
+                  )
+                })?;
               if let Some(guard) = guard {
                 guard.finish(
                   true,
@@ -335,6 +373,16 @@ pub fn build_contest(
       },
     )?;
 
+    for (problem_name, (_, runtime)) in &runtime_by_problem {
+      print_subtask_predictions(
+        &format!("contest `{contest}`, problem `{problem_name}`"),
+        runtime,
+      );
+    }
+    for (problem_name, (_, runtime)) in &runtime_by_problem {
+      ensure_predictions_hold(problem_name, &runtime.subtask_predictions)?;
+    }
+
     timings.run_phase("target packaging", None, || {
       for (problem_name, (_, runtime)) in &mut runtime_by_problem {
         let problem_progress = options.progress.child_scope(problem_name);
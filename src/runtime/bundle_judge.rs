@@ -28,6 +28,7 @@ use super::types::{
   TestCaseSpec, ValidationReport,
 };
 use super::workspace::RuntimeWorkspace;
+use crate::runner::IoCost;
 
 /// Canonical filename used for bundled official output archives.
 pub const OFFICIAL_DATA_TAR_NAME: &str = "official-data.tar";
@@ -73,6 +74,12 @@ pub struct BundleJudgeTestCaseInput<'a> {
   pub tick_limit: u64,
   /// Memory limit forwarded to the bundled judger.
   pub memory_limit: u64,
+  /// Explicit stack limit forwarded to the bundled judger.
+  pub stack_limit: Option<u64>,
+  /// I/O tick costs forwarded to the bundled judger.
+  pub io_cost: IoCost,
+  /// Explicit solution `random_get` seed forwarded to the bundled judger.
+  pub random_seed: Option<u64>,
   /// Logical testcase groups used by the runtime judger.
   pub groups: Vec<String>,
   /// Optional trait overrides. If empty, traits bundled in official data are used.
@@ -137,6 +144,8 @@ pub fn make_runtime_problem(
     name: problem.name.clone(),
     tick_limit: problem.tick_limit,
     memory_limit: problem.memory_limit,
+    stack_limit: problem.stack_limit,
+    io_cost: problem.io_cost,
     file_size_limit: problem.file_size_limit,
     full_score: problem.full_score,
     checker: problem.checker.clone(),
@@ -178,6 +187,7 @@ pub fn build_runtime_solutions(
     src: participant_source.to_string(),
     main_correct_solution: false,
     participant_visibility: true,
+    subtask_predictions: Vec::new(),
   });
   solutions
 }
@@ -255,6 +265,8 @@ pub fn judge_test_case_with_parts(
     input_file: Some(local_input_path.to_string_lossy().into_owned()),
     tick_limit: test_case.tick_limit,
     memory_limit: test_case.memory_limit,
+    stack_limit: test_case.stack_limit,
+    io_cost: test_case.io_cost,
     groups: test_case.groups,
     trait_hints: if test_case.trait_hints.is_empty() {
       loaded.validation.traits
@@ -263,6 +275,7 @@ pub fn judge_test_case_with_parts(
     },
     generator: None,
     arguments: None,
+    random_seed: test_case.random_seed,
   };
   run_judge(
     runtime_problem,
@@ -0,0 +1,447 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+// Bump when the entry layout or the meaning of a key component changes.
+const CACHE_FORMAT: &str = "hull-result-cache-v1";
+const REPORT_FILE: &str = "report.json";
+const OUTPUTS_DIRECTORY: &str = "outputs";
+
+/// Returns the persistent directory for cached validator, checker, and judge reports.
+pub fn result_cache_directory() -> Option<PathBuf> {
+  if let Some(cache) = std::env::var_os("HULL_RESULT_CACHE_DIR").filter(|path| !path.is_empty()) {
+    return Some(PathBuf::from(cache));
+  }
+  if let Some(cache) = std::env::var_os("XDG_CACHE_HOME").filter(|path| !path.is_empty()) {
+    return Some(PathBuf::from(cache).join("hull").join("results"));
+  }
+  std::env::var_os("HOME")
+    .filter(|path| !path.is_empty())
+    .map(PathBuf::from)
+    .map(|home| home.join(".cache").join("hull").join("results"))
+}
+
+/// Content hash identifying one cached report.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CacheKey(String);
+
+/// Accumulates labelled key components into a [`CacheKey`].
+///
+/// Every component is framed by its label and length, so moving bytes between components always
+/// changes the key.
+pub struct CacheKeyBuilder {
+  digest: Sha256,
+}
+
+impl CacheKeyBuilder {
+  /// Starts a key for one kind of report, bound to the running Hull executable.
+  pub fn new(kind: &str) -> Self {
+    let mut builder = Self {
+      digest: Sha256::new(),
+    };
+    builder.frame("format", CACHE_FORMAT.as_bytes());
+    builder.frame("hull", executable_digest().as_bytes());
+    builder.frame("kind", kind.as_bytes());
+    builder
+  }
+
+  /// Adds one string component.
+  pub fn text(mut self, label: &str, value: &str) -> Self {
+    self.frame(label, value.as_bytes());
+    self
+  }
+
+  /// Adds one integer component.
+  pub fn number(mut self, label: &str, value: u64) -> Self {
+    self.frame(label, &value.to_le_bytes());
+    self
+  }
+
+  /// Adds a finished key, such as a digest shared by many entries.
+  pub fn key(mut self, label: &str, key: &CacheKey) -> Self {
+    self.frame(label, key.0.as_bytes());
+    self
+  }
+
+  /// Adds the contents of a file, or of every file below a directory, but not its location.
+  pub fn contents(mut self, label: &str, path: &Path) -> Result<Self> {
+    self.frame(label, &[]);
+    self.hash_tree(path)?;
+    Ok(self)
+  }
+
+  /// Adds the contents at `path` when present, keeping absent and empty apart.
+  pub fn optional_contents(self, label: &str, path: Option<&Path>) -> Result<Self> {
+    match path {
+      Some(path) => self.text(label, "some").contents(label, path),
+      None => Ok(self.text(label, "none")),
+    }
+  }
+
+  /// Finishes the key.
+  pub fn finish(self) -> CacheKey {
+    CacheKey(hex(&self.digest.finalize()))
+  }
+
+  fn frame(&mut self, label: &str, bytes: &[u8]) {
+    self.digest.update((label.len() as u64).to_le_bytes());
+    self.digest.update(label.as_bytes());
+    self.digest.update((bytes.len() as u64).to_le_bytes());
+    self.digest.update(bytes);
+  }
+
+  fn hash_tree(&mut self, path: &Path) -> Result<()> {
+    let metadata =
+      fs::metadata(path).with_context(|| format!("Failed to inspect {}", path.display()))?;
+    if !metadata.is_dir() {
+      let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+      self.frame("file", &bytes);
+      return Ok(());
+    }
+    let mut entries = fs::read_dir(path)
+      .with_context(|| format!("Failed to list {}", path.display()))?
+      .map(|entry| entry.map(|entry| entry.file_name()))
+      .collect::<std::io::Result<Vec<_>>>()
+      .with_context(|| format!("Failed to list {}", path.display()))?;
+    entries.sort();
+    self.frame("directory", &(entries.len() as u64).to_le_bytes());
+    for name in entries {
+      self.frame("entry", name.as_encoded_bytes());
+      self.hash_tree(&path.join(name))?;
+    }
+    Ok(())
+  }
+}
+
+/// Entries and bytes removed by [`ResultCache::clean`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CleanSummary {
+  /// Removed entries, including abandoned staging directories.
+  pub entries: u64,
+  /// Bytes of the removed files.
+  pub bytes: u64,
+}
+
+/// Persistent store of reports keyed by the content of everything that produced them.
+///
+/// Reading and writing are best effort: an unreadable entry is a miss and a failed write is
+/// dropped, so the cache can never fail an analysis. The cache never evicts on its own; every
+/// hit refreshes the entry's modification time so that [`ResultCache::clean`] can drop entries
+/// no recent run used.
+#[derive(Clone, Debug)]
+pub struct ResultCache {
+  root: PathBuf,
+}
+
+impl ResultCache {
+  /// Opens the cache rooted at `root`; the directory is created on first store.
+  pub fn new(root: PathBuf) -> Self {
+    Self { root }
+  }
+
+  /// Opens the cache at [`result_cache_directory`], unless `disabled` or no location is known.
+  pub fn open_default(disabled: bool) -> Option<Self> {
+    if disabled {
+      return None;
+    }
+    result_cache_directory().map(Self::new)
+  }
+
+  /// Returns the cached report for `key`.
+  ///
+  /// When `outputs` is given, the cached outputs directory is copied there as well, and a
+  /// missing copy makes the lookup a miss.
+  pub fn load<T: DeserializeOwned>(&self, key: &CacheKey, outputs: Option<&Path>) -> Option<T> {
+    let entry = self.root.join(&key.0);
+    let report_path = entry.join(REPORT_FILE);
+    let report = serde_json::from_slice(&fs::read(&report_path).ok()?).ok()?;
+    if let Some(outputs) = outputs {
+      let cached = entry.join(OUTPUTS_DIRECTORY);
+      if !cached.is_dir() {
+        return None;
+      }
+      if outputs.exists() {
+        fs::remove_dir_all(outputs).ok()?;
+      }
+      copy_tree(&cached, outputs).ok()?;
+    }
+    let _ = fs::File::options()
+      .write(true)
+      .open(&report_path)
+      .and_then(|file| file.set_modified(SystemTime::now()));
+    Some(report)
+  }
+
+  /// Stores `report` and, when given, a copy of the `outputs` directory under `key`.
+  pub fn store<T: Serialize>(&self, key: &CacheKey, report: &T, outputs: Option<&Path>) {
+    let entry = self.root.join(&key.0);
+    if entry.exists() || fs::create_dir_all(&self.root).is_err() {
+      return;
+    }
+    let Ok(staging) = tempfile::Builder::new()
+      .prefix(".staging-")
+      .tempdir_in(&self.root)
+    else {
+      return;
+    };
+    let staged = (|| {
+      fs::write(
+        staging.path().join(REPORT_FILE),
+        serde_json::to_vec(report)?,
+      )?;
+      if let Some(outputs) = outputs {
+        copy_tree(outputs, &staging.path().join(OUTPUTS_DIRECTORY))?;
+      }
+      Ok::<_, anyhow::Error>(())
+    })();
+    if staged.is_ok() {
+      // A concurrent writer of the same key produced an identical entry, so losing the rename
+      // race is harmless; the staging directory is then removed on drop.
+      let _ = fs::rename(staging.path(), &entry);
+    }
+  }
+
+  /// Removes every entry, or with `unused_for` only entries no lookup or store touched for that
+  /// long.
+  ///
+  /// Only directories the cache creates are touched: key directories and staging directories.
+  /// Anything else in the root, for example when it was pointed at a shared directory, is kept.
+  pub fn clean(&self, unused_for: Option<Duration>) -> Result<CleanSummary> {
+    let mut summary = CleanSummary::default();
+    let entries = match fs::read_dir(&self.root) {
+      Ok(entries) => entries,
+      Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(summary),
+      Err(error) => {
+        return Err(error).with_context(|| format!("Failed to list {}", self.root.display()));
+      }
+    };
+    let now = SystemTime::now();
+    for entry in entries {
+      let entry = entry.with_context(|| format!("Failed to list {}", self.root.display()))?;
+      let path = entry.path();
+      let created_by_cache = entry.file_name().to_str().is_some_and(|name| {
+        name.starts_with(".staging-")
+          || (name.len() == 64
+            && name
+              .bytes()
+              .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f')))
+      });
+      if !created_by_cache || !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+        continue;
+      }
+      if let Some(unused_for) = unused_for {
+        let used = fs::metadata(path.join(REPORT_FILE))
+          .or_else(|_| fs::metadata(&path))
+          .and_then(|metadata| metadata.modified())
+          .with_context(|| format!("Failed to inspect {}", path.display()))?;
+        if now.duration_since(used).unwrap_or_default() < unused_for {
+          continue;
+        }
+      }
+      let bytes = tree_size(&path);
+      fs::remove_dir_all(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+      summary.entries += 1;
+      summary.bytes += bytes;
+    }
+    Ok(summary)
+  }
+
+  /// Returns the directory holding the entries.
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+}
+
+/// Returns the bytes of every file below `path`, counting unreadable parts as empty.
+fn tree_size(path: &Path) -> u64 {
+  let Ok(metadata) = fs::symlink_metadata(path) else {
+    return 0;
+  };
+  if !metadata.is_dir() {
+    return metadata.len();
+  }
+  fs::read_dir(path)
+    .map(|entries| {
+      entries
+        .filter_map(Result::ok)
+        .map(|entry| tree_size(&entry.path()))
+        .sum()
+    })
+    .unwrap_or(0)
+}
+
+fn copy_tree(source: &Path, destination: &Path) -> std::io::Result<()> {
+  fs::create_dir_all(destination)?;
+  for entry in fs::read_dir(source)? {
+    let entry = entry?;
+    let target = destination.join(entry.file_name());
+    if fs::metadata(entry.path())?.is_dir() {
+      copy_tree(&entry.path(), &target)?;
+    } else {
+      fs::copy(entry.path(), &target)?;
+    }
+  }
+  Ok(())
+}
+
+/// Hashes the running executable once, so a rebuilt Hull never reuses reports of an older one.
+fn executable_digest() -> &'static str {
+  static DIGEST: OnceLock<String> = OnceLock::new();
+  DIGEST.get_or_init(|| {
+    std::env::current_exe()
+      .and_then(fs::read)
+      .map(|bytes| hex(&Sha256::digest(bytes)))
+      .unwrap_or_else(|_| env!("CARGO_PKG_VERSION").to_string())
+  })
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes
+    .iter()
+    .fold(String::with_capacity(64), |mut output, byte| {
+      write!(output, "{byte:02x}").unwrap();
+      output
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keys_follow_contents_not_locations() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = directory.path().join("first");
+    let second = directory.path().join("second");
+    fs::write(&first, "1 2\n").unwrap();
+    fs::write(&second, "1 2\n").unwrap();
+    let key = |path: &Path, limit| {
+      CacheKeyBuilder::new("judge")
+        .contents("input", path)
+        .unwrap()
+        .number("tick_limit", limit)
+        .finish()
+    };
+
+    assert_eq!(key(&first, 10), key(&second, 10));
+    assert_ne!(key(&first, 10), key(&first, 11));
+    fs::write(&second, "1 3\n").unwrap();
+    assert_ne!(key(&first, 10), key(&second, 10));
+    assert_ne!(
+      CacheKeyBuilder::new("judge").text("a", "bc").finish(),
+      CacheKeyBuilder::new("judge").text("ab", "c").finish()
+    );
+  }
+
+  #[test]
+  fn stored_reports_and_outputs_are_restored() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = ResultCache::new(directory.path().join("cache"));
+    let outputs = directory.path().join("outputs");
+    fs::create_dir_all(outputs.join("nested")).unwrap();
+    fs::write(outputs.join("nested").join("output"), "3\n").unwrap();
+    let key = CacheKeyBuilder::new("judge")
+      .contents("outputs", &outputs)
+      .unwrap()
+      .finish();
+    let restored = directory.path().join("restored");
+
+    assert_eq!(cache.load::<String>(&key, Some(&restored)), None);
+    cache.store(&key, &"accepted".to_string(), Some(&outputs));
+    assert_eq!(
+      cache.load::<String>(&key, Some(&restored)).as_deref(),
+      Some("accepted")
+    );
+    assert_eq!(
+      fs::read_to_string(restored.join("nested").join("output")).unwrap(),
+      "3\n"
+    );
+    let other = CacheKeyBuilder::new("validator").finish();
+    cache.store(&other, &1u8, None);
+    assert_eq!(cache.load::<u8>(&other, None), Some(1));
+    assert_eq!(
+      cache.load::<u8>(&other, Some(&restored)),
+      None,
+      "an entry without outputs cannot serve an outputs lookup"
+    );
+  }
+
+  #[test]
+  fn clean_removes_entries_unused_for_the_given_age() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = ResultCache::new(directory.path().join("cache"));
+    assert_eq!(cache.clean(None).unwrap(), CleanSummary::default());
+
+    let stale = CacheKeyBuilder::new("validator")
+      .text("entry", "stale")
+      .finish();
+    let fresh = CacheKeyBuilder::new("validator")
+      .text("entry", "fresh")
+      .finish();
+    cache.store(&stale, &1u8, None);
+    cache.store(&fresh, &2u8, None);
+    let month_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+    for key in [&stale, &fresh] {
+      fs::File::options()
+        .write(true)
+        .open(cache.root().join(&key.0).join(REPORT_FILE))
+        .unwrap()
+        .set_modified(month_ago)
+        .unwrap();
+    }
+    // A hit counts as a use.
+    assert_eq!(cache.load::<u8>(&fresh, None), Some(2));
+
+    let week = Duration::from_secs(7 * 24 * 60 * 60);
+    let summary = cache.clean(Some(week)).unwrap();
+    assert_eq!(summary.entries, 1);
+    assert_eq!(summary.bytes, 1);
+    assert_eq!(cache.load::<u8>(&stale, None), None);
+    assert_eq!(cache.load::<u8>(&fresh, None), Some(2));
+    assert_eq!(cache.clean(None).unwrap().entries, 1);
+    assert_eq!(cache.load::<u8>(&fresh, None), None);
+  }
+
+  #[test]
+  fn clean_keeps_files_the_cache_did_not_create() {
+    let directory = tempfile::tempdir().unwrap();
+    let cache = ResultCache::new(directory.path().to_path_buf());
+    let key = CacheKeyBuilder::new("validator")
+      .text("entry", "a")
+      .finish();
+    cache.store(&key, &1u8, None);
+    fs::write(directory.path().join("notes.txt"), "keep").unwrap();
+    fs::create_dir(directory.path().join("project")).unwrap();
+    let lookalike = directory.path().join("a".repeat(64));
+    fs::write(&lookalike, "not a directory").unwrap();
+    fs::create_dir(directory.path().join(".staging-abandoned")).unwrap();
+
+    assert_eq!(cache.clean(None).unwrap().entries, 2);
+    assert_eq!(cache.load::<u8>(&key, None), None);
+    assert!(directory.path().join("notes.txt").exists());
+    assert!(directory.path().join("project").exists());
+    assert!(lookalike.exists());
+    assert!(!directory.path().join(".staging-abandoned").exists());
+  }
+}
@@ -21,10 +21,14 @@ pub mod artifact;
 pub mod build;
 /// Exported bundle judge helpers shared by judge adapters.
 pub mod bundle_judge;
+/// Persistent content-addressed cache of validator, checker, and judge reports.
+pub mod cache;
 /// Shared testcase scheduler for custom-judge adapters.
 pub mod custom_judge_scheduler;
 /// Runtime metadata loading from flakes and exported bundles.
 pub mod metadata;
+/// Subtask prediction checking while runtime analysis runs.
+pub mod prediction;
 /// Runtime data models shared by analysis and packaging.
 pub mod types;
 /// Ephemeral filesystem workspace management for runtime jobs.
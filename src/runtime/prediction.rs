@@ -0,0 +1,346 @@
+/*
+  This file is part of Hull.
+
+  Hull is free software: you can redistribute it and/or modify it under the terms of the GNU
+  Lesser General Public License as published by the Free Software Foundation, either version 3 of
+  the License, or (at your option) any later version.
+
+  Hull is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+  the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Lesser
+  General Public License for more details.
+
+  You should have received a copy of the GNU Lesser General Public License along with Hull. If
+  not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tracing::{error, info, warn};
+
+use super::types::{JudgeStatus, RuntimeOptions, RuntimeSolutionData, SolutionSpec};
+use crate::nix::{EvalCommand, get_flake_url};
+
+/// Evaluates one batch of queries, returning whether each prediction holds.
+pub type PredictionEvaluator = Box<dyn FnMut(&[PredictionQuery]) -> Result<Vec<bool>> + Send>;
+
+#[derive(Clone, Debug)]
+/// Flake output whose configuration holds the subtask prediction functions of a problem.
+pub enum PredictionSource {
+  /// A problem selected from `hullProblems`.
+  Problem(String),
+  /// A problem of a contest selected from `hullContests`.
+  ContestProblem {
+    /// Contest selector.
+    contest: String,
+    /// Problem name within the contest.
+    problem: String,
+  },
+}
+
+impl PredictionSource {
+  fn problem_config_expr(&self) -> Result<String> {
+    Ok(match self {
+      Self::Problem(problem) => {
+        format!("flake.outputs.hullProblems.${{builtins.currentSystem}}.{problem}.config")
+      }
+      Self::ContestProblem { contest, problem } => format!(
+        "runtime.contestProblemConfig flake.outputs.hullContests.${{builtins.currentSystem}}.{contest} {}",
+        serde_json::to_string(problem)?
+      ),
+    })
+  }
+
+  /// Returns an evaluator that runs the prediction functions through `nix eval`.
+  pub fn nix_evaluator(self) -> PredictionEvaluator {
+    let mut flake_ref = None;
+    Box::new(move |queries| {
+      let flake_ref = match &flake_ref {
+        Some(flake_ref) => flake_ref,
+        None => flake_ref.insert(get_flake_url()?),
+      };
+      let mut queries_file =
+        NamedTempFile::new().context("Failed to create subtask prediction query file")?;
+      queries_file
+        .write_all(&serde_json::to_vec(queries)?)
+        .context("Failed to write subtask prediction query file")?;
+      let queries_path = queries_file
+        .path()
+        .to_str()
+        .context("Subtask prediction query path contains non-UTF-8 characters")?;
+      let expr = format!(
+        r#"
+          let
+            flake = builtins.getFlake {flake_ref};
+            runtime = (flake.inputs.hull.lib or flake.outputs.lib).${{builtins.currentSystem}}.runtime;
+            queries = builtins.fromJSON (builtins.readFile (/. + {queries_path}));
+          in
+          builtins.toJSON (runtime.checkSubtaskPredictions ({problem_config}) queries)
+        "#,
+        flake_ref = serde_json::to_string(flake_ref)?,
+        queries_path = serde_json::to_string(queries_path)?,
+        problem_config = self.problem_config_expr()?,
+      );
+      let output = EvalCommand::new()
+        .impure(true)
+        .expr_stdin(&expr)
+        .run_and_capture_stdout()
+        .context("Failed to evaluate subtask predictions")?;
+      serde_json::from_str(&output).context("Failed to parse subtask prediction results")
+    })
+  }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Actual result of one fully judged subtask, matched against a solution's prediction.
+pub struct PredictionQuery {
+  /// Solution name.
+  pub solution: String,
+  /// Subtask index.
+  pub subtask: usize,
+  /// Unweighted subtask score in `[0, 1]`.
+  pub score: f64,
+  /// Distinct testcase statuses of the subtask.
+  pub statuses: Vec<JudgeStatus>,
+}
+
+#[derive(Clone, Debug)]
+/// One solution's subtask prediction together with the result it was checked against.
+pub struct PredictionOutcome {
+  /// Solution name.
+  pub solution: String,
+  /// Subtask index.
+  pub subtask: usize,
+  /// Unweighted subtask score in `[0, 1]`.
+  pub raw_score: f64,
+  /// Distinct testcase statuses of the subtask.
+  pub statuses: Vec<JudgeStatus>,
+  /// Whether the prediction holds, or `None` when it could not be checked.
+  pub holds: Option<bool>,
+}
+
+/// Checks subtask predictions on a background thread while runtime analysis keeps judging.
+///
+/// Queries submitted while an evaluation runs are batched into the next one, so a slow evaluator
+/// costs at most one evaluation per wave of completed subtasks.
+pub struct PredictionChecker {
+  sender: Sender<PredictionQuery>,
+  worker: JoinHandle<Vec<PredictionOutcome>>,
+}
+
+impl PredictionChecker {
+  /// Starts a checker that requests fail-fast cancellation through `options` on a violation.
+  pub fn spawn(mut evaluate: PredictionEvaluator, options: RuntimeOptions) -> Self {
+    let (sender, receiver) = mpsc::channel::<PredictionQuery>();
+    let worker = thread::spawn(move || {
+      let mut outcomes = Vec::new();
+      let mut evaluator_failed = false;
+      while let Ok(query) = receiver.recv() {
+        let batch = std::iter::once(query)
+          .chain(receiver.try_iter())
+          .collect::<Vec<_>>();
+        let verdicts = if evaluator_failed {
+          None
+        } else {
+          match evaluate(&batch) {
+            Ok(verdicts) if verdicts.len() == batch.len() => Some(verdicts),
+            Ok(verdicts) => {
+              warn!(
+                "Subtask prediction evaluation returned {} results for {} queries; leaving predictions to the final build",
+                verdicts.len(),
+                batch.len()
+              );
+              evaluator_failed = true;
+              None
+            }
+            Err(err) => {
+              warn!("{err:#}; leaving predictions to the final build");
+              evaluator_failed = true;
+              None
+            }
+          }
+        };
+        for (index, query) in batch.into_iter().enumerate() {
+          let holds = verdicts.as_ref().map(|verdicts| verdicts[index]);
+          match holds {
+            Some(true) => info!(
+              "Solution {} meets its prediction for subtask #{}",
+              query.solution, query.subtask
+            ),
+            Some(false) => {
+              error!(
+                "{}",
+                violation_message(&query.solution, query.subtask, query.score, &query.statuses)
+              );
+              options.request_stop();
+            }
+            None => {}
+          }
+          outcomes.push(PredictionOutcome {
+            solution: query.solution,
+            subtask: query.subtask,
+            raw_score: query.score,
+            statuses: query.statuses,
+            holds,
+          });
+        }
+      }
+      outcomes
+    });
+    Self { sender, worker }
+  }
+
+  /// Queues one completed subtask result for checking.
+  pub fn submit(&self, query: PredictionQuery) {
+    // The worker only exits once every sender is gone, so this cannot fail.
+    let _ = self.sender.send(query);
+  }
+
+  /// Waits for all queued queries and returns their outcomes.
+  pub fn finish(self) -> Vec<PredictionOutcome> {
+    drop(self.sender);
+    self.worker.join().unwrap_or_default()
+  }
+}
+
+/// Fails when any outcome shows a violated prediction.
+pub fn ensure_predictions_hold(problem_name: &str, outcomes: &[PredictionOutcome]) -> Result<()> {
+  let violations = outcomes
+    .iter()
+    .filter(|outcome| outcome.holds == Some(false))
+    .map(|outcome| {
+      format!(
+        "  - {}",
+        violation_message(
+          &outcome.solution,
+          outcome.subtask,
+          outcome.raw_score,
+          &outcome.statuses
+        )
+      )
+    })
+    .collect::<Vec<_>>();
+  if !violations.is_empty() {
+    bail!(
+      "Problem `{problem_name}` has solutions with incorrect subtask predictions:\n{}",
+      violations.join("\n")
+    );
+  }
+  Ok(())
+}
+
+/// Lists every declared prediction of `solutions`, keeping checked outcomes and adding the final
+/// results of the rest as unchecked.
+pub fn complete_prediction_outcomes(
+  checked: Vec<PredictionOutcome>,
+  solutions: &[SolutionSpec],
+  results: &BTreeMap<String, RuntimeSolutionData>,
+) -> Vec<PredictionOutcome> {
+  let mut outcomes = checked
+    .into_iter()
+    .map(|outcome| ((outcome.solution.clone(), outcome.subtask), outcome))
+    .collect::<BTreeMap<_, _>>();
+  for solution in solutions {
+    let Some(result) = results.get(&solution.name) else {
+      continue;
+    };
+    for &subtask in &BTreeSet::from_iter(&solution.subtask_predictions) {
+      let Some(report) = result.subtask_results.get(*subtask) else {
+        continue;
+      };
+      outcomes
+        .entry((solution.name.clone(), *subtask))
+        .or_insert_with(|| PredictionOutcome {
+          solution: solution.name.clone(),
+          subtask: *subtask,
+          raw_score: report.raw_score,
+          statuses: report.statuses.clone(),
+          holds: None,
+        });
+    }
+  }
+  outcomes.into_values().collect()
+}
+
+fn violation_message(
+  solution: &str,
+  subtask: usize,
+  raw_score: f64,
+  statuses: &[JudgeStatus],
+) -> String {
+  format!(
+    "Solution `{solution}` violates its prediction for subtask #{subtask} (raw score: {raw_score:.3}, statuses: [{}])",
+    statuses
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>()
+      .join(", ")
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn query(solution: &str, subtask: usize, statuses: Vec<JudgeStatus>) -> PredictionQuery {
+    PredictionQuery {
+      solution: solution.to_string(),
+      subtask,
+      score: if statuses == [JudgeStatus::Accepted] {
+        1.0
+      } else {
+        0.0
+      },
+      statuses,
+    }
+  }
+
+  #[test]
+  fn violations_request_stop() {
+    let options = RuntimeOptions::new(Some(1)).with_stop_on_failure(true);
+    let checker = PredictionChecker::spawn(
+      Box::new(|queries| Ok(queries.iter().map(|query| query.score == 1.0).collect())),
+      options.clone(),
+    );
+    checker.submit(query("std", 0, vec![JudgeStatus::Accepted]));
+    checker.submit(query("brute", 1, vec![JudgeStatus::TimeLimitExceeded]));
+    let outcomes = checker.finish();
+
+    assert!(options.should_stop());
+    assert_eq!(
+      outcomes
+        .iter()
+        .map(|outcome| (outcome.solution.as_str(), outcome.holds))
+        .collect::<Vec<_>>(),
+      vec![("std", Some(true)), ("brute", Some(false))]
+    );
+    let err = ensure_predictions_hold("aPlusB", &outcomes).unwrap_err();
+    assert!(
+      err
+        .to_string()
+        .contains("`brute` violates its prediction for subtask #1")
+    );
+  }
+
+  #[test]
+  fn failed_evaluation_leaves_predictions_unchecked() {
+    let options = RuntimeOptions::new(Some(1)).with_stop_on_failure(true);
+    let checker = PredictionChecker::spawn(
+      Box::new(|_| bail!("flake is not available")),
+      options.clone(),
+    );
+    checker.submit(query("std", 0, vec![JudgeStatus::WrongAnswer]));
+    let outcomes = checker.finish();
+
+    assert!(!options.should_stop());
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].holds, None);
+    ensure_predictions_hold("aPlusB", &outcomes).unwrap();
+  }
+}
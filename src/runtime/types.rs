@@ -20,10 +20,17 @@ use std::sync::{
   atomic::{AtomicBool, Ordering},
 };
 
+use anyhow::{Context, Result, bail};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use super::cache::ResultCache;
+use super::prediction::{PredictionOutcome, PredictionSource};
 use crate::interactive::ProblemProgressHandle;
 use crate::platform::default_parallelism;
+use crate::runner::BacktraceFrame;
+use crate::runner::IoCost;
 
 #[derive(Clone, Debug)]
 /// Runtime analysis configuration shared by build, judge, and stress commands.
@@ -36,6 +43,12 @@ pub struct RuntimeOptions {
   pub solution_names: Option<BTreeSet<String>>,
   /// Whether one failed task requests cancellation of sibling work.
   pub stop_on_failure: bool,
+  /// Persistent cache that lets unchanged validator, checker, and judge runs reuse reports.
+  pub result_cache: Option<ResultCache>,
+  /// Testcases judged by runtime analysis; the rest are only validated.
+  pub test_selection: TestSelection,
+  /// Configuration whose subtask predictions are checked as subtasks finish judging.
+  pub prediction_source: Option<PredictionSource>,
   stop_requested: Arc<AtomicBool>,
 }
 
@@ -47,6 +60,9 @@ impl RuntimeOptions {
       progress: ProblemProgressHandle::disabled(),
       solution_names: None,
       stop_on_failure: false,
+      result_cache: None,
+      test_selection: TestSelection::default(),
+      prediction_source: None,
       stop_requested: Arc::new(AtomicBool::new(false)),
     }
   }
@@ -69,6 +85,24 @@ impl RuntimeOptions {
     self
   }
 
+  /// Reuses and records reports in `result_cache` when provided.
+  pub fn with_result_cache(mut self, result_cache: Option<ResultCache>) -> Self {
+    self.result_cache = result_cache;
+    self
+  }
+
+  /// Restricts judging to the testcases chosen by `test_selection`.
+  pub fn with_test_selection(mut self, test_selection: TestSelection) -> Self {
+    self.test_selection = test_selection;
+    self
+  }
+
+  /// Checks subtask predictions from `prediction_source` during analysis when provided.
+  pub fn with_prediction_source(mut self, prediction_source: Option<PredictionSource>) -> Self {
+    self.prediction_source = prediction_source;
+    self
+  }
+
   /// Creates child options for one serial problem analysis while sharing cancellation state.
   pub fn single_job_child(&self, progress: ProblemProgressHandle) -> Self {
     Self {
@@ -76,6 +110,9 @@ impl RuntimeOptions {
       progress,
       solution_names: self.solution_names.clone(),
       stop_on_failure: self.stop_on_failure,
+      result_cache: self.result_cache.clone(),
+      test_selection: self.test_selection.clone(),
+      prediction_source: self.prediction_source.clone(),
       stop_requested: self.stop_requested.clone(),
     }
   }
@@ -93,6 +130,114 @@ impl RuntimeOptions {
   }
 }
 
+#[derive(Clone, Debug, Default)]
+/// Testcase name globs and subtask indices that narrow which testcases get judged.
+///
+/// A testcase is selected when it matches any glob, if globs are given, and belongs to any of the
+/// subtasks, if subtasks are given. An empty selection selects every testcase.
+pub struct TestSelection {
+  patterns: Vec<(String, Regex)>,
+  subtasks: BTreeSet<usize>,
+}
+
+impl TestSelection {
+  /// Builds a selection from testcase name globs, where `*` and `?` are wildcards, and subtask
+  /// indices.
+  pub fn new(
+    patterns: impl IntoIterator<Item = String>,
+    subtasks: impl IntoIterator<Item = usize>,
+  ) -> Result<Self> {
+    let patterns = patterns
+      .into_iter()
+      .map(|pattern| {
+        let expression = pattern
+          .split('*')
+          .map(|part| {
+            part
+              .split('?')
+              .map(regex::escape)
+              .collect::<Vec<_>>()
+              .join(".")
+          })
+          .collect::<Vec<_>>()
+          .join(".*");
+        let regex = Regex::new(&format!("^{expression}$"))
+          .with_context(|| format!("Invalid testcase glob `{pattern}`"))?;
+        Ok((pattern, regex))
+      })
+      .collect::<Result<_>>()?;
+    Ok(Self {
+      patterns,
+      subtasks: subtasks.into_iter().collect(),
+    })
+  }
+
+  /// Returns whether every testcase is selected.
+  pub fn is_everything(&self) -> bool {
+    self.patterns.is_empty() && self.subtasks.is_empty()
+  }
+
+  /// Returns the selected subtask indices.
+  pub fn subtasks(&self) -> &BTreeSet<usize> {
+    &self.subtasks
+  }
+
+  /// Returns whether the testcase name matches the globs.
+  pub fn matches_name(&self, name: &str) -> bool {
+    self.patterns.is_empty() || self.patterns.iter().any(|(_, regex)| regex.is_match(name))
+  }
+
+  /// Returns whether a testcase with this name and these validator-derived traits is selected.
+  pub fn selects(
+    &self,
+    problem: &ProblemSpec,
+    name: &str,
+    traits: &BTreeMap<String, bool>,
+  ) -> bool {
+    self.matches_name(name)
+      && (self.subtasks.is_empty()
+        || self.subtasks.iter().any(|&index| {
+          problem
+            .subtasks
+            .get(index)
+            .is_some_and(|subtask| subtask.contains(traits))
+        }))
+  }
+
+  /// Rejects subtask indices the problem does not have and globs that match no testcase.
+  pub fn check(&self, problem: &ProblemSpec) -> Result<()> {
+    if let Some(index) = self
+      .subtasks
+      .iter()
+      .find(|&&index| index >= problem.subtasks.len())
+    {
+      bail!(
+        "Problem `{}` has no subtask {index}; it has {} subtasks",
+        problem.name,
+        problem.subtasks.len()
+      );
+    }
+    if !self.patterns.is_empty()
+      && !problem
+        .test_cases
+        .iter()
+        .any(|test_case| self.matches_name(&test_case.name))
+    {
+      let patterns = self
+        .patterns
+        .iter()
+        .map(|(pattern, _)| format!("`{pattern}`"))
+        .collect::<Vec<_>>();
+      bail!(
+        "No testcase of problem `{}` matches {}",
+        problem.name,
+        patterns.join(", ")
+      );
+    }
+    Ok(())
+  }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 /// Resolved path metadata for one runtime artifact.
@@ -137,6 +282,9 @@ pub struct SolutionSpec {
   pub main_correct_solution: bool,
   /// Whether participant-facing packages may include this solution.
   pub participant_visibility: bool,
+  /// Indexes of the subtasks whose results this solution's configuration predicts.
+  #[serde(default)]
+  pub subtask_predictions: Vec<usize>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -159,8 +307,14 @@ pub struct TestCaseSpec {
   pub input_file: Option<String>,
   /// Per-program execution limit in deterministic ticks.
   pub tick_limit: u64,
-  /// Independent byte ceiling for linear memory and the guest stack.
+  /// Byte ceiling for linear memory, and for the guest stack unless `stack_limit` is set.
   pub memory_limit: u64,
+  /// Separate byte ceiling for the guest stack.
+  #[serde(default)]
+  pub stack_limit: Option<u64>,
+  /// Ticks charged to judged solutions for read and write hostcalls.
+  #[serde(default)]
+  pub io_cost: IoCost,
   /// Named groups containing this testcase.
   pub groups: Vec<String>,
   /// Expected subset of validator-derived traits.
@@ -169,6 +323,27 @@ pub struct TestCaseSpec {
   pub generator: Option<String>,
   /// Optional arguments passed to the generator.
   pub arguments: Option<Vec<String>>,
+  /// Explicit `random_get` seed for judged solutions, derived from `name` when absent.
+  #[serde(default)]
+  pub random_seed: Option<u64>,
+}
+
+impl TestCaseSpec {
+  /// Returns the deterministic `random_get` seed for solutions judged on this testcase.
+  ///
+  /// The derived seed is the first 13 hex digits of the name's SHA-256, which keeps it exact in
+  /// JSON tools that parse numbers as doubles.
+  pub fn random_seed(&self) -> u64 {
+    self.random_seed.unwrap_or_else(|| {
+      let digest = Sha256::digest(self.name.as_bytes());
+      u64::from_be_bytes(digest[..8].try_into().unwrap()) >> 12
+    })
+  }
+
+  /// Returns the guest stack byte ceiling for programs run on this testcase.
+  pub fn stack_limit(&self) -> u64 {
+    self.stack_limit.unwrap_or(self.memory_limit)
+  }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -183,6 +358,16 @@ pub struct SubtaskSpec {
   pub traits: BTreeMap<String, bool>,
 }
 
+impl SubtaskSpec {
+  /// Returns whether a testcase with these validator-derived traits belongs to this subtask.
+  pub fn contains(&self, traits: &BTreeMap<String, bool>) -> bool {
+    self
+      .traits
+      .iter()
+      .all(|(name, value)| traits.get(name) == Some(value))
+  }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 /// Supported subtask score aggregation methods.
@@ -235,8 +420,14 @@ pub struct ProblemSpec {
   pub name: String,
   /// Default per-program execution limit in deterministic ticks.
   pub tick_limit: u64,
-  /// Default byte ceiling used independently for linear memory and the guest stack.
+  /// Default byte ceiling for linear memory, and for the guest stack unless `stack_limit` is set.
   pub memory_limit: u64,
+  /// Default separate byte ceiling for the guest stack.
+  #[serde(default)]
+  pub stack_limit: Option<u64>,
+  /// Default ticks charged to judged solutions for read and write hostcalls.
+  #[serde(default)]
+  pub io_cost: IoCost,
   /// Logical file and cumulative pipe byte ceiling for contestant resources.
   pub file_size_limit: u64,
   /// Total score assigned to the problem.
@@ -305,8 +496,14 @@ pub struct BundleJudgeProblemSpec {
   pub name: String,
   /// Default per-program execution limit in deterministic ticks.
   pub tick_limit: u64,
-  /// Default byte ceiling used independently for linear memory and the guest stack.
+  /// Default byte ceiling for linear memory, and for the guest stack unless `stack_limit` is set.
   pub memory_limit: u64,
+  /// Default separate byte ceiling for the guest stack.
+  #[serde(default)]
+  pub stack_limit: Option<u64>,
+  /// Default ticks charged to judged solutions for read and write hostcalls.
+  #[serde(default)]
+  pub io_cost: IoCost,
   /// Logical file and cumulative pipe byte ceiling for contestant resources.
   pub file_size_limit: u64,
   /// Total score assigned to the problem.
@@ -339,12 +536,21 @@ pub struct BundleJudgeTestCaseSpec {
   pub name: String,
   /// Per-program execution limit in deterministic ticks.
   pub tick_limit: u64,
-  /// Independent byte ceiling for linear memory and the guest stack.
+  /// Byte ceiling for linear memory, and for the guest stack unless `stack_limit` is set.
   pub memory_limit: u64,
+  /// Separate byte ceiling for the guest stack.
+  #[serde(default)]
+  pub stack_limit: Option<u64>,
+  /// Ticks charged to judged solutions for read and write hostcalls.
+  #[serde(default)]
+  pub io_cost: IoCost,
   /// Named groups containing this testcase.
   pub groups: Vec<String>,
   /// Expected subset of validator-derived traits.
   pub trait_hints: BTreeMap<String, bool>,
+  /// Explicit `random_get` seed for judged solutions, derived from `name` when absent.
+  #[serde(default)]
+  pub random_seed: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -374,10 +580,15 @@ pub struct RuntimeData {
   pub checker: CheckerRuntimeData,
   /// Generated and validated testcase data indexed by name.
   pub test_cases: BTreeMap<String, RuntimeTestCaseData>,
+  /// Validated testcases left out of judging by the test selection.
+  pub skipped_test_cases: BTreeSet<String>,
   /// Validator self-test runtime data.
   pub validator: ValidatorRuntimeData,
   /// Judging results indexed by solution name.
   pub solutions: BTreeMap<String, RuntimeSolutionData>,
+  /// Declared subtask predictions of the judged solutions, ordered by solution and subtask.
+  #[serde(skip)]
+  pub subtask_predictions: Vec<PredictionOutcome>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -444,6 +655,9 @@ pub struct SubtaskRuntimeReport {
   pub raw_score: f64,
   /// Score after applying the subtask's full-score weight.
   pub scaled_score: f64,
+  /// Matching testcases left out of judging by the test selection, so the score covers only
+  /// `test_cases`.
+  pub skipped_test_cases: BTreeSet<String>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -535,6 +749,9 @@ pub struct JudgeReport {
   pub tick: u64,
   /// Peak linear-memory usage in bytes.
   pub memory: u64,
+  #[serde(default)]
+  /// Symbolized contestant frames of a runtime error, innermost first.
+  pub backtrace: Vec<BacktraceFrame>,
   #[serde(skip_deserializing)]
   /// Path containing captured contestant outputs for detailed reporting.
   pub outputs: String,
@@ -653,6 +870,82 @@ pub struct CheckerReport {
   pub evaluator_trace_stacks: Vec<serde_json::Value>,
 }
 
+#[cfg(test)]
+impl ProblemSpec {
+  /// Returns a problem with testcases `a` and `b` in one subtask, for unit tests.
+  pub(crate) fn fixture(scoring_method: ScoringMethod) -> ProblemSpec {
+    ProblemSpec {
+      name: "p".to_string(),
+      tick_limit: 1,
+      memory_limit: 1,
+      stack_limit: None,
+      io_cost: IoCost::default(),
+      file_size_limit: 1,
+      full_score: 1.0,
+      checker: ProgramSpec {
+        src: None,
+        wasm: None,
+      },
+      validator: ProgramSpec {
+        src: None,
+        wasm: None,
+      },
+      generators: BTreeMap::new(),
+      main_correct_solution: "std".to_string(),
+      judger: JudgerSpec {
+        prepare_solution_runner: ArtifactSpec {
+          path: String::new(),
+          drv_path: None,
+        },
+        generate_outputs_runner: Some(ArtifactSpec {
+          path: String::new(),
+          drv_path: None,
+        }),
+        judge_runner: ArtifactSpec {
+          path: String::new(),
+          drv_path: None,
+        },
+      },
+      test_cases: vec![
+        TestCaseSpec {
+          name: "a".to_string(),
+          input_file: None,
+          tick_limit: 1,
+          memory_limit: 1,
+          stack_limit: None,
+          io_cost: IoCost::default(),
+          groups: Vec::new(),
+          trait_hints: BTreeMap::new(),
+          generator: None,
+          arguments: None,
+          random_seed: None,
+        },
+        TestCaseSpec {
+          name: "b".to_string(),
+          input_file: None,
+          tick_limit: 1,
+          memory_limit: 1,
+          stack_limit: None,
+          io_cost: IoCost::default(),
+          groups: Vec::new(),
+          trait_hints: BTreeMap::new(),
+          generator: None,
+          arguments: None,
+          random_seed: None,
+        },
+      ],
+      subtasks: vec![SubtaskSpec {
+        full_score: 0.5,
+        scoring_method,
+        traits: BTreeMap::new(),
+      }],
+      solutions: Vec::new(),
+      checker_tests: Vec::new(),
+      validator_tests: Vec::new(),
+    }
+  }
+}
+
 fn default_json_object() -> serde_json::Value {
   serde_json::json!({})
 }
@@ -677,6 +970,49 @@ mod tests {
     assert!(err.to_string().contains("unknown variant"));
   }
 
+  #[test]
+  fn test_case_random_seed() {
+    let mut test_case = TestCaseSpec {
+      name: "random".to_string(),
+      input_file: None,
+      tick_limit: 1,
+      memory_limit: 1,
+      stack_limit: None,
+      io_cost: IoCost::default(),
+      groups: Vec::new(),
+      trait_hints: BTreeMap::new(),
+      generator: None,
+      arguments: None,
+      random_seed: None,
+    };
+    let derived = test_case.random_seed();
+    assert!(derived < 1 << 52);
+    assert_eq!(derived, test_case.random_seed());
+    test_case.name = "random-2".to_string();
+    assert_ne!(test_case.random_seed(), derived);
+    test_case.random_seed = Some(42);
+    assert_eq!(test_case.random_seed(), 42);
+  }
+
+  #[test]
+  fn test_case_io_cost_json() {
+    let test_case = |io_cost: &str| {
+      serde_json::from_str::<TestCaseSpec>(&format!(
+        r#"{{"name":"a","input_file":null,"tick_limit":1,"memory_limit":1,"groups":[],"trait_hints":{{}},"generator":null,"arguments":null{io_cost}}}"#
+      ))
+      .unwrap()
+      .io_cost
+    };
+    assert_eq!(test_case(""), IoCost::default());
+    assert_eq!(
+      test_case(r#","io_cost":{"ticks_per_call":1000,"ticks_per_byte":2}"#),
+      IoCost {
+        ticks_per_call: 1000,
+        ticks_per_byte: 2,
+      }
+    );
+  }
+
   #[test]
   fn file_error_json() {
     assert_eq!(